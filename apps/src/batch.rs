@@ -0,0 +1,329 @@
+//! Batch mode of the publishers, proving a metric at every sampled block of a range.
+//!
+//! Each sample is preflighted and proven on its own; the receipts are written to the output
+//! directory together with a [`Manifest`] listing them in block order.
+
+use std::{
+    fs,
+    future::Future,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{ensure, Context, Result};
+use futures::future::join_all;
+use risc0_zkvm::{ProverOpts, Receipt};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Semaphore;
+
+use crate::{
+    output::{self, Report},
+    profile::{self, Profile},
+    prove_with_opts, GuestInput,
+};
+
+/// Name of the manifest in the output directory.
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// Command line arguments of the batch mode, which is enabled by `--from-block`.
+#[derive(Clone, Debug, clap::Args)]
+pub struct BatchArgs {
+    /// First block of the sampled range; proves every sample instead of a single block
+    #[arg(long, requires_all = ["to_block", "step"])]
+    pub from_block: Option<u64>,
+
+    /// Last block of the sampled range
+    #[arg(long, requires = "from_block")]
+    pub to_block: Option<u64>,
+
+    /// Number of blocks between two samples
+    #[arg(long, requires = "from_block", value_parser = clap::value_parser!(u64).range(1..))]
+    pub step: Option<u64>,
+
+    /// Directory to write the receipts and the manifest to
+    #[arg(long, default_value = "receipts")]
+    pub out_dir: PathBuf,
+
+    /// Maximum number of samples preflighted or waiting for a prover at the same time
+    #[arg(long, default_value_t = 8)]
+    pub preflight_concurrency: usize,
+
+    /// Maximum number of samples proven at the same time
+    #[arg(long, default_value_t = 1)]
+    pub prove_concurrency: usize,
+
+    /// Create succinct receipts, which can be aggregated, instead of Groth16 receipts
+    #[arg(long)]
+    pub succinct: bool,
+}
+
+impl BatchArgs {
+    /// Blocks from `from_block` to `to_block`, `step` blocks apart.
+    fn blocks(&self) -> Option<Vec<u64>> {
+        let (from, to, step) = (self.from_block?, self.to_block?, self.step?);
+        Some((from..=to).step_by(step as usize).collect())
+    }
+
+    /// One sample per block of the range, for metrics of a single block.
+    ///
+    /// Returns `None` unless batch mode is enabled.
+    pub fn points(&self) -> Option<Vec<Sample>> {
+        let blocks = self.blocks()?;
+        Some(
+            blocks
+                .into_iter()
+                .map(|block| Sample {
+                    block,
+                    past_block: None,
+                })
+                .collect(),
+        )
+    }
+
+    /// One sample per pair of consecutive blocks of the range, for metrics over two blocks.
+    ///
+    /// Returns `None` unless batch mode is enabled.
+    pub fn windows(&self) -> Option<Vec<Sample>> {
+        let blocks = self.blocks()?;
+        Some(
+            blocks
+                .windows(2)
+                .map(|window| Sample {
+                    block: window[1],
+                    past_block: Some(window[0]),
+                })
+                .collect(),
+        )
+    }
+}
+
+/// Block at which a metric is proven and, for metrics over two blocks, the start of its window.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Sample {
+    pub block: u64,
+    pub past_block: Option<u64>,
+}
+
+/// Index of the receipts written by a batch.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Manifest {
+    /// Name of the guest that was proven.
+    pub guest: String,
+    /// Image ID of the guest, in hex.
+    pub image_id: String,
+    /// Proven samples, ordered by block.
+    pub entries: Vec<Entry>,
+}
+
+/// A proven sample of a [`Manifest`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Entry {
+    #[serde(flatten)]
+    pub sample: Sample,
+    /// Path of the bincode encoded receipt, relative to the manifest.
+    pub receipt: PathBuf,
+    /// Cost of the proof, unknown for manifests written before it was recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<Profile>,
+}
+
+impl Manifest {
+    /// Reads the manifest of the batch written to `dir`.
+    pub fn load(dir: &Path) -> Result<Self> {
+        let path = dir.join(MANIFEST_FILE);
+        let json = fs::read(&path).with_context(|| format!("failed to read {}", path.display()))?;
+        serde_json::from_slice(&json)
+            .with_context(|| format!("invalid manifest {}", path.display()))
+    }
+
+    /// Writes the manifest to `dir`.
+    pub fn save(&self, dir: &Path) -> Result<()> {
+        let path = dir.join(MANIFEST_FILE);
+        fs::write(&path, serde_json::to_vec_pretty(self)?)
+            .with_context(|| format!("failed to write {}", path.display()))
+    }
+
+    /// Reads the receipt of `entry`, with the manifest stored in `dir`.
+    pub fn receipt(&self, dir: &Path, entry: &Entry) -> Result<Receipt> {
+        let path = dir.join(&entry.receipt);
+        let bytes =
+            fs::read(&path).with_context(|| format!("failed to read {}", path.display()))?;
+        bincode::deserialize(&bytes).with_context(|| format!("invalid receipt {}", path.display()))
+    }
+}
+
+/// Proves the guest of `report` at every sample, writing the receipts and the manifest to the
+/// output directory, and prints the records of the proven samples.
+///
+/// `preflight` returns the guest input of a sample. At most `preflight_concurrency` samples are
+/// preflighted or waiting for a prover, and `prove_concurrency` samples are proven at the same
+/// time, so that at most that many inputs are held in memory. A failing sample does
+/// not stop the batch: it is left out of the manifest and reported once all samples are done.
+pub async fn run<F, Fut>(
+    args: &BatchArgs,
+    report: &Report,
+    elf: &'static [u8],
+    samples: Vec<Sample>,
+    preflight: F,
+) -> Result<()>
+where
+    F: Fn(Sample) -> Fut,
+    Fut: Future<Output = Result<GuestInput>>,
+{
+    ensure!(!samples.is_empty(), "block range contains no samples");
+    ensure!(
+        report.receipt_path().is_none(),
+        "batches write their receipts to --out-dir instead of --receipt"
+    );
+    ensure!(
+        args.preflight_concurrency > 0 && args.prove_concurrency > 0,
+        "concurrency must be positive"
+    );
+    fs::create_dir_all(&args.out_dir)
+        .with_context(|| format!("failed to create {}", args.out_dir.display()))?;
+
+    let opts = if args.succinct {
+        ProverOpts::succinct()
+    } else {
+        ProverOpts::groth16()
+    };
+    let preflights = Semaphore::new(args.preflight_concurrency);
+    let provers = Semaphore::new(args.prove_concurrency);
+    let results = join_all(samples.iter().map(|&sample| async {
+        let preflight_permit = preflights.acquire().await?;
+        log::info!("Preflighting block {}", sample.block);
+        let input = profile::timed(preflight(sample)).await?;
+        let proof = {
+            let _permit = provers.acquire().await?;
+            // Only release the preflight once its input is handed to a prover, so that inputs
+            // waiting for a prover are bounded as well.
+            drop(preflight_permit);
+            log::info!("Proving block {}", sample.block);
+            prove_with_opts(elf, input, opts.clone()).await?
+        };
+
+        let receipt_file = PathBuf::from(format!("{}-{}.receipt", report.guest(), sample.block));
+        let path = args.out_dir.join(&receipt_file);
+        output::write_receipt(&path, &proof.receipt)?;
+        let record = if report.is_enabled() {
+            Some(report.record(&proof, Some(path)).await?)
+        } else {
+            None
+        };
+        Ok::<_, anyhow::Error>((
+            Entry {
+                sample,
+                receipt: receipt_file,
+                profile: Some(proof.profile),
+            },
+            record,
+        ))
+    }))
+    .await;
+
+    let mut entries = Vec::with_capacity(samples.len());
+    let mut records = Vec::new();
+    for (sample, result) in samples.iter().zip(results) {
+        match result {
+            Ok((entry, record)) => {
+                entries.push(entry);
+                records.extend(record);
+            }
+            Err(err) => log::error!("Block {} failed: {err:#}", sample.block),
+        }
+    }
+    let proven = entries.len();
+    let manifest = Manifest {
+        guest: report.guest().to_string(),
+        image_id: report.image_id().to_string(),
+        entries,
+    };
+    manifest.save(&args.out_dir)?;
+    log::info!(
+        "Wrote {} receipts and the manifest to {}",
+        proven,
+        args.out_dir.display()
+    );
+    report.print(&records)?;
+    ensure!(
+        proven == samples.len(),
+        "{} of {} samples failed",
+        samples.len() - proven,
+        samples.len()
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+
+    #[derive(Parser)]
+    struct Args {
+        #[command(flatten)]
+        batch: BatchArgs,
+    }
+
+    fn parse(args: &[&str]) -> Result<BatchArgs, clap::Error> {
+        Args::try_parse_from(["publisher"].iter().chain(args)).map(|args| args.batch)
+    }
+
+    fn range(from: u64, to: u64, step: u64) -> BatchArgs {
+        parse(&[
+            "--from-block",
+            &from.to_string(),
+            "--to-block",
+            &to.to_string(),
+            "--step",
+            &step.to_string(),
+        ])
+        .unwrap()
+    }
+
+    fn point(block: u64) -> Sample {
+        Sample {
+            block,
+            past_block: None,
+        }
+    }
+
+    fn window(past_block: u64, block: u64) -> Sample {
+        Sample {
+            block,
+            past_block: Some(past_block),
+        }
+    }
+
+    #[test]
+    fn batch_mode_is_enabled_by_a_range() {
+        let args = parse(&[]).unwrap();
+        assert_eq!(args.points(), None);
+        assert_eq!(args.windows(), None);
+    }
+
+    #[test]
+    fn points_are_step_blocks_apart() {
+        let expected = vec![point(100), point(110), point(120), point(130)];
+        assert_eq!(range(100, 130, 10).points(), Some(expected));
+        // The last block is only sampled when it falls on a step.
+        let expected = vec![point(100), point(110), point(120)];
+        assert_eq!(range(100, 129, 10).points(), Some(expected));
+    }
+
+    #[test]
+    fn windows_span_consecutive_points() {
+        let expected = vec![window(100, 110), window(110, 120), window(120, 130)];
+        assert_eq!(range(100, 130, 10).windows(), Some(expected));
+        // A range of a single point has no window.
+        assert_eq!(range(100, 105, 10).windows(), Some(Vec::new()));
+    }
+
+    #[test]
+    fn incomplete_ranges_are_rejected() {
+        assert!(parse(&["--from-block", "100", "--to-block", "130"]).is_err());
+        assert!(parse(&["--to-block", "130", "--step", "10"]).is_err());
+        assert!(parse(&["--from-block", "100", "--to-block", "130", "--step", "0"]).is_err());
+    }
+}
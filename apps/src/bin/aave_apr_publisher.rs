@@ -0,0 +1,306 @@
+use alloy_primitives::{Address, U256};
+use anyhow::{ensure, Context, Result};
+use apps::{
+    batch::{self, BatchArgs},
+    check::Expected,
+    inspect::InspectArgs,
+    output::{OutputArgs, Report},
+    profile, prove, reverted,
+    units::Unit,
+    Block, GuestInput,
+};
+use clap::Parser;
+use erc20_counter_core::{
+    aave::{self, IPool, IRewardsController, Journal, AAVE_V3_POOL, AAVE_V3_REWARDS_CONTROLLER},
+    erc20::IERC20,
+    journal::MetricJournal,
+    math,
+    pricing::{self, QuoterV2, SwapPath, QUOTER_V2},
+    status::Status,
+};
+use erc20_counter_methods::{AAVE_APR_ELF, AAVE_APR_ID};
+use risc0_steel::alloy::{
+    network::EthereumWallet, providers::ProviderBuilder, signers::local::PrivateKeySigner,
+};
+use risc0_steel::{
+    ethereum::{EthEvmEnv, ETH_MAINNET_CHAIN_SPEC},
+    host::BlockNumberOrTag,
+    Contract, EvmBlockHeader,
+};
+use tracing_subscriber::EnvFilter;
+use url::Url;
+
+/// Proves the supply and borrow rates of an Aave V3 reserve.
+#[derive(Parser)]
+struct Args {
+    /// Ethereum private key
+    #[arg(long, env = "ETH_WALLET_PRIVATE_KEY")]
+    eth_wallet_private_key: PrivateKeySigner,
+
+    /// Ethereum RPC endpoint URL
+    #[arg(long, env = "ETH_RPC_URL")]
+    eth_rpc_url: Url,
+
+    /// Beacon API endpoint URL
+    ///
+    /// Steel uses a beacon block commitment instead of the execution block.
+    /// This allows proofs to be validated using the EIP-4788 beacon roots contract.
+    #[cfg(any(feature = "beacon", feature = "history"))]
+    #[arg(long, env = "BEACON_API_URL")]
+    beacon_api_url: Url,
+
+    /// Ethereum block to use as the state for the contract call
+    #[arg(long, env = "EXECUTION_BLOCK", default_value_t = BlockNumberOrTag::Parent)]
+    execution_block: BlockNumberOrTag,
+
+    /// Ethereum block to use for the beacon block commitment.
+    #[cfg(feature = "history")]
+    #[arg(long, env = "COMMITMENT_BLOCK")]
+    commitment_block: BlockNumberOrTag,
+
+    /// Address of the Aave V3 Pool
+    #[arg(long, default_value_t = AAVE_V3_POOL)]
+    pool: Address,
+
+    /// Address of the reserve asset
+    #[arg(long)]
+    asset: Address,
+
+    /// Include the incentives paid by the RewardsController
+    #[arg(long)]
+    incentives: bool,
+
+    /// Address of the Aave V3 RewardsController
+    #[arg(long, default_value_t = AAVE_V3_REWARDS_CONTROLLER)]
+    rewards_controller: Address,
+
+    /// Uniswap V3 route pricing a reward token in the reserve asset, as `token:fee:token...`
+    #[arg(long = "reward-path")]
+    reward_paths: Vec<SwapPath>,
+
+    #[command(flatten)]
+    batch: BatchArgs,
+
+    #[command(flatten)]
+    output: OutputArgs,
+
+    #[command(flatten)]
+    inspect: InspectArgs,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    // Initialize tracing. In order to view logs, run `RUST_LOG=info cargo run`
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .with_writer(std::io::stderr)
+        .init();
+    // Parse the command line arguments.
+    let args = Args::try_parse()?;
+
+    let report = Report::new(&args.output, "aave_apr", AAVE_APR_ID)
+        .rpc(&args.eth_rpc_url)
+        .input("pool", args.pool)
+        .input("asset", args.asset);
+
+    if let Some(samples) = args.batch.points() {
+        return batch::run(&args.batch, &report, AAVE_APR_ELF, samples, |sample| {
+            preflight(&args, BlockNumberOrTag::Number(sample.block))
+        })
+        .await;
+    }
+
+    let input = profile::timed(preflight(&args, args.execution_block)).await?;
+    if args.inspect.is_enabled() {
+        return args.inspect.print(&input);
+    }
+    let input_hash = input.digest();
+    let proof = prove(AAVE_APR_ELF, input).await?;
+    let receipt = &proof.receipt;
+    let journal = &receipt.journal.bytes;
+
+    // Decode and log the commitment
+    let (header, journal) = Journal::decode_with_header(journal).context("invalid journal")?;
+    ensure!(
+        header.inputHash == input_hash,
+        "journal is of another input"
+    );
+    log::info!("Aave pool: {:?}", journal.pool);
+    log::info!("Aave reserve: {:?}", journal.reserve);
+    log::info!("Aave rewards controller: {:?}", journal.rewardsController);
+    log::info!("Steel commitment: {:?}", journal.commitment);
+    report.publish(&proof).await?;
+
+    Ok(())
+}
+
+/// Preflights the reserve rates at the block and returns the input of the guest.
+async fn preflight(args: &Args, execution_block: BlockNumberOrTag) -> Result<GuestInput> {
+    // Create an alloy provider for that private key and URL.
+    let wallet = EthereumWallet::from(args.eth_wallet_private_key.clone());
+    let provider = ProviderBuilder::new()
+        .wallet(wallet)
+        .on_client(args.inspect.client(&args.eth_rpc_url));
+
+    #[cfg(feature = "beacon")]
+    log::info!("Beacon commitment to block {}", execution_block);
+    #[cfg(feature = "history")]
+    log::info!("History commitment to block {}", args.commitment_block);
+
+    let builder = EthEvmEnv::builder()
+        .provider(provider.clone())
+        .block_number_or_tag(execution_block);
+    #[cfg(any(feature = "beacon", feature = "history"))]
+    let builder = builder.beacon_api(args.beacon_api_url.clone());
+    #[cfg(feature = "history")]
+    let builder = builder.commitment_block_number_or_tag(args.commitment_block);
+
+    let mut env = builder.build().await?;
+    //  The `with_chain_spec` method is used to specify the chain configuration.
+    env = env.with_chain_spec(&ETH_MAINNET_CHAIN_SPEC);
+    let timestamp = env.header().timestamp();
+
+    let rewards_controller = args.incentives.then_some(args.rewards_controller);
+
+    // Preflight the calls to prepare the input that is required to execute the function in
+    // the guest without RPC access.
+    let outcome = async {
+        let reserve = Contract::preflight(args.pool, &mut env)
+            .call_builder(&IPool::getReserveDataCall { asset: args.asset })
+            .call()
+            .await
+            .map_err(reverted)?
+            ._0;
+        let liquidity_rate = U256::from(reserve.currentLiquidityRate);
+        let variable_borrow_rate = U256::from(reserve.currentVariableBorrowRate);
+
+        let mut incentives_aprs = [U256::ZERO; 2];
+        if let Some(rewards_controller) = rewards_controller {
+            let tokens = [reserve.aTokenAddress, reserve.variableDebtTokenAddress];
+            for (token, incentives_apr) in tokens.into_iter().zip(&mut incentives_aprs) {
+                let rewards = Contract::preflight(rewards_controller, &mut env)
+                    .call_builder(&IRewardsController::getRewardsByAssetCall { asset: token })
+                    .call()
+                    .await
+                    .map_err(reverted)?
+                    ._0;
+                let total_supply = Contract::preflight(token, &mut env)
+                    .call_builder(&IERC20::totalSupplyCall {})
+                    .call()
+                    .await
+                    .map_err(reverted)?
+                    ._0;
+
+                for reward in rewards {
+                    let path = pricing::find_route(&args.reward_paths, reward, args.asset);
+                    if path.is_none() && reward != args.asset {
+                        log::warn!("No price route for reward {reward}, skipping it");
+                        continue;
+                    }
+
+                    let data = Contract::preflight(rewards_controller, &mut env)
+                        .call_builder(&IRewardsController::getRewardsDataCall {
+                            asset: token,
+                            reward,
+                        })
+                        .call()
+                        .await
+                        .map_err(reverted)?;
+                    let reward_decimals = Contract::preflight(reward, &mut env)
+                        .call_builder(&IERC20::decimalsCall {})
+                        .call()
+                        .await
+                        .map_err(reverted)?
+                        ._0;
+                    let reward_unit = math::pow10(reward_decimals)?;
+                    let reward_price = match path {
+                        Some(path) => {
+                            Contract::preflight(QUOTER_V2, &mut env)
+                                .call_builder(&QuoterV2::quoteExactInputCall {
+                                    path: path.encode(),
+                                    amountIn: reward_unit,
+                                })
+                                .call()
+                                .await
+                                .map_err(reverted)?
+                                .amountOut
+                        }
+                        None => reward_unit,
+                    };
+                    log::info!("Reward {reward} price: {:?}", reward_price);
+
+                    let reward_apr = aave::incentive_apr(
+                        data.emissionPerSecond,
+                        data.distributionEnd,
+                        timestamp,
+                        reward_price,
+                        reward_decimals,
+                        total_supply,
+                    )?;
+                    *incentives_apr = math::add(*incentives_apr, reward_apr)?;
+                }
+            }
+        }
+        let [supply_incentives_apr, borrow_incentives_apr] = incentives_aprs;
+
+        let supply_apr = math::ray_to_wad(liquidity_rate);
+        let supply_apy = math::ray_to_wad(math::ray_apr_to_apy(liquidity_rate)?);
+        let borrow_apr = math::ray_to_wad(variable_borrow_rate);
+        let borrow_apy = math::ray_to_wad(math::ray_apr_to_apy(variable_borrow_rate)?);
+        Ok::<_, Status>((
+            supply_apr,
+            supply_apy,
+            borrow_apr,
+            borrow_apy,
+            supply_incentives_apr,
+            borrow_incentives_apr,
+        ))
+    }
+    .await;
+    let (
+        status,
+        (
+            supply_apr,
+            supply_apy,
+            borrow_apr,
+            borrow_apy,
+            supply_incentives_apr,
+            borrow_incentives_apr,
+        ),
+    ) = Status::split(outcome);
+
+    log::info!("Status: {}", status);
+    log::info!("Supply APR: {}", Unit::RATE.format(supply_apr));
+    log::info!("Supply APY: {}", Unit::RATE.format(supply_apy));
+    log::info!("Borrow APR: {}", Unit::RATE.format(borrow_apr));
+    log::info!("Borrow APY: {}", Unit::RATE.format(borrow_apy));
+    log::info!(
+        "Supply Incentives APR: {}",
+        Unit::RATE.format(supply_incentives_apr)
+    );
+    log::info!(
+        "Borrow Incentives APR: {}",
+        Unit::RATE.format(borrow_incentives_apr)
+    );
+
+    // Finally, construct the input from the environment.
+    let block = Block::of(env.header());
+    let evm_input = env.into_input().await?;
+
+    GuestInput::default()
+        .at(block)
+        .expect(
+            Expected::new(status)
+                .value("supplyApr", supply_apr)
+                .value("supplyApy", supply_apy)
+                .value("borrowApr", borrow_apr)
+                .value("borrowApy", borrow_apy)
+                .value("supplyIncentivesApr", supply_incentives_apr)
+                .value("borrowIncentivesApr", borrow_incentives_apr),
+        )
+        .write(&evm_input)?
+        .write(&args.pool)?
+        .write(&args.asset)?
+        .write(&rewards_controller)?
+        .write(&args.reward_paths)
+}
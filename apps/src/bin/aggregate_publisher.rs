@@ -0,0 +1,138 @@
+use std::path::PathBuf;
+
+use alloy_primitives::U256;
+use anyhow::{ensure, Context, Result};
+use apps::{
+    batch::Manifest,
+    check::Expected,
+    image_id,
+    output::{OutputArgs, Report},
+    prove, GuestInput,
+};
+use clap::Parser;
+use erc20_counter_core::{
+    aggregate::{self, Journal, Metric},
+    journal::MetricJournal,
+    status::Status,
+};
+use erc20_counter_methods::{AGGREGATE_ELF, AGGREGATE_ID};
+use tracing_subscriber::EnvFilter;
+
+/// Proves the minimum, maximum, mean and time weighted mean of a metric over the receipts of a
+/// batch.
+#[derive(Parser)]
+struct Args {
+    /// Directory with the succinct receipts and the manifest written by a batch
+    #[arg(long)]
+    batch_dir: PathBuf,
+
+    /// Journal value to aggregate, e.g. `compound-supply-apr` or `inflation-basis-points`
+    #[arg(long)]
+    metric: Metric,
+
+    #[command(flatten)]
+    output: OutputArgs,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    // Initialize tracing. In order to view logs, run `RUST_LOG=info cargo run`
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .with_writer(std::io::stderr)
+        .init();
+    // Parse the command line arguments.
+    let args = Args::try_parse()?;
+
+    let report = Report::new(&args.output, "aggregate", AGGREGATE_ID)
+        .input("batch_dir", args.batch_dir.display())
+        .input("metric", format!("{:?}", args.metric));
+
+    let manifest = Manifest::load(&args.batch_dir)?;
+    ensure!(
+        manifest.guest == args.metric.guest(),
+        "batch of {} does not contain {:?}",
+        manifest.guest,
+        args.metric
+    );
+    // Only receipts of the guests built with this binary are accepted.
+    let image_id = image_id(&manifest.guest).context("unknown guest")?;
+    ensure!(
+        manifest.image_id == image_id.to_string(),
+        "batch was proven with another image ID: {}",
+        manifest.image_id
+    );
+
+    let mut input = GuestInput::default();
+    let mut journals = Vec::with_capacity(manifest.entries.len());
+    let mut samples = Vec::with_capacity(manifest.entries.len());
+    for entry in &manifest.entries {
+        let receipt = manifest.receipt(&args.batch_dir, entry)?;
+        receipt
+            .verify(image_id)
+            .with_context(|| format!("invalid receipt of block {}", entry.sample.block))?;
+        // Groth16 receipts cannot be used as assumptions.
+        ensure!(
+            receipt.inner.succinct().is_ok(),
+            "receipt of block {} is not succinct, prove the batch with --succinct",
+            entry.sample.block
+        );
+        let journal = receipt.journal.bytes.clone();
+        samples.push(
+            args.metric
+                .decode(&journal)
+                .with_context(|| format!("invalid journal of block {}", entry.sample.block))?,
+        );
+        journals.push(journal);
+        input = input.assume(receipt);
+    }
+    aggregate::check_order(&samples).map_err(anyhow::Error::msg)?;
+    let summary = aggregate::summarize(&samples);
+    match summary {
+        Ok(summary) => log::info!("Expected summary: {:?}", summary),
+        Err(status) => log::warn!("Status: {}", status),
+    }
+    let (status, summary) = Status::split(summary);
+    let skipped = samples.iter().filter(|sample| !sample.is_ok()).count();
+
+    let input = input
+        .expect(
+            Expected::new(status)
+                .value("samples", U256::from(samples.len()))
+                .value("skipped", U256::from(skipped))
+                .value("min", summary.min)
+                .value("max", summary.max)
+                .value("mean", summary.mean)
+                .value("timeWeightedMean", summary.time_weighted_mean),
+        )
+        .write(&image_id)?
+        .write(&args.metric)?
+        .write(&journals)?;
+    let input_hash = input.digest();
+    let proof = prove(AGGREGATE_ELF, input).await?;
+    let receipt = &proof.receipt;
+
+    // Decode and log the aggregated journal
+    let (header, journal) =
+        Journal::decode_with_header(&receipt.journal.bytes).context("invalid journal")?;
+    ensure!(
+        header.inputHash == input_hash,
+        "journal is of another input"
+    );
+    log::info!("Subject: {}", journal.subject);
+    for commitment in &journal.commitments {
+        log::info!("Commitment: {:?}", commitment);
+    }
+    log::info!(
+        "Samples: {}, skipped: {}, min: {}, max: {}, mean: {}, time weighted mean: {}",
+        journal.samples,
+        journal.skipped,
+        journal.min,
+        journal.max,
+        journal.mean,
+        journal.timeWeightedMean
+    );
+    report.publish(&proof).await?;
+
+    Ok(())
+}
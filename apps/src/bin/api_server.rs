@@ -0,0 +1,554 @@
+use std::{
+    collections::HashMap,
+    fs,
+    net::SocketAddr,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use alloy_primitives::Bytes;
+use anyhow::{Context, Result};
+use apps::{
+    output::Record,
+    runner::{Retry, Run, Runner, Task},
+};
+use axum::{
+    extract::{Path, Request, State},
+    http::{header, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use clap::Parser;
+use risc0_ethereum_contracts::encode_seal;
+use serde::{Deserialize, Serialize};
+use tokio::{net::TcpListener, sync::Semaphore};
+use tracing_subscriber::EnvFilter;
+use url::Url;
+
+/// HTTP API to request proofs of metrics and fetch them once proven.
+///
+/// Every job runs the publisher of its metric with the runner of the scheduler daemon, with the
+/// RPC endpoint, prover and wallet of the server. Jobs are kept in memory until they finish, and
+/// then in the state directory next to their receipts, where they are found again after a restart.
+#[derive(Parser)]
+struct Args {
+    /// Address to listen on
+    #[arg(long, default_value = "127.0.0.1:8080")]
+    listen: SocketAddr,
+
+    /// Ethereum RPC endpoint URL the metrics are computed from
+    #[arg(long, env = "ETH_RPC_URL")]
+    eth_rpc_url: Url,
+
+    /// Directory to write the receipts to
+    #[arg(long, default_value = "api")]
+    state_dir: PathBuf,
+
+    /// Directory of the publishers; defaults to the directory of this executable
+    #[arg(long)]
+    bin_dir: Option<PathBuf>,
+
+    /// Maximum number of jobs proven at the same time
+    #[arg(long, default_value_t = 1)]
+    concurrency: usize,
+
+    /// Attempts of a job, including the first one
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    attempts: u32,
+
+    /// API key the clients must send in the `x-api-key` header; any client is served if unset
+    #[arg(long, env = "API_KEY")]
+    api_key: Option<String>,
+}
+
+/// Request of a proof of a metric.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JobReq {
+    /// Name of the guest, such as `compound_apr`.
+    metric: String,
+    /// Execution block to compute the metric at, passed as `--execution-block`.
+    #[serde(default)]
+    block: Option<String>,
+    /// Further command line arguments of the publisher, such as the markets to measure, among
+    /// [`CLIENT_OPTIONS`] and [`CLIENT_SWITCHES`].
+    #[serde(default)]
+    args: Vec<String>,
+}
+
+/// Options of the publishers identifying what is measured, which clients may set. Everything else,
+/// such as the blocks of a batch or where receipts are written, is up to the server.
+const CLIENT_OPTIONS: &[&str] = &[
+    "past-execution-block",
+    "token-contract",
+    "account",
+    "account-2",
+    "comet",
+    "morpho",
+    "morpho-market",
+    "pool",
+    "asset",
+    "rewards-controller",
+    "reward-path",
+    "aave-pool",
+    "aave-asset",
+    "vault",
+    "steth",
+    "gauge",
+    "gauge-controller",
+    "base-token",
+    "holding",
+    "numeraire",
+    "price-path",
+];
+
+/// Flags of the publishers, taking no value, which clients may set.
+const CLIENT_SWITCHES: &[&str] = &["incentives"];
+
+/// Checks that `args` only set [`CLIENT_OPTIONS`] and [`CLIENT_SWITCHES`], and binds every value
+/// to its option as `--<option>=<value>`, so that no value is taken for an option of its own.
+fn client_args(args: Vec<String>) -> Result<Vec<String>, ApiError> {
+    let invalid = |message: String| ApiError(StatusCode::BAD_REQUEST, message);
+    let mut args = args.into_iter();
+    let mut checked = Vec::new();
+    while let Some(arg) = args.next() {
+        let Some(option) = arg.strip_prefix("--") else {
+            return Err(invalid(format!("unexpected argument {arg}")));
+        };
+        let (name, value) = match option.split_once('=') {
+            Some((name, value)) => (name, Some(value.to_string())),
+            None => (option, None),
+        };
+        if CLIENT_SWITCHES.contains(&name) {
+            if value.is_some() {
+                return Err(invalid(format!("--{name} takes no value")));
+            }
+            checked.push(format!("--{name}"));
+            continue;
+        }
+        if !CLIENT_OPTIONS.contains(&name) {
+            return Err(invalid(format!("--{name} is not allowed")));
+        }
+        let value = match value {
+            Some(value) => value,
+            None => args
+                .next()
+                .ok_or_else(|| invalid(format!("--{name} has no value")))?,
+        };
+        checked.push(format!("--{name}={value}"));
+    }
+    Ok(checked)
+}
+
+/// State of a job.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+enum Job {
+    Queued,
+    Running,
+    /// Proven, with its receipt in the state directory.
+    Succeeded {
+        attempts: u32,
+        record: Box<Record>,
+    },
+    Failed(String),
+}
+
+impl Job {
+    fn status(&self) -> &'static str {
+        match self {
+            Self::Queued => "queued",
+            Self::Running => "running",
+            Self::Succeeded { .. } => "succeeded",
+            Self::Failed(_) => "failed",
+        }
+    }
+}
+
+struct Server {
+    runner: Runner,
+    eth_rpc_url: Url,
+    state_dir: PathBuf,
+    retry: Retry,
+    api_key: Option<String>,
+    /// Permits of the jobs proven at the same time.
+    provers: Semaphore,
+    next_id: Mutex<u64>,
+    /// Metric and state of the jobs that did not finish yet.
+    jobs: Mutex<HashMap<u64, (String, Job)>>,
+}
+
+/// Finished job, as written to the state directory.
+#[derive(Serialize, Deserialize)]
+struct FinishedJob {
+    metric: String,
+    job: Job,
+}
+
+impl Server {
+    fn job_path(&self, id: u64) -> PathBuf {
+        self.state_dir.join(format!("{id}.json"))
+    }
+
+    fn receipt_path(&self, id: u64) -> PathBuf {
+        self.state_dir.join(format!("{id}.bin"))
+    }
+
+    fn job(&self, id: u64) -> Result<(String, Job), ApiError> {
+        if let Some(job) = self.jobs.lock().unwrap().get(&id) {
+            return Ok(job.clone());
+        }
+        let json = fs::read(self.job_path(id))
+            .map_err(|_| ApiError(StatusCode::NOT_FOUND, format!("unknown job {id}")))?;
+        let finished: FinishedJob = serde_json::from_slice(&json).map_err(|err| {
+            ApiError(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("invalid state of job {id}: {err}"),
+            )
+        })?;
+        Ok((finished.metric, finished.job))
+    }
+
+    /// Run of job `id`, if it succeeded.
+    fn run(&self, id: u64) -> Result<Run, ApiError> {
+        match self.job(id)?.1 {
+            Job::Succeeded { attempts, record } => {
+                let receipt = fs::read(self.receipt_path(id))
+                    .map_err(anyhow::Error::from)
+                    .and_then(|bytes| Ok(bincode::deserialize(&bytes)?))
+                    .map_err(|err| {
+                        ApiError(
+                            StatusCode::INTERNAL_SERVER_ERROR,
+                            format!("invalid receipt of job {id}: {err:#}"),
+                        )
+                    })?;
+                Ok(Run {
+                    record: *record,
+                    receipt,
+                    attempts,
+                })
+            }
+            job => Err(ApiError(
+                StatusCode::CONFLICT,
+                format!("job {id} is {}", job.status()),
+            )),
+        }
+    }
+
+    fn set(&self, id: u64, job: Job) {
+        if let Some((_, state)) = self.jobs.lock().unwrap().get_mut(&id) {
+            *state = job;
+        }
+    }
+
+    /// Moves the finished job `id` from memory to the state directory, or keeps it in memory if
+    /// it cannot be written.
+    ///
+    /// The jobs stay locked until the job is written, so that it is always found in either.
+    fn finish(&self, id: u64, job: Job) {
+        let mut jobs = self.jobs.lock().unwrap();
+        let Some((metric, state)) = jobs.get_mut(&id) else {
+            return;
+        };
+        let finished = FinishedJob {
+            metric: metric.clone(),
+            job,
+        };
+        let path = self.job_path(id);
+        let written = serde_json::to_vec(&finished)
+            .map_err(anyhow::Error::from)
+            .and_then(|json| Ok(fs::write(&path, json)?));
+        match written {
+            Ok(()) => {
+                jobs.remove(&id);
+            }
+            Err(err) => {
+                log::error!("Failed to write {}: {err:#}", path.display());
+                *state = finished.job;
+            }
+        }
+    }
+}
+
+/// Largest id of the jobs in `state_dir`, so that new jobs do not take over their files.
+fn last_job_id(state_dir: &std::path::Path) -> Result<u64> {
+    let mut last_id = 0;
+    for entry in fs::read_dir(state_dir)? {
+        let path = entry?.path();
+        if path
+            .extension()
+            .is_some_and(|extension| extension == "json")
+        {
+            let id = path
+                .file_stem()
+                .and_then(|stem| stem.to_str()?.parse().ok());
+            last_id = last_id.max(id.unwrap_or(0));
+        }
+    }
+    Ok(last_id)
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    // Initialize tracing. In order to view logs, run `RUST_LOG=info cargo run`
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .init();
+    // Parse the command line arguments.
+    let args = Args::parse();
+
+    fs::create_dir_all(&args.state_dir)
+        .with_context(|| format!("failed to create {}", args.state_dir.display()))?;
+    let last_id = last_job_id(&args.state_dir)
+        .with_context(|| format!("failed to read {}", args.state_dir.display()))?;
+    let server = Arc::new(Server {
+        runner: Runner::new(args.bin_dir)?,
+        eth_rpc_url: args.eth_rpc_url,
+        state_dir: args.state_dir,
+        retry: Retry {
+            attempts: args.attempts,
+            ..Retry::default()
+        },
+        api_key: args.api_key,
+        provers: Semaphore::new(args.concurrency.max(1)),
+        next_id: Mutex::new(last_id),
+        jobs: Mutex::default(),
+    });
+
+    let app = Router::new()
+        .route("/jobs", post(create_job))
+        .route("/jobs/{id}", get(job_status))
+        .route("/jobs/{id}/receipt", get(receipt))
+        .route("/jobs/{id}/seal", get(seal))
+        .route("/jobs/{id}/journal", get(journal))
+        .layer(middleware::from_fn_with_state(server.clone(), authorize))
+        .with_state(server);
+
+    let listener = TcpListener::bind(args.listen).await?;
+    log::info!("API listening on {}", args.listen);
+    axum::serve(listener, app).await?;
+
+    Ok(())
+}
+
+async fn authorize(State(server): State<Arc<Server>>, request: Request, next: Next) -> Response {
+    if let Some(api_key) = &server.api_key {
+        let key = request.headers().get("x-api-key");
+        if key.map(|key| key.as_bytes()) != Some(api_key.as_bytes()) {
+            return StatusCode::UNAUTHORIZED.into_response();
+        }
+    }
+    next.run(request).await
+}
+
+/// Error of a request, answered with its message.
+#[derive(Debug)]
+struct ApiError(StatusCode, String);
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, self.1).into_response()
+    }
+}
+
+#[derive(Serialize)]
+struct CreateRes {
+    id: u64,
+}
+
+/// Queues a job proving the requested metric.
+async fn create_job(
+    State(server): State<Arc<Server>>,
+    Json(request): Json<JobReq>,
+) -> Result<(StatusCode, Json<CreateRes>), ApiError> {
+    let mut task = Task::of_metric(&request.metric).ok_or_else(|| {
+        ApiError(
+            StatusCode::BAD_REQUEST,
+            format!("unknown metric {}", request.metric),
+        )
+    })?;
+    server
+        .runner
+        .check(&task)
+        .map_err(|err| ApiError(StatusCode::BAD_REQUEST, format!("{err:#}")))?;
+    let args = client_args(request.args)?;
+    task.eth_rpc_url = Some(server.eth_rpc_url.clone());
+    if let Some(block) = request.block {
+        task.args.push(format!("--execution-block={block}"));
+    }
+    task.args.extend(args);
+
+    let id = {
+        let mut next_id = server.next_id.lock().unwrap();
+        *next_id += 1;
+        *next_id
+    };
+    server
+        .jobs
+        .lock()
+        .unwrap()
+        .insert(id, (request.metric.clone(), Job::Queued));
+    log::info!("Job {id} of {} queued", request.metric);
+
+    let task_server = server.clone();
+    tokio::spawn(async move {
+        let server = task_server;
+        let Ok(_permit) = server.provers.acquire().await else {
+            return;
+        };
+        server.set(id, Job::Running);
+        let receipt = server.receipt_path(id);
+        let job = match server
+            .runner
+            .run_with_retries(&task, &receipt, &server.retry)
+            .await
+        {
+            Ok(run) => Job::Succeeded {
+                attempts: run.attempts,
+                record: Box::new(run.record),
+            },
+            Err(err) => Job::Failed(format!("{err:#}")),
+        };
+        log::info!("Job {id} {}", job.status());
+        server.finish(id, job);
+    });
+
+    Ok((StatusCode::ACCEPTED, Json(CreateRes { id })))
+}
+
+#[derive(Serialize)]
+struct JobStatusRes {
+    id: u64,
+    metric: String,
+    status: &'static str,
+    error: Option<String>,
+    attempts: Option<u32>,
+    /// Decoded journal, once proven.
+    record: Option<Record>,
+}
+
+async fn job_status(
+    State(server): State<Arc<Server>>,
+    Path(id): Path<u64>,
+) -> Result<Json<JobStatusRes>, ApiError> {
+    let (metric, job) = server.job(id)?;
+    let mut res = JobStatusRes {
+        id,
+        metric,
+        status: job.status(),
+        error: None,
+        attempts: None,
+        record: None,
+    };
+    match job {
+        Job::Queued | Job::Running => {}
+        Job::Succeeded { attempts, record } => {
+            res.attempts = Some(attempts);
+            res.record = Some(*record);
+        }
+        Job::Failed(error) => res.error = Some(error),
+    }
+    Ok(Json(res))
+}
+
+/// Bincode encoded receipt of a proven job.
+async fn receipt(
+    State(server): State<Arc<Server>>,
+    Path(id): Path<u64>,
+) -> Result<Response, ApiError> {
+    let run = server.run(id)?;
+    let receipt = bincode::serialize(&run.receipt)
+        .map_err(|err| ApiError(StatusCode::INTERNAL_SERVER_ERROR, err.to_string()))?;
+    Ok((
+        [(header::CONTENT_TYPE, "application/octet-stream")],
+        receipt,
+    )
+        .into_response())
+}
+
+#[derive(Serialize)]
+struct SealRes {
+    /// ABI encoded seal, as verified by the RISC Zero verifier contracts.
+    seal: Bytes,
+    /// ABI encoded journal.
+    journal: Bytes,
+    image_id: String,
+}
+
+/// Seal and journal of a proven job, to be submitted on-chain.
+async fn seal(
+    State(server): State<Arc<Server>>,
+    Path(id): Path<u64>,
+) -> Result<Json<SealRes>, ApiError> {
+    let run = server.run(id)?;
+    let seal = encode_seal(&run.receipt)
+        .map_err(|err| ApiError(StatusCode::UNPROCESSABLE_ENTITY, format!("{err:#}")))?;
+    Ok(Json(SealRes {
+        seal: seal.into(),
+        journal: run.receipt.journal.bytes.into(),
+        image_id: run.record.image_id,
+    }))
+}
+
+/// Decoded journal of a proven job.
+async fn journal(
+    State(server): State<Arc<Server>>,
+    Path(id): Path<u64>,
+) -> Result<Json<Record>, ApiError> {
+    Ok(Json(server.run(id)?.record))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn client_options_are_bound_to_their_values() {
+        let checked = client_args(args(&[
+            "--comet",
+            "0xc3d688B66703497DAA19211EEdff47f25384cdc3",
+            "--incentives",
+            "--price-path=0x01",
+        ]))
+        .unwrap();
+        assert_eq!(
+            checked,
+            args(&[
+                "--comet=0xc3d688B66703497DAA19211EEdff47f25384cdc3",
+                "--incentives",
+                "--price-path=0x01",
+            ])
+        );
+        // A value looking like an option is still bound to the option before it.
+        assert_eq!(
+            client_args(args(&["--pool", "--receipt"])).unwrap(),
+            args(&["--pool=--receipt"])
+        );
+    }
+
+    #[test]
+    fn server_options_are_rejected() {
+        for arg in [
+            "--from-block=1",
+            "--to-block=2",
+            "--step=1",
+            "--out-dir=/tmp",
+            "--inspect",
+            "--succinct",
+            "--receipt=/tmp/receipt.bin",
+            "--output=json",
+            "--eth-rpc-url=http://localhost",
+            "--execution-block=1",
+            "--incentives=false",
+            "0x01",
+        ] {
+            assert!(client_args(args(&[arg])).is_err(), "{arg}");
+        }
+        assert!(client_args(args(&["--pool"])).is_err());
+    }
+}
@@ -0,0 +1,267 @@
+use alloy_primitives::Address;
+use anyhow::{ensure, Context, Result};
+use apps::{
+    batch::{self, BatchArgs},
+    check::Expected,
+    inspect::InspectArgs,
+    output::{OutputArgs, Report},
+    profile, prove, reverted,
+    units::{self, Denomination},
+    Block, GuestInput,
+};
+use clap::Parser;
+use erc20_counter_core::{
+    compound::{self, CometMainInterface, RevenueJournal, CUSDC_V3},
+    journal::MetricJournal,
+    math::{self, MathError},
+    status::Status,
+};
+use erc20_counter_methods::{COMET_REVENUE_ELF, COMET_REVENUE_ID};
+use risc0_steel::alloy::{
+    network::EthereumWallet, providers::ProviderBuilder, signers::local::PrivateKeySigner,
+};
+use risc0_steel::{
+    ethereum::{EthEvmEnv, ETH_MAINNET_CHAIN_SPEC},
+    host::BlockNumberOrTag,
+    Contract, EvmBlockHeader,
+};
+use tracing_subscriber::EnvFilter;
+use url::Url;
+
+/// Proves the reserve growth and interest revenue of a Comet market between two blocks.
+#[derive(Parser)]
+struct Args {
+    /// Ethereum private key
+    #[arg(long, env = "ETH_WALLET_PRIVATE_KEY")]
+    eth_wallet_private_key: PrivateKeySigner,
+
+    /// Ethereum RPC endpoint URL
+    #[arg(long, env = "ETH_RPC_URL")]
+    eth_rpc_url: Url,
+
+    /// Beacon API endpoint URL
+    ///
+    /// Steel uses a beacon block commitment instead of the execution block.
+    /// This allows proofs to be validated using the EIP-4788 beacon roots contract.
+    #[cfg(any(feature = "beacon", feature = "history"))]
+    #[arg(long, env = "BEACON_API_URL")]
+    beacon_api_url: Url,
+
+    /// Ethereum block to use as the state for the contract call
+    #[arg(long, env = "EXECUTION_BLOCK", default_value_t = BlockNumberOrTag::Parent)]
+    execution_block: BlockNumberOrTag,
+
+    /// Ethereum block at the start of the measured window
+    #[arg(
+        long,
+        env = "PAST_EXECUTION_BLOCK",
+        required_unless_present = "from_block"
+    )]
+    past_execution_block: Option<BlockNumberOrTag>,
+
+    /// Ethereum block to use for the beacon block commitment.
+    #[cfg(feature = "history")]
+    #[arg(long, env = "COMMITMENT_BLOCK")]
+    commitment_block: BlockNumberOrTag,
+
+    /// Address of the Comet market
+    #[arg(long, default_value_t = CUSDC_V3)]
+    comet: Address,
+
+    #[command(flatten)]
+    batch: BatchArgs,
+
+    #[command(flatten)]
+    output: OutputArgs,
+
+    #[command(flatten)]
+    inspect: InspectArgs,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    // Initialize tracing. In order to view logs, run `RUST_LOG=info cargo run`
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .with_writer(std::io::stderr)
+        .init();
+    // Parse the command line arguments.
+    let args = Args::try_parse()?;
+
+    let report = Report::new(&args.output, "comet_revenue", COMET_REVENUE_ID)
+        .rpc(&args.eth_rpc_url)
+        .input("comet", args.comet);
+
+    if let Some(samples) = args.batch.windows() {
+        return batch::run(&args.batch, &report, COMET_REVENUE_ELF, samples, |sample| {
+            let past_block = sample.past_block.expect("windows have a past block");
+            preflight(
+                &args,
+                BlockNumberOrTag::Number(sample.block),
+                BlockNumberOrTag::Number(past_block),
+            )
+        })
+        .await;
+    }
+
+    let past_execution_block = args
+        .past_execution_block
+        .context("missing past execution block")?;
+    let input =
+        profile::timed(preflight(&args, args.execution_block, past_execution_block)).await?;
+    if args.inspect.is_enabled() {
+        return args.inspect.print(&input);
+    }
+    let input_hash = input.digest();
+    let proof = prove(COMET_REVENUE_ELF, input).await?;
+    let receipt = &proof.receipt;
+    let journal = &receipt.journal.bytes;
+
+    // Decode and log the commitment
+    let (header, journal) =
+        RevenueJournal::decode_with_header(journal).context("invalid journal")?;
+    ensure!(
+        header.inputHash == input_hash,
+        "journal is of another input"
+    );
+    log::info!("Comet: {:?}", journal.comet);
+    log::info!("Steel commitment: {:?}", journal.commitment);
+    log::info!("Past Steel commitment: {:?}", journal.pastCommitment);
+    report.publish(&proof).await?;
+
+    Ok(())
+}
+
+/// Preflights the reserves and totals at both blocks and returns the input of the guest.
+async fn preflight(
+    args: &Args,
+    execution_block: BlockNumberOrTag,
+    past_execution_block: BlockNumberOrTag,
+) -> Result<GuestInput> {
+    // Create an alloy provider for that private key and URL.
+    let wallet = EthereumWallet::from(args.eth_wallet_private_key.clone());
+    let provider = ProviderBuilder::new()
+        .wallet(wallet)
+        .on_client(args.inspect.client(&args.eth_rpc_url));
+
+    #[cfg(feature = "beacon")]
+    log::info!("Beacon commitment to block {}", execution_block);
+    #[cfg(feature = "history")]
+    log::info!("History commitment to block {}", args.commitment_block);
+
+    let builder = EthEvmEnv::builder()
+        .provider(provider.clone())
+        .block_number_or_tag(execution_block);
+    let past_builder = EthEvmEnv::builder()
+        .provider(provider.clone())
+        .block_number_or_tag(past_execution_block);
+
+    #[cfg(any(feature = "beacon", feature = "history"))]
+    let builder = builder.beacon_api(args.beacon_api_url.clone());
+    #[cfg(any(feature = "beacon", feature = "history"))]
+    let past_builder = past_builder.beacon_api(args.beacon_api_url.clone());
+    #[cfg(feature = "history")]
+    let builder = builder.commitment_block_number_or_tag(args.commitment_block);
+    #[cfg(feature = "history")]
+    let past_builder = past_builder.commitment_block_number_or_tag(args.commitment_block);
+
+    let mut env = builder.build().await?;
+    let mut past_env = past_builder.build().await?;
+    //  The `with_chain_spec` method is used to specify the chain configuration.
+    env = env.with_chain_spec(&ETH_MAINNET_CHAIN_SPEC);
+    past_env = past_env.with_chain_spec(&ETH_MAINNET_CHAIN_SPEC);
+
+    ensure!(
+        past_env.header().timestamp() < env.header().timestamp(),
+        "past block must precede the execution block"
+    );
+
+    let outcome = async {
+        ///// Present reserves
+        let mut comet_contract = Contract::preflight(args.comet, &mut env);
+        let reserves = comet_contract
+            .call_builder(&CometMainInterface::getReservesCall {})
+            .call()
+            .await
+            .map_err(reverted)?
+            ._0;
+        let totals = comet_contract
+            .call_builder(&CometMainInterface::totalsBasicCall {})
+            .call()
+            .await
+            .map_err(reverted)?
+            ._0;
+
+        ///// Past reserves
+        let mut comet_contract = Contract::preflight(args.comet, &mut past_env);
+        let past_reserves = comet_contract
+            .call_builder(&CometMainInterface::getReservesCall {})
+            .call()
+            .await
+            .map_err(reverted)?
+            ._0;
+        let past_totals = comet_contract
+            .call_builder(&CometMainInterface::totalsBasicCall {})
+            .call()
+            .await
+            .map_err(reverted)?
+            ._0;
+
+        // Borrowers pay `borrow_interest`, of which suppliers receive `supply_interest`; the
+        // protocol keeps the difference.
+        let (borrow_interest, supply_interest) = compound::interest_between(&past_totals, &totals)?;
+        let reserve_growth = reserves
+            .checked_sub(past_reserves)
+            .ok_or(MathError::Underflow)?;
+        let interest_revenue = math::signed_change(supply_interest, borrow_interest)?;
+        Ok::<_, Status>((
+            reserves,
+            past_reserves,
+            reserve_growth,
+            borrow_interest,
+            supply_interest,
+            interest_revenue,
+        ))
+    }
+    .await;
+    let (
+        status,
+        (
+            reserves,
+            past_reserves,
+            reserve_growth,
+            borrow_interest,
+            supply_interest,
+            interest_revenue,
+        ),
+    ) = Status::split(outcome);
+
+    let base = units::token(&args.eth_rpc_url, Denomination::CometBase(args.comet)).await;
+    log::info!("Status: {}", status);
+    log::info!("Reserves: {}", base.format(reserves));
+    log::info!("Past Reserves: {}", base.format(past_reserves));
+    log::info!("Reserve Growth: {}", base.format(reserve_growth));
+    log::info!("Borrow Interest: {}", base.format(borrow_interest));
+    log::info!("Supply Interest: {}", base.format(supply_interest));
+    log::info!("Interest Revenue: {}", base.format(interest_revenue));
+
+    // Finally, construct the input from the environment.
+    let block = Block::of(env.header());
+    let evm_input = env.into_input().await?;
+    let past_evm_input = past_env.into_input().await?;
+
+    GuestInput::default()
+        .at(block)
+        .expect(
+            Expected::new(status)
+                .value("reserves", reserves)
+                .value("pastReserves", past_reserves)
+                .value("reserveGrowth", reserve_growth)
+                .value("borrowInterest", borrow_interest)
+                .value("supplyInterest", supply_interest)
+                .value("interestRevenue", interest_revenue),
+        )
+        .write(&evm_input)?
+        .write(&past_evm_input)?
+        .write(&args.comet)
+}
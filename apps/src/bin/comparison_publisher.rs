@@ -0,0 +1,287 @@
+use alloy_primitives::{Address, B256, U256};
+use anyhow::{ensure, Context, Result};
+use apps::{
+    batch::{self, BatchArgs},
+    check::Expected,
+    inspect::InspectArgs,
+    output::{OutputArgs, Report},
+    profile, prove, reverted,
+    units::Unit,
+    Block, GuestInput,
+};
+use clap::Parser;
+use erc20_counter_core::{
+    aave::{IPool, AAVE_V3_POOL},
+    comparison::{self, Journal, SupplyMarket},
+    compound::CometMainInterface,
+    journal::MetricJournal,
+    math,
+    morpho::{self, IIrm, IMorpho, MORPHO_BLUE},
+    status::Status,
+    SECONDS_PER_YEAR,
+};
+use erc20_counter_methods::{COMPARISON_ELF, COMPARISON_ID};
+use risc0_steel::alloy::{
+    network::EthereumWallet, providers::ProviderBuilder, signers::local::PrivateKeySigner,
+};
+use risc0_steel::{
+    ethereum::{EthEvmEnv, ETH_MAINNET_CHAIN_SPEC},
+    host::BlockNumberOrTag,
+    Contract,
+};
+use tracing_subscriber::EnvFilter;
+use url::Url;
+
+/// Proves a ranking of lending markets of Compound III, Morpho Blue and Aave V3 by supply APR.
+#[derive(Parser)]
+struct Args {
+    /// Ethereum private key
+    #[arg(long, env = "ETH_WALLET_PRIVATE_KEY")]
+    eth_wallet_private_key: PrivateKeySigner,
+
+    /// Ethereum RPC endpoint URL
+    #[arg(long, env = "ETH_RPC_URL")]
+    eth_rpc_url: Url,
+
+    /// Beacon API endpoint URL
+    ///
+    /// Steel uses a beacon block commitment instead of the execution block.
+    /// This allows proofs to be validated using the EIP-4788 beacon roots contract.
+    #[cfg(any(feature = "beacon", feature = "history"))]
+    #[arg(long, env = "BEACON_API_URL")]
+    beacon_api_url: Url,
+
+    /// Ethereum block to use as the state for the contract call
+    #[arg(long, env = "EXECUTION_BLOCK", default_value_t = BlockNumberOrTag::Parent)]
+    execution_block: BlockNumberOrTag,
+
+    /// Ethereum block to use for the beacon block commitment.
+    #[cfg(feature = "history")]
+    #[arg(long, env = "COMMITMENT_BLOCK")]
+    commitment_block: BlockNumberOrTag,
+
+    /// Address of a Compound III (Comet) market to compare
+    #[arg(long = "comet")]
+    comets: Vec<Address>,
+
+    /// Id of a Morpho Blue market to compare
+    #[arg(long = "morpho-market")]
+    morpho_markets: Vec<B256>,
+
+    /// Address of Morpho Blue
+    #[arg(long, default_value_t = MORPHO_BLUE)]
+    morpho: Address,
+
+    /// Asset of an Aave V3 reserve to compare
+    #[arg(long = "aave-asset")]
+    aave_assets: Vec<Address>,
+
+    /// Address of the Aave V3 Pool
+    #[arg(long, default_value_t = AAVE_V3_POOL)]
+    aave_pool: Address,
+
+    #[command(flatten)]
+    batch: BatchArgs,
+
+    #[command(flatten)]
+    output: OutputArgs,
+
+    #[command(flatten)]
+    inspect: InspectArgs,
+}
+
+impl Args {
+    /// Compared markets, in the order committed for ties: Comet, Morpho Blue, then Aave.
+    fn markets(&self) -> Vec<SupplyMarket> {
+        let comets = self.comets.iter().map(|&comet| SupplyMarket::Comet(comet));
+        let morpho_markets = self.morpho_markets.iter().map(|&id| SupplyMarket::Morpho {
+            morpho: self.morpho,
+            id,
+        });
+        let aave_reserves = self.aave_assets.iter().map(|&asset| SupplyMarket::Aave {
+            pool: self.aave_pool,
+            asset,
+        });
+        comets.chain(morpho_markets).chain(aave_reserves).collect()
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    // Initialize tracing. In order to view logs, run `RUST_LOG=info cargo run`
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .with_writer(std::io::stderr)
+        .init();
+    // Parse the command line arguments.
+    let args = Args::try_parse()?;
+
+    ensure!(
+        !args.markets().is_empty(),
+        "pass at least one --comet, --morpho-market or --aave-asset"
+    );
+
+    let report = Report::new(&args.output, "comparison", COMPARISON_ID)
+        .rpc(&args.eth_rpc_url)
+        .inputs("comets", &args.comets)
+        .inputs("morpho_markets", &args.morpho_markets)
+        .inputs("aave_assets", &args.aave_assets);
+
+    if let Some(samples) = args.batch.points() {
+        return batch::run(&args.batch, &report, COMPARISON_ELF, samples, |sample| {
+            preflight(&args, BlockNumberOrTag::Number(sample.block))
+        })
+        .await;
+    }
+
+    let input = profile::timed(preflight(&args, args.execution_block)).await?;
+    if args.inspect.is_enabled() {
+        return args.inspect.print(&input);
+    }
+    let input_hash = input.digest();
+    let proof = prove(COMPARISON_ELF, input).await?;
+    let receipt = &proof.receipt;
+    let journal = &receipt.journal.bytes;
+
+    // Decode and log the commitment
+    let (header, journal) = Journal::decode_with_header(journal).context("invalid journal")?;
+    ensure!(
+        header.inputHash == input_hash,
+        "journal is of another input"
+    );
+    log::info!(
+        "Winner: {:?} {:?}",
+        journal.winner.market,
+        journal.winner.marketId
+    );
+    log::info!("Steel commitment: {:?}", journal.commitment);
+    report.publish(&proof).await?;
+
+    Ok(())
+}
+
+/// Preflights the supply rates of all markets at the block and returns the input of the guest.
+async fn preflight(args: &Args, execution_block: BlockNumberOrTag) -> Result<GuestInput> {
+    // Create an alloy provider for that private key and URL.
+    let wallet = EthereumWallet::from(args.eth_wallet_private_key.clone());
+    let provider = ProviderBuilder::new()
+        .wallet(wallet)
+        .on_client(args.inspect.client(&args.eth_rpc_url));
+
+    #[cfg(feature = "beacon")]
+    log::info!("Beacon commitment to block {}", execution_block);
+    #[cfg(feature = "history")]
+    log::info!("History commitment to block {}", args.commitment_block);
+
+    let builder = EthEvmEnv::builder()
+        .provider(provider.clone())
+        .block_number_or_tag(execution_block);
+    #[cfg(any(feature = "beacon", feature = "history"))]
+    let builder = builder.beacon_api(args.beacon_api_url.clone());
+    #[cfg(feature = "history")]
+    let builder = builder.commitment_block_number_or_tag(args.commitment_block);
+
+    let mut env = builder.build().await?;
+    //  The `with_chain_spec` method is used to specify the chain configuration.
+    env = env.with_chain_spec(&ETH_MAINNET_CHAIN_SPEC);
+
+    // Preflight the calls of every market. A degenerate market only fails its own entry, like
+    // in the guest.
+    let supply_markets = args.markets();
+    let mut markets = Vec::with_capacity(supply_markets.len());
+    for market in &supply_markets {
+        let supply_apr = async {
+            match *market {
+                SupplyMarket::Comet(comet) => {
+                    let utilization = Contract::preflight(comet, &mut env)
+                        .call_builder(&CometMainInterface::getUtilizationCall {})
+                        .call()
+                        .await
+                        .map_err(reverted)?
+                        ._0;
+                    let supply_rate = Contract::preflight(comet, &mut env)
+                        .call_builder(&CometMainInterface::getSupplyRateCall { utilization })
+                        .call()
+                        .await
+                        .map_err(reverted)?
+                        ._0;
+                    Ok::<_, Status>(math::mul(
+                        U256::from(supply_rate),
+                        U256::from(SECONDS_PER_YEAR),
+                    )?)
+                }
+                SupplyMarket::Morpho { morpho, id } => {
+                    let state = Contract::preflight(morpho, &mut env)
+                        .call_builder(&IMorpho::marketCall { id })
+                        .call()
+                        .await
+                        .map_err(reverted)?
+                        ._0;
+                    let market_params = Contract::preflight(morpho, &mut env)
+                        .call_builder(&IMorpho::idToMarketParamsCall { id })
+                        .call()
+                        .await
+                        .map_err(reverted)?
+                        ._0;
+                    let borrow_rate = if market_params.irm == Address::ZERO {
+                        U256::ZERO
+                    } else {
+                        Contract::preflight(market_params.irm, &mut env)
+                            .call_builder(&IIrm::borrowRateViewCall {
+                                marketParams: market_params,
+                                market: state.clone(),
+                            })
+                            .call()
+                            .await
+                            .map_err(reverted)?
+                            ._0
+                    };
+                    Ok(morpho::supply_apr(borrow_rate, &state)?)
+                }
+                SupplyMarket::Aave { pool, asset } => {
+                    let reserve = Contract::preflight(pool, &mut env)
+                        .call_builder(&IPool::getReserveDataCall { asset })
+                        .call()
+                        .await
+                        .map_err(reverted)?
+                        ._0;
+                    Ok(math::ray_to_wad(U256::from(reserve.currentLiquidityRate)))
+                }
+            }
+        }
+        .await;
+        markets.push(market.with_apr(supply_apr));
+    }
+    let (status, ranking, winner) = comparison::rank(markets);
+
+    log::info!("Status: {}", status);
+    for (rank, market) in ranking.iter().enumerate() {
+        log::info!(
+            "#{} {:?} {:?}: status {}, supply APR {}",
+            rank + 1,
+            market.market,
+            market.marketId,
+            market.status,
+            Unit::RATE.format(market.supplyApr)
+        );
+    }
+    log::info!("Winner: {:?} {:?}", winner.market, winner.marketId);
+
+    // Finally, construct the input from the environment.
+    let block = Block::of(env.header());
+    let evm_input = env.into_input().await?;
+
+    let expected = ranking
+        .iter()
+        .enumerate()
+        .fold(Expected::new(status), |expected, (rank, market)| {
+            expected.value(&format!("ranking[{rank}].supplyApr"), market.supplyApr)
+        })
+        .value("winner.supplyApr", winner.supplyApr);
+
+    GuestInput::default()
+        .at(block)
+        .expect(expected)
+        .write(&evm_input)?
+        .write(&supply_markets)
+}
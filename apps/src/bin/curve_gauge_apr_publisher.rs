@@ -0,0 +1,341 @@
+use alloy_primitives::Address;
+use anyhow::{ensure, Context, Result};
+use apps::{
+    batch::{self, BatchArgs},
+    check::Expected,
+    inspect::InspectArgs,
+    output::{OutputArgs, Report},
+    price_routes, profile, prove, reverted,
+    units::{self, Denomination, Unit},
+    Block, GuestInput,
+};
+use clap::Parser;
+use erc20_counter_core::{
+    curve::{
+        self, ICurvePool, ICurveToken, IGaugeController, ILiquidityGauge, Journal, CRV,
+        GAUGE_CONTROLLER,
+    },
+    erc20::IERC20,
+    journal::MetricJournal,
+    math,
+    pricing::{QuoterV2, SwapPath, QUOTER_V2},
+    status::Status,
+    tvl::USDC,
+};
+use erc20_counter_methods::{CURVE_GAUGE_APR_ELF, CURVE_GAUGE_APR_ID};
+use risc0_steel::alloy::{
+    network::EthereumWallet, providers::ProviderBuilder, signers::local::PrivateKeySigner,
+};
+use risc0_steel::{
+    ethereum::{EthEvmEnv, ETH_MAINNET_CHAIN_SPEC},
+    host::BlockNumberOrTag,
+    Contract,
+};
+use tracing_subscriber::EnvFilter;
+use url::Url;
+
+/// Proves the CRV emission APR of a Curve gauge.
+#[derive(Parser)]
+struct Args {
+    /// Ethereum private key
+    #[arg(long, env = "ETH_WALLET_PRIVATE_KEY")]
+    eth_wallet_private_key: PrivateKeySigner,
+
+    /// Ethereum RPC endpoint URL
+    #[arg(long, env = "ETH_RPC_URL")]
+    eth_rpc_url: Url,
+
+    /// Beacon API endpoint URL
+    ///
+    /// Steel uses a beacon block commitment instead of the execution block.
+    /// This allows proofs to be validated using the EIP-4788 beacon roots contract.
+    #[cfg(any(feature = "beacon", feature = "history"))]
+    #[arg(long, env = "BEACON_API_URL")]
+    beacon_api_url: Url,
+
+    /// Ethereum block to use as the state for the contract call
+    #[arg(long, env = "EXECUTION_BLOCK", default_value_t = BlockNumberOrTag::Parent)]
+    execution_block: BlockNumberOrTag,
+
+    /// Ethereum block to use for the beacon block commitment.
+    #[cfg(feature = "history")]
+    #[arg(long, env = "COMMITMENT_BLOCK")]
+    commitment_block: BlockNumberOrTag,
+
+    /// Address of the Curve liquidity gauge
+    #[arg(long)]
+    gauge: Address,
+
+    /// Address of the Curve GaugeController
+    #[arg(long, default_value_t = GAUGE_CONTROLLER)]
+    gauge_controller: Address,
+
+    /// Address of the Curve pool whose LP token is staked in the gauge
+    #[arg(long)]
+    pool: Address,
+
+    /// Token in which the pool virtual price is denominated
+    #[arg(long)]
+    base_token: Address,
+
+    /// Token in which CRV and the LP token are valued
+    #[arg(long, default_value_t = USDC)]
+    numeraire: Address,
+
+    /// Uniswap V3 route pricing a token in the numeraire, as `token:fee:token...`
+    #[arg(long = "price-path")]
+    price_paths: Vec<SwapPath>,
+
+    #[command(flatten)]
+    batch: BatchArgs,
+
+    #[command(flatten)]
+    output: OutputArgs,
+
+    #[command(flatten)]
+    inspect: InspectArgs,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    // Initialize tracing. In order to view logs, run `RUST_LOG=info cargo run`
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .with_writer(std::io::stderr)
+        .init();
+    // Parse the command line arguments.
+    let args = Args::try_parse()?;
+
+    let report = Report::new(&args.output, "curve_gauge_apr", CURVE_GAUGE_APR_ID)
+        .rpc(&args.eth_rpc_url)
+        .input("gauge", args.gauge)
+        .input("gauge_controller", args.gauge_controller)
+        .input("pool", args.pool)
+        .input("base_token", args.base_token)
+        .input("numeraire", args.numeraire);
+
+    if let Some(samples) = args.batch.points() {
+        return batch::run(
+            &args.batch,
+            &report,
+            CURVE_GAUGE_APR_ELF,
+            samples,
+            |sample| preflight(&args, BlockNumberOrTag::Number(sample.block)),
+        )
+        .await;
+    }
+
+    let input = profile::timed(preflight(&args, args.execution_block)).await?;
+    if args.inspect.is_enabled() {
+        return args.inspect.print(&input);
+    }
+    let input_hash = input.digest();
+    let proof = prove(CURVE_GAUGE_APR_ELF, input).await?;
+    let receipt = &proof.receipt;
+    let journal = &receipt.journal.bytes;
+
+    // Decode and log the commitment
+    let (header, journal) = Journal::decode_with_header(journal).context("invalid journal")?;
+    ensure!(
+        header.inputHash == input_hash,
+        "journal is of another input"
+    );
+    log::info!("Curve gauge: {:?}", journal.gauge);
+    log::info!("Curve pool: {:?}", journal.pool);
+    log::info!("Steel commitment: {:?}", journal.commitment);
+    report.publish(&proof).await?;
+
+    Ok(())
+}
+
+/// Preflights the gauge emissions at the block and returns the input of the guest.
+async fn preflight(args: &Args, execution_block: BlockNumberOrTag) -> Result<GuestInput> {
+    // Routes pricing CRV and the base token of the pool; the numeraire needs none.
+    let routes = price_routes(&args.price_paths, [CRV, args.base_token], args.numeraire)?;
+
+    // Create an alloy provider for that private key and URL.
+    let wallet = EthereumWallet::from(args.eth_wallet_private_key.clone());
+    let provider = ProviderBuilder::new()
+        .wallet(wallet)
+        .on_client(args.inspect.client(&args.eth_rpc_url));
+
+    #[cfg(feature = "beacon")]
+    log::info!("Beacon commitment to block {}", execution_block);
+    #[cfg(feature = "history")]
+    log::info!("History commitment to block {}", args.commitment_block);
+
+    let builder = EthEvmEnv::builder()
+        .provider(provider.clone())
+        .block_number_or_tag(execution_block);
+    #[cfg(any(feature = "beacon", feature = "history"))]
+    let builder = builder.beacon_api(args.beacon_api_url.clone());
+    #[cfg(feature = "history")]
+    let builder = builder.commitment_block_number_or_tag(args.commitment_block);
+
+    let mut env = builder.build().await?;
+    //  The `with_chain_spec` method is used to specify the chain configuration.
+    env = env.with_chain_spec(&ETH_MAINNET_CHAIN_SPEC);
+
+    // Preflight the calls to prepare the input that is required to execute the function in
+    // the guest without RPC access.
+    ensure!(
+        args.gauge_controller == GAUGE_CONTROLLER,
+        "not the Curve GaugeController"
+    );
+    let lp_token = Contract::preflight(args.gauge, &mut env)
+        .call_builder(&ILiquidityGauge::lp_tokenCall {})
+        .call()
+        .await?
+        ._0;
+    let minter = if lp_token != args.pool {
+        let minter = Contract::preflight(lp_token, &mut env)
+            .call_builder(&ICurveToken::minterCall {})
+            .call()
+            .await?
+            ._0;
+        Some(minter)
+    } else {
+        None
+    };
+    ensure!(
+        curve::is_gauge_pool(args.pool, lp_token, minter),
+        "pool {} does not issue the LP token {} of the gauge",
+        args.pool,
+        lp_token
+    );
+
+    let outcome = async {
+        let mut gauge_contract = Contract::preflight(args.gauge, &mut env);
+        let inflation_rate = gauge_contract
+            .call_builder(&ILiquidityGauge::inflation_rateCall {})
+            .call()
+            .await
+            .map_err(reverted)?
+            ._0;
+        let working_supply = gauge_contract
+            .call_builder(&ILiquidityGauge::working_supplyCall {})
+            .call()
+            .await
+            .map_err(reverted)?
+            ._0;
+        if working_supply.is_zero() {
+            return Err(Status::NoSupply);
+        }
+        let total_supply = gauge_contract
+            .call_builder(&ILiquidityGauge::totalSupplyCall {})
+            .call()
+            .await
+            .map_err(reverted)?
+            ._0;
+        let relative_weight = Contract::preflight(args.gauge_controller, &mut env)
+            .call_builder(&IGaugeController::gauge_relative_weightCall { addr: args.gauge })
+            .call()
+            .await
+            .map_err(reverted)?
+            ._0;
+        let virtual_price = Contract::preflight(args.pool, &mut env)
+            .call_builder(&ICurvePool::get_virtual_priceCall {})
+            .call()
+            .await
+            .map_err(reverted)?
+            ._0;
+
+        // Price of one whole token in base units of the numeraire.
+        let mut prices = Vec::new();
+        for token in [CRV, args.base_token] {
+            let decimals = Contract::preflight(token, &mut env)
+                .call_builder(&IERC20::decimalsCall {})
+                .call()
+                .await
+                .map_err(reverted)?
+                ._0;
+            let unit = math::pow10(decimals)?;
+            let price = match routes.get(&token) {
+                None => unit,
+                Some(path) => {
+                    Contract::preflight(QUOTER_V2, &mut env)
+                        .call_builder(&QuoterV2::quoteExactInputCall {
+                            path: path.encode(),
+                            amountIn: unit,
+                        })
+                        .call()
+                        .await
+                        .map_err(reverted)?
+                        .amountOut
+                }
+            };
+            prices.push(price);
+        }
+        let crv_price = prices[0];
+        let lp_price = curve::lp_price(virtual_price, prices[1])?;
+
+        let max_boost_apr = curve::max_boost_apr(
+            inflation_rate,
+            relative_weight,
+            working_supply,
+            crv_price,
+            lp_price,
+        )?;
+        let base_apr = curve::base_apr(max_boost_apr)?;
+        Ok::<_, Status>((
+            inflation_rate,
+            relative_weight,
+            working_supply,
+            total_supply,
+            crv_price,
+            lp_price,
+            base_apr,
+            max_boost_apr,
+        ))
+    }
+    .await;
+    let (
+        status,
+        (
+            inflation_rate,
+            relative_weight,
+            working_supply,
+            total_supply,
+            crv_price,
+            lp_price,
+            base_apr,
+            max_boost_apr,
+        ),
+    ) = Status::split(outcome);
+
+    let numeraire = units::token(&args.eth_rpc_url, Denomination::Token(args.numeraire)).await;
+    log::info!("Status: {}", status);
+    log::info!("Inflation Rate: {} CRV/s", Unit::WAD.format(inflation_rate));
+    log::info!("Relative Weight: {}", Unit::RATE.format(relative_weight));
+    log::info!("Working Supply: {}", Unit::WAD.format(working_supply));
+    log::info!("Total Supply: {}", Unit::WAD.format(total_supply));
+    log::info!("CRV Price: {}", numeraire.format(crv_price));
+    log::info!("LP Price: {}", numeraire.format(lp_price));
+    log::info!("Base CRV APR: {}", Unit::RATE.format(base_apr));
+    log::info!("Max Boost CRV APR: {}", Unit::RATE.format(max_boost_apr));
+
+    // Finally, construct the input from the environment.
+    let block = Block::of(env.header());
+    let evm_input = env.into_input().await?;
+
+    GuestInput::default()
+        .at(block)
+        .expect(
+            Expected::new(status)
+                .value("inflationRate", inflation_rate)
+                .value("relativeWeight", relative_weight)
+                .value("workingSupply", working_supply)
+                .value("totalSupply", total_supply)
+                .value("crvPrice", crv_price)
+                .value("lpPrice", lp_price)
+                .value("baseApr", base_apr)
+                .value("maxBoostApr", max_boost_apr),
+        )
+        .write(&evm_input)?
+        .write(&args.gauge)?
+        .write(&args.gauge_controller)?
+        .write(&args.pool)?
+        .write(&args.base_token)?
+        .write(&args.numeraire)?
+        .write(&args.price_paths)
+}
@@ -0,0 +1,286 @@
+use alloy_primitives::Address;
+use anyhow::{ensure, Context, Result};
+use apps::{
+    batch::{self, BatchArgs},
+    check::Expected,
+    inspect::InspectArgs,
+    output::{OutputArgs, Report},
+    profile, prove, reverted,
+    units::{self, Denomination, Unit},
+    Block, GuestInput,
+};
+use clap::Parser;
+use erc20_counter_core::{
+    erc4626::{Journal, IERC4626},
+    journal::MetricJournal,
+    math,
+    status::Status,
+};
+use erc20_counter_methods::{ERC4626_APY_ELF, ERC4626_APY_ID};
+use risc0_steel::alloy::{
+    network::EthereumWallet, providers::ProviderBuilder, signers::local::PrivateKeySigner,
+};
+use risc0_steel::{
+    ethereum::{EthEvmEnv, ETH_MAINNET_CHAIN_SPEC},
+    host::BlockNumberOrTag,
+    Contract, EvmBlockHeader,
+};
+use tracing_subscriber::EnvFilter;
+use url::Url;
+
+/// Proves the yield realized by an ERC-4626 vault share between two blocks.
+#[derive(Parser)]
+struct Args {
+    /// Ethereum private key
+    #[arg(long, env = "ETH_WALLET_PRIVATE_KEY")]
+    eth_wallet_private_key: PrivateKeySigner,
+
+    /// Ethereum RPC endpoint URL
+    #[arg(long, env = "ETH_RPC_URL")]
+    eth_rpc_url: Url,
+
+    /// Beacon API endpoint URL
+    ///
+    /// Steel uses a beacon block commitment instead of the execution block.
+    /// This allows proofs to be validated using the EIP-4788 beacon roots contract.
+    #[cfg(any(feature = "beacon", feature = "history"))]
+    #[arg(long, env = "BEACON_API_URL")]
+    beacon_api_url: Url,
+
+    /// Ethereum block to use as the state for the contract call
+    #[arg(long, env = "EXECUTION_BLOCK", default_value_t = BlockNumberOrTag::Parent)]
+    execution_block: BlockNumberOrTag,
+
+    /// Ethereum block at the start of the measured window
+    #[arg(
+        long,
+        env = "PAST_EXECUTION_BLOCK",
+        required_unless_present = "from_block"
+    )]
+    past_execution_block: Option<BlockNumberOrTag>,
+
+    /// Ethereum block to use for the beacon block commitment.
+    #[cfg(feature = "history")]
+    #[arg(long, env = "COMMITMENT_BLOCK")]
+    commitment_block: BlockNumberOrTag,
+
+    /// Address of the ERC-4626 vault
+    #[arg(long)]
+    vault: Address,
+
+    #[command(flatten)]
+    batch: BatchArgs,
+
+    #[command(flatten)]
+    output: OutputArgs,
+
+    #[command(flatten)]
+    inspect: InspectArgs,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    // Initialize tracing. In order to view logs, run `RUST_LOG=info cargo run`
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .with_writer(std::io::stderr)
+        .init();
+    // Parse the command line arguments.
+    let args = Args::try_parse()?;
+
+    let report = Report::new(&args.output, "erc4626_apy", ERC4626_APY_ID)
+        .rpc(&args.eth_rpc_url)
+        .input("vault", args.vault);
+
+    if let Some(samples) = args.batch.windows() {
+        return batch::run(&args.batch, &report, ERC4626_APY_ELF, samples, |sample| {
+            let past_block = sample.past_block.expect("windows have a past block");
+            preflight(
+                &args,
+                BlockNumberOrTag::Number(sample.block),
+                BlockNumberOrTag::Number(past_block),
+            )
+        })
+        .await;
+    }
+
+    let past_execution_block = args
+        .past_execution_block
+        .context("missing past execution block")?;
+    let input =
+        profile::timed(preflight(&args, args.execution_block, past_execution_block)).await?;
+    if args.inspect.is_enabled() {
+        return args.inspect.print(&input);
+    }
+    let input_hash = input.digest();
+    let proof = prove(ERC4626_APY_ELF, input).await?;
+    let receipt = &proof.receipt;
+    let journal = &receipt.journal.bytes;
+
+    // Decode and log the commitment
+    let (header, journal) = Journal::decode_with_header(journal).context("invalid journal")?;
+    ensure!(
+        header.inputHash == input_hash,
+        "journal is of another input"
+    );
+    log::info!("Vault: {:?}", journal.vault);
+    log::info!("Underlying asset: {:?}", journal.asset);
+    log::info!("Steel commitment: {:?}", journal.commitment);
+    log::info!("Past Steel commitment: {:?}", journal.pastCommitment);
+    report.publish(&proof).await?;
+
+    Ok(())
+}
+
+/// Preflights the share prices at both blocks and returns the input of the guest.
+async fn preflight(
+    args: &Args,
+    execution_block: BlockNumberOrTag,
+    past_execution_block: BlockNumberOrTag,
+) -> Result<GuestInput> {
+    // Create an alloy provider for that private key and URL.
+    let wallet = EthereumWallet::from(args.eth_wallet_private_key.clone());
+    let provider = ProviderBuilder::new()
+        .wallet(wallet)
+        .on_client(args.inspect.client(&args.eth_rpc_url));
+
+    #[cfg(feature = "beacon")]
+    log::info!("Beacon commitment to block {}", execution_block);
+    #[cfg(feature = "history")]
+    log::info!("History commitment to block {}", args.commitment_block);
+
+    let builder = EthEvmEnv::builder()
+        .provider(provider.clone())
+        .block_number_or_tag(execution_block);
+    let past_builder = EthEvmEnv::builder()
+        .provider(provider.clone())
+        .block_number_or_tag(past_execution_block);
+
+    #[cfg(any(feature = "beacon", feature = "history"))]
+    let builder = builder.beacon_api(args.beacon_api_url.clone());
+    #[cfg(any(feature = "beacon", feature = "history"))]
+    let past_builder = past_builder.beacon_api(args.beacon_api_url.clone());
+    #[cfg(feature = "history")]
+    let builder = builder.commitment_block_number_or_tag(args.commitment_block);
+    #[cfg(feature = "history")]
+    let past_builder = past_builder.commitment_block_number_or_tag(args.commitment_block);
+
+    let mut env = builder.build().await?;
+    let mut past_env = past_builder.build().await?;
+    //  The `with_chain_spec` method is used to specify the chain configuration.
+    env = env.with_chain_spec(&ETH_MAINNET_CHAIN_SPEC);
+    past_env = past_env.with_chain_spec(&ETH_MAINNET_CHAIN_SPEC);
+
+    let timestamp = env.header().timestamp();
+    let past_timestamp = past_env.header().timestamp();
+    ensure!(
+        past_timestamp < timestamp,
+        "past block must precede the execution block"
+    );
+
+    let outcome = async {
+        ///// Present share price
+        let mut vault_contract = Contract::preflight(args.vault, &mut env);
+        vault_contract
+            .call_builder(&IERC4626::assetCall {})
+            .call()
+            .await
+            .map_err(reverted)?;
+        let decimals = vault_contract
+            .call_builder(&IERC4626::decimalsCall {})
+            .call()
+            .await
+            .map_err(reverted)?
+            ._0;
+        let convert_call = IERC4626::convertToAssetsCall {
+            shares: math::pow10(decimals)?,
+        };
+        let share_price = vault_contract
+            .call_builder(&convert_call)
+            .call()
+            .await
+            .map_err(reverted)?
+            ._0;
+        let total_assets = vault_contract
+            .call_builder(&IERC4626::totalAssetsCall {})
+            .call()
+            .await
+            .map_err(reverted)?
+            ._0;
+        let total_supply = vault_contract
+            .call_builder(&IERC4626::totalSupplyCall {})
+            .call()
+            .await
+            .map_err(reverted)?
+            ._0;
+
+        ///// Past share price
+        let mut vault_contract = Contract::preflight(args.vault, &mut past_env);
+        let past_share_price = vault_contract
+            .call_builder(&convert_call)
+            .call()
+            .await
+            .map_err(reverted)?
+            ._0;
+        vault_contract
+            .call_builder(&IERC4626::totalAssetsCall {})
+            .call()
+            .await
+            .map_err(reverted)?;
+        let past_total_supply = vault_contract
+            .call_builder(&IERC4626::totalSupplyCall {})
+            .call()
+            .await
+            .map_err(reverted)?
+            ._0;
+
+        if total_supply.is_zero() || past_total_supply.is_zero() {
+            return Err(Status::NoSupply);
+        }
+
+        let elapsed = timestamp - past_timestamp;
+        let apr = math::annualized_change(past_share_price, share_price, elapsed)?;
+        let apy = math::realized_apy(past_share_price, share_price, elapsed)?;
+        Ok::<_, Status>((
+            share_price,
+            past_share_price,
+            total_assets,
+            total_supply,
+            apr,
+            apy,
+        ))
+    }
+    .await;
+    let (status, (share_price, past_share_price, total_assets, total_supply, apr, apy)) =
+        Status::split(outcome);
+
+    let asset = units::token(&args.eth_rpc_url, Denomination::VaultAsset(args.vault)).await;
+    let shares = units::token(&args.eth_rpc_url, Denomination::Token(args.vault)).await;
+    log::info!("Status: {}", status);
+    log::info!("Share Price: {}", asset.format(share_price));
+    log::info!("Past Share Price: {}", asset.format(past_share_price));
+    log::info!("Total Assets: {}", asset.format(total_assets));
+    log::info!("Total Supply: {}", shares.format(total_supply));
+    log::info!("Realized APR: {}", Unit::RATE.format(apr));
+    log::info!("Realized APY: {}", Unit::RATE.format(apy));
+
+    // Finally, construct the input from the environment.
+    let block = Block::of(env.header());
+    let evm_input = env.into_input().await?;
+    let past_evm_input = past_env.into_input().await?;
+
+    GuestInput::default()
+        .at(block)
+        .expect(
+            Expected::new(status)
+                .value("sharePrice", share_price)
+                .value("pastSharePrice", past_share_price)
+                .value("totalAssets", total_assets)
+                .value("totalSupply", total_supply)
+                .value("apr", apr)
+                .value("apy", apy),
+        )
+        .write(&evm_input)?
+        .write(&past_evm_input)?
+        .write(&args.vault)
+}
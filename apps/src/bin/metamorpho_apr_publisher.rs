@@ -0,0 +1,295 @@
+use alloy_primitives::{Address, U256};
+use anyhow::{ensure, Context, Result};
+use apps::{
+    batch::{self, BatchArgs},
+    check::Expected,
+    inspect::InspectArgs,
+    output::{OutputArgs, Report},
+    profile, prove, reverted,
+    units::{self, Denomination, Unit},
+    Block, GuestInput,
+};
+use clap::Parser;
+use erc20_counter_core::{
+    journal::MetricJournal,
+    math,
+    morpho::{self, IIrm, IMetaMorpho, IMorpho, Journal, MarketAllocation},
+    status::Status,
+};
+use erc20_counter_methods::{METAMORPHO_APR_ELF, METAMORPHO_APR_ID};
+use risc0_steel::alloy::{
+    network::EthereumWallet, providers::ProviderBuilder, signers::local::PrivateKeySigner,
+};
+use risc0_steel::{
+    ethereum::{EthEvmEnv, ETH_MAINNET_CHAIN_SPEC},
+    host::BlockNumberOrTag,
+    Contract,
+};
+use tracing_subscriber::EnvFilter;
+use url::Url;
+
+/// Proves the supply APR of a MetaMorpho vault across its Morpho Blue markets.
+#[derive(Parser)]
+struct Args {
+    /// Ethereum private key
+    #[arg(long, env = "ETH_WALLET_PRIVATE_KEY")]
+    eth_wallet_private_key: PrivateKeySigner,
+
+    /// Ethereum RPC endpoint URL
+    #[arg(long, env = "ETH_RPC_URL")]
+    eth_rpc_url: Url,
+
+    /// Beacon API endpoint URL
+    ///
+    /// Steel uses a beacon block commitment instead of the execution block.
+    /// This allows proofs to be validated using the EIP-4788 beacon roots contract.
+    #[cfg(any(feature = "beacon", feature = "history"))]
+    #[arg(long, env = "BEACON_API_URL")]
+    beacon_api_url: Url,
+
+    /// Ethereum block to use as the state for the contract call
+    #[arg(long, env = "EXECUTION_BLOCK", default_value_t = BlockNumberOrTag::Parent)]
+    execution_block: BlockNumberOrTag,
+
+    /// Ethereum block to use for the beacon block commitment.
+    #[cfg(feature = "history")]
+    #[arg(long, env = "COMMITMENT_BLOCK")]
+    commitment_block: BlockNumberOrTag,
+
+    /// Address of the MetaMorpho vault
+    #[arg(long)]
+    vault: Address,
+
+    #[command(flatten)]
+    batch: BatchArgs,
+
+    #[command(flatten)]
+    output: OutputArgs,
+
+    #[command(flatten)]
+    inspect: InspectArgs,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    // Initialize tracing. In order to view logs, run `RUST_LOG=info cargo run`
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .with_writer(std::io::stderr)
+        .init();
+    // Parse the command line arguments.
+    let args = Args::try_parse()?;
+
+    let report = Report::new(&args.output, "metamorpho_apr", METAMORPHO_APR_ID)
+        .rpc(&args.eth_rpc_url)
+        .input("vault", args.vault);
+
+    if let Some(samples) = args.batch.points() {
+        return batch::run(
+            &args.batch,
+            &report,
+            METAMORPHO_APR_ELF,
+            samples,
+            |sample| preflight(&args, BlockNumberOrTag::Number(sample.block)),
+        )
+        .await;
+    }
+
+    let input = profile::timed(preflight(&args, args.execution_block)).await?;
+    if args.inspect.is_enabled() {
+        return args.inspect.print(&input);
+    }
+    let input_hash = input.digest();
+    let proof = prove(METAMORPHO_APR_ELF, input).await?;
+    let receipt = &proof.receipt;
+    let journal = &receipt.journal.bytes;
+
+    // Decode and log the commitment
+    let (header, journal) = Journal::decode_with_header(journal).context("invalid journal")?;
+    ensure!(
+        header.inputHash == input_hash,
+        "journal is of another input"
+    );
+    log::info!("MetaMorpho vault: {:?}", journal.vault);
+    log::info!("Steel commitment: {:?}", journal.commitment);
+    report.publish(&proof).await?;
+
+    Ok(())
+}
+
+/// Preflights the vault allocation at the block and returns the input of the guest.
+async fn preflight(args: &Args, execution_block: BlockNumberOrTag) -> Result<GuestInput> {
+    // Create an alloy provider for that private key and URL.
+    let wallet = EthereumWallet::from(args.eth_wallet_private_key.clone());
+    let provider = ProviderBuilder::new()
+        .wallet(wallet)
+        .on_client(args.inspect.client(&args.eth_rpc_url));
+
+    #[cfg(feature = "beacon")]
+    log::info!("Beacon commitment to block {}", execution_block);
+    #[cfg(feature = "history")]
+    log::info!("History commitment to block {}", args.commitment_block);
+
+    let builder = EthEvmEnv::builder()
+        .provider(provider.clone())
+        .block_number_or_tag(execution_block);
+    #[cfg(any(feature = "beacon", feature = "history"))]
+    let builder = builder.beacon_api(args.beacon_api_url.clone());
+    #[cfg(feature = "history")]
+    let builder = builder.commitment_block_number_or_tag(args.commitment_block);
+
+    let mut env = builder.build().await?;
+    //  The `with_chain_spec` method is used to specify the chain configuration.
+    env = env.with_chain_spec(&ETH_MAINNET_CHAIN_SPEC);
+
+    // Preflight the calls to prepare the input that is required to execute the function in
+    // the guest without RPC access.
+    let asset = units::token(&args.eth_rpc_url, Denomination::VaultAsset(args.vault)).await;
+    let outcome = async {
+        let mut vault_contract = Contract::preflight(args.vault, &mut env);
+        let morpho_address = vault_contract
+            .call_builder(&IMetaMorpho::MORPHOCall {})
+            .call()
+            .await
+            .map_err(reverted)?
+            ._0;
+        let fee = U256::from(
+            vault_contract
+                .call_builder(&IMetaMorpho::feeCall {})
+                .call()
+                .await
+                .map_err(reverted)?
+                ._0,
+        );
+        let total_assets = vault_contract
+            .call_builder(&IMetaMorpho::totalAssetsCall {})
+            .call()
+            .await
+            .map_err(reverted)?
+            ._0;
+
+        let supply_queue_length = vault_contract
+            .call_builder(&IMetaMorpho::supplyQueueLengthCall {})
+            .call()
+            .await
+            .map_err(reverted)?
+            ._0;
+        let mut supply_queue = Vec::new();
+        for index in 0..supply_queue_length.to::<u64>() {
+            let id = vault_contract
+                .call_builder(&IMetaMorpho::supplyQueueCall {
+                    index: U256::from(index),
+                })
+                .call()
+                .await
+                .map_err(reverted)?
+                ._0;
+            supply_queue.push(id);
+        }
+
+        // Every market the vault can hold a position in is part of the withdraw queue.
+        let withdraw_queue_length = vault_contract
+            .call_builder(&IMetaMorpho::withdrawQueueLengthCall {})
+            .call()
+            .await
+            .map_err(reverted)?
+            ._0;
+        let mut markets = Vec::new();
+        for index in 0..withdraw_queue_length.to::<u64>() {
+            let id = Contract::preflight(args.vault, &mut env)
+                .call_builder(&IMetaMorpho::withdrawQueueCall {
+                    index: U256::from(index),
+                })
+                .call()
+                .await
+                .map_err(reverted)?
+                ._0;
+            let mut morpho_contract = Contract::preflight(morpho_address, &mut env);
+            let market = morpho_contract
+                .call_builder(&IMorpho::marketCall { id })
+                .call()
+                .await
+                .map_err(reverted)?
+                ._0;
+            let market_params = morpho_contract
+                .call_builder(&IMorpho::idToMarketParamsCall { id })
+                .call()
+                .await
+                .map_err(reverted)?
+                ._0;
+            let position = morpho_contract
+                .call_builder(&IMorpho::positionCall {
+                    id,
+                    user: args.vault,
+                })
+                .call()
+                .await
+                .map_err(reverted)?;
+
+            // Idle markets have no interest rate model and earn nothing.
+            let borrow_rate = if market_params.irm == Address::ZERO {
+                U256::ZERO
+            } else {
+                Contract::preflight(market_params.irm, &mut env)
+                    .call_builder(&IIrm::borrowRateViewCall {
+                        marketParams: market_params,
+                        market: market.clone(),
+                    })
+                    .call()
+                    .await
+                    .map_err(reverted)?
+                    ._0
+            };
+
+            let supply_apr = morpho::supply_apr(borrow_rate, &market)?;
+            let allocation = MarketAllocation {
+                id,
+                inSupplyQueue: supply_queue.contains(&id),
+                supplyAssets: morpho::to_assets_down(
+                    position.supplyShares,
+                    market.totalSupplyAssets,
+                    market.totalSupplyShares,
+                )?,
+                supplyApr: supply_apr,
+                supplyApy: math::wad_apr_to_apy(supply_apr)?,
+            };
+            log::info!(
+                "Market {}: supplied {}, supply APR {}",
+                allocation.id,
+                asset.format(allocation.supplyAssets),
+                Unit::RATE.format(allocation.supplyApr)
+            );
+            markets.push(allocation);
+        }
+
+        let gross_apr = morpho::weighted_apr(&markets)?;
+        let net_apr = morpho::net_of_fee(gross_apr, fee)?;
+        let net_apy = math::wad_apr_to_apy(net_apr)?;
+        Ok::<_, Status>((fee, total_assets, gross_apr, net_apr, net_apy))
+    }
+    .await;
+    let (status, (fee, total_assets, gross_apr, net_apr, net_apy)) = Status::split(outcome);
+    log::info!("Status: {}", status);
+    log::info!("Vault Total Assets: {}", asset.format(total_assets));
+    log::info!("Vault Fee: {}", Unit::RATE.format(fee));
+    log::info!("Gross Supply APR: {}", Unit::RATE.format(gross_apr));
+    log::info!("Net Supply APR: {}", Unit::RATE.format(net_apr));
+    log::info!("Net Supply APY: {}", Unit::RATE.format(net_apy));
+
+    // Finally, construct the input from the environment.
+    let block = Block::of(env.header());
+    let evm_input = env.into_input().await?;
+
+    GuestInput::default()
+        .at(block)
+        .expect(
+            Expected::new(status)
+                .value("fee", fee)
+                .value("totalAssets", total_assets)
+                .value("grossApr", gross_apr)
+                .value("netApr", net_apr)
+                .value("netApy", net_apy),
+        )
+        .write(&evm_input)?
+        .write(&args.vault)
+}
@@ -0,0 +1,459 @@
+use std::{
+    collections::HashMap,
+    env,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
+use anyhow::{Context, Result};
+use axum::{
+    body::Bytes,
+    extract::{DefaultBodyLimit, Path, Request, State},
+    http::StatusCode,
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use clap::Parser;
+use risc0_zkvm::{
+    default_executor, default_prover, ExecutorEnv, ProverOpts, Receipt, SessionStats,
+};
+use serde::{Deserialize, Serialize};
+use tokio::{net::TcpListener, task};
+use tracing_subscriber::EnvFilter;
+use url::Url;
+
+/// Stand-in for a Bonsai-compatible proving service, proving with the local prover.
+///
+/// Serves the upload, session and poll API used by the remote prover of the publishers, so remote
+/// proving can be developed and tested without network access. Everything is kept in memory.
+#[derive(Parser)]
+struct Args {
+    /// Address to listen on
+    #[arg(long, default_value = "127.0.0.1:8081")]
+    listen: SocketAddr,
+
+    /// URL the clients reach the server at, used in upload and download links; defaults to
+    /// `http://<listen>`
+    #[arg(long)]
+    public_url: Option<Url>,
+
+    /// API key the clients must send; any key is accepted if unset
+    #[arg(long, env = "BONSAI_API_KEY")]
+    api_key: Option<String>,
+}
+
+/// Status of a proving session or a Groth16 compression.
+#[derive(Clone, Debug)]
+enum Job {
+    Running {
+        started: Instant,
+    },
+    Succeeded {
+        receipt: Option<String>,
+        stats: Option<Stats>,
+    },
+    Failed {
+        error: String,
+    },
+}
+
+impl Job {
+    fn status(&self) -> &'static str {
+        match self {
+            Self::Running { .. } => "RUNNING",
+            Self::Succeeded { .. } => "SUCCEEDED",
+            Self::Failed { .. } => "FAILED",
+        }
+    }
+}
+
+/// Statistics of a session, as reported by Bonsai.
+#[derive(Clone, Copy, Debug, Serialize)]
+struct Stats {
+    segments: usize,
+    total_cycles: u64,
+    cycles: u64,
+}
+
+impl From<&SessionStats> for Stats {
+    fn from(stats: &SessionStats) -> Self {
+        Self {
+            segments: stats.segments,
+            total_cycles: stats.total_cycles,
+            cycles: stats.user_cycles,
+        }
+    }
+}
+
+struct Server {
+    public_url: String,
+    api_key: Option<String>,
+    next_id: Mutex<u64>,
+    /// Uploaded images, inputs and receipts, and proven receipts, by `<kind>/<id>`.
+    blobs: Mutex<HashMap<String, Vec<u8>>>,
+    sessions: Mutex<HashMap<String, Job>>,
+    snarks: Mutex<HashMap<String, Job>>,
+}
+
+impl Server {
+    fn new_id(&self) -> String {
+        let mut next_id = self.next_id.lock().unwrap();
+        *next_id += 1;
+        format!("{:032x}", *next_id)
+    }
+
+    fn blob_url(&self, kind: &str, id: &str) -> String {
+        format!("{}/blobs/{kind}/{id}", self.public_url)
+    }
+
+    fn blob(&self, kind: &str, id: &str) -> Result<Vec<u8>> {
+        self.blobs
+            .lock()
+            .unwrap()
+            .get(&format!("{kind}/{id}"))
+            .cloned()
+            .with_context(|| format!("unknown {kind} {id}"))
+    }
+
+    fn store(&self, kind: &str, id: &str, blob: Vec<u8>) {
+        self.blobs
+            .lock()
+            .unwrap()
+            .insert(format!("{kind}/{id}"), blob);
+    }
+}
+
+fn main() -> Result<()> {
+    // Initialize tracing. In order to view logs, run `RUST_LOG=info cargo run`
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .init();
+    // Parse the command line arguments.
+    let args = Args::parse();
+
+    // `default_prover` would forward the sessions to a proving service if one is configured. The
+    // variable is cleared before the runtime starts its threads, which may read the environment.
+    env::remove_var("BONSAI_API_URL");
+
+    tokio::runtime::Runtime::new()?.block_on(serve(args))
+}
+
+async fn serve(args: Args) -> Result<()> {
+    let public_url = match args.public_url {
+        Some(url) => url.to_string(),
+        None => format!("http://{}", args.listen),
+    };
+    let server = Arc::new(Server {
+        public_url: public_url.trim_end_matches('/').to_string(),
+        api_key: args.api_key,
+        next_id: Mutex::new(0),
+        blobs: Mutex::default(),
+        sessions: Mutex::default(),
+        snarks: Mutex::default(),
+    });
+
+    let app = Router::new()
+        .route("/images/upload/{image_id}", get(image_upload))
+        .route("/inputs/upload", get(input_upload))
+        .route("/receipts/upload", get(receipt_upload))
+        .route("/blobs/{kind}/{id}", get(download).put(put_blob))
+        .route("/sessions/create", post(create_session))
+        .route("/sessions/status/{id}", get(session_status))
+        .route("/snark/create", post(create_snark))
+        .route("/snark/status/{id}", get(snark_status))
+        .layer(middleware::from_fn_with_state(server.clone(), authorize))
+        // Images and inputs are larger than the default limit.
+        .layer(DefaultBodyLimit::disable())
+        .with_state(server);
+
+    let listener = TcpListener::bind(args.listen).await?;
+    log::info!("Proving service stand-in listening on {}", args.listen);
+    axum::serve(listener, app).await?;
+
+    Ok(())
+}
+
+async fn authorize(State(server): State<Arc<Server>>, request: Request, next: Next) -> Response {
+    if let Some(api_key) = &server.api_key {
+        let key = request.headers().get("x-api-key");
+        if key.map(|key| key.as_bytes()) != Some(api_key.as_bytes()) {
+            return StatusCode::UNAUTHORIZED.into_response();
+        }
+    }
+    next.run(request).await
+}
+
+/// Error of a request for an unknown or invalid resource, answered with its message.
+struct ApiError(StatusCode, String);
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, self.1).into_response()
+    }
+}
+
+impl From<anyhow::Error> for ApiError {
+    fn from(err: anyhow::Error) -> Self {
+        Self(StatusCode::NOT_FOUND, format!("{err:#}"))
+    }
+}
+
+#[derive(Serialize)]
+struct UploadRes {
+    url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    uuid: Option<String>,
+}
+
+/// Link to upload an image to, or no content if it was uploaded before.
+async fn image_upload(State(server): State<Arc<Server>>, Path(image_id): Path<String>) -> Response {
+    if server.blob("images", &image_id).is_ok() {
+        return StatusCode::NO_CONTENT.into_response();
+    }
+    Json(UploadRes {
+        url: server.blob_url("images", &image_id),
+        uuid: None,
+    })
+    .into_response()
+}
+
+async fn input_upload(State(server): State<Arc<Server>>) -> Json<UploadRes> {
+    upload(&server, "inputs")
+}
+
+async fn receipt_upload(State(server): State<Arc<Server>>) -> Json<UploadRes> {
+    upload(&server, "receipts")
+}
+
+/// New id and the link to upload a blob of `kind` to.
+fn upload(server: &Server, kind: &str) -> Json<UploadRes> {
+    let uuid = server.new_id();
+    Json(UploadRes {
+        url: server.blob_url(kind, &uuid),
+        uuid: Some(uuid),
+    })
+}
+
+async fn put_blob(
+    State(server): State<Arc<Server>>,
+    Path((kind, id)): Path<(String, String)>,
+    body: Bytes,
+) -> StatusCode {
+    server.store(&kind, &id, body.to_vec());
+    StatusCode::OK
+}
+
+async fn download(
+    State(server): State<Arc<Server>>,
+    Path((kind, id)): Path<(String, String)>,
+) -> Result<Vec<u8>, ApiError> {
+    Ok(server.blob(&kind, &id)?)
+}
+
+#[derive(Deserialize)]
+struct ProofReq {
+    img: String,
+    input: String,
+    #[serde(default)]
+    assumptions: Vec<String>,
+    #[serde(default)]
+    execute_only: bool,
+}
+
+#[derive(Serialize)]
+struct CreateRes {
+    uuid: String,
+}
+
+async fn create_session(
+    State(server): State<Arc<Server>>,
+    Json(request): Json<ProofReq>,
+) -> Result<Json<CreateRes>, ApiError> {
+    let elf = server.blob("images", &request.img)?;
+    let input = server.blob("inputs", &request.input)?;
+    let assumptions = request
+        .assumptions
+        .iter()
+        .map(|id| {
+            let receipt = server.blob("receipts", id)?;
+            Ok(bincode::deserialize::<Receipt>(&receipt)?)
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let uuid = server.new_id();
+    let started = Instant::now();
+    server
+        .sessions
+        .lock()
+        .unwrap()
+        .insert(uuid.clone(), Job::Running { started });
+    log::info!("Session {uuid} of image {} started", request.img);
+
+    let (task_server, task_uuid) = (server.clone(), uuid.clone());
+    task::spawn_blocking(move || {
+        let job = match prove(&elf, &input, assumptions, request.execute_only) {
+            Ok((receipt, stats)) => {
+                let receipt = receipt.map(|receipt| {
+                    task_server.store("receipts", &task_uuid, receipt);
+                    task_server.blob_url("receipts", &task_uuid)
+                });
+                Job::Succeeded {
+                    receipt,
+                    stats: Some(stats),
+                }
+            }
+            Err(err) => Job::Failed {
+                error: format!("{err:#}"),
+            },
+        };
+        log::info!("Session {task_uuid} {}", job.status().to_lowercase());
+        task_server.sessions.lock().unwrap().insert(task_uuid, job);
+    });
+
+    Ok(Json(CreateRes { uuid }))
+}
+
+/// Proves, or only executes, `elf` on `input` into a bincode encoded succinct receipt.
+fn prove(
+    elf: &[u8],
+    input: &[u8],
+    assumptions: Vec<Receipt>,
+    execute_only: bool,
+) -> Result<(Option<Vec<u8>>, Stats)> {
+    let mut builder = ExecutorEnv::builder();
+    for receipt in assumptions {
+        builder.add_assumption(receipt);
+    }
+    let env = builder.write_slice(input).build()?;
+    if execute_only {
+        let stats = default_executor().execute(env, elf)?.stats();
+        return Ok((None, Stats::from(&stats)));
+    }
+    let prove_info = default_prover().prove_with_opts(env, elf, &ProverOpts::succinct())?;
+    Ok((
+        Some(bincode::serialize(&prove_info.receipt)?),
+        Stats::from(&prove_info.stats),
+    ))
+}
+
+#[derive(Serialize)]
+struct SessionStatusRes {
+    status: &'static str,
+    receipt_url: Option<String>,
+    error_msg: Option<String>,
+    state: Option<String>,
+    elapsed_time: Option<f64>,
+    stats: Option<Stats>,
+}
+
+async fn session_status(
+    State(server): State<Arc<Server>>,
+    Path(id): Path<String>,
+) -> Result<Json<SessionStatusRes>, ApiError> {
+    let job = server
+        .sessions
+        .lock()
+        .unwrap()
+        .get(&id)
+        .cloned()
+        .with_context(|| format!("unknown session {id}"))?;
+    let mut res = SessionStatusRes {
+        status: job.status(),
+        receipt_url: None,
+        error_msg: None,
+        state: None,
+        elapsed_time: None,
+        stats: None,
+    };
+    match job {
+        Job::Running { started } => {
+            res.state = Some("Proving".to_string());
+            res.elapsed_time = Some(started.elapsed().as_secs_f64());
+        }
+        Job::Succeeded { receipt, stats } => {
+            res.receipt_url = receipt;
+            res.stats = stats;
+        }
+        Job::Failed { error } => res.error_msg = Some(error),
+    }
+    Ok(Json(res))
+}
+
+#[derive(Deserialize)]
+struct SnarkReq {
+    session_id: String,
+}
+
+async fn create_snark(
+    State(server): State<Arc<Server>>,
+    Json(request): Json<SnarkReq>,
+) -> Result<Json<CreateRes>, ApiError> {
+    let receipt: Receipt = bincode::deserialize(&server.blob("receipts", &request.session_id)?)
+        .map_err(anyhow::Error::from)?;
+
+    let uuid = server.new_id();
+    let started = Instant::now();
+    server
+        .snarks
+        .lock()
+        .unwrap()
+        .insert(uuid.clone(), Job::Running { started });
+    log::info!("Snark {uuid} of session {} started", request.session_id);
+
+    let (task_server, task_uuid) = (server.clone(), uuid.clone());
+    task::spawn_blocking(move || {
+        let compressed = default_prover()
+            .compress(&ProverOpts::groth16(), &receipt)
+            .and_then(|receipt| Ok(bincode::serialize(&receipt)?));
+        let job = match compressed {
+            Ok(receipt) => {
+                task_server.store("snarks", &task_uuid, receipt);
+                Job::Succeeded {
+                    receipt: Some(task_server.blob_url("snarks", &task_uuid)),
+                    stats: None,
+                }
+            }
+            Err(err) => Job::Failed {
+                error: format!("{err:#}"),
+            },
+        };
+        log::info!("Snark {task_uuid} {}", job.status().to_lowercase());
+        task_server.snarks.lock().unwrap().insert(task_uuid, job);
+    });
+
+    Ok(Json(CreateRes { uuid }))
+}
+
+#[derive(Serialize)]
+struct SnarkStatusRes {
+    status: &'static str,
+    output: Option<String>,
+    error_msg: Option<String>,
+}
+
+async fn snark_status(
+    State(server): State<Arc<Server>>,
+    Path(id): Path<String>,
+) -> Result<Json<SnarkStatusRes>, ApiError> {
+    let job = server
+        .snarks
+        .lock()
+        .unwrap()
+        .get(&id)
+        .cloned()
+        .with_context(|| format!("unknown snark {id}"))?;
+    let status = job.status();
+    let (output, error_msg) = match job {
+        Job::Running { .. } => (None, None),
+        Job::Succeeded { receipt, .. } => (receipt, None),
+        Job::Failed { error } => (None, Some(error)),
+    };
+    Ok(Json(SnarkStatusRes {
+        status,
+        output,
+        error_msg,
+    }))
+}
@@ -1,41 +1,34 @@
-use alloy_primitives::{Address, U256};
+use alloy_primitives::Address;
 use anyhow::{ensure, Context, Result};
+use apps::{
+    batch::{self, BatchArgs},
+    check::Expected,
+    inspect::InspectArgs,
+    output::{OutputArgs, Report},
+    profile, prove, reverted,
+    units::{self, Denomination, Unit},
+    Block, GuestInput,
+};
 use clap::Parser;
+use erc20_counter_core::{
+    erc20::{self, Journal, IERC20},
+    journal::MetricJournal,
+    status::Status,
+};
 use erc20_counter_methods::{BALANCE_OF_ELF, BALANCE_OF_ID};
 use risc0_ethereum_contracts::encode_seal;
 use risc0_steel::{
     alloy::{
-        network::EthereumWallet,
-        providers::ProviderBuilder,
-        signers::local::PrivateKeySigner,
-        sol,
-        sol_types::{SolCall, SolValue},
+        network::EthereumWallet, providers::ProviderBuilder, signers::local::PrivateKeySigner,
+        sol_types::SolCall,
     },
     ethereum::ETH_MAINNET_CHAIN_SPEC,
 };
-use risc0_steel::{ethereum::EthEvmEnv, host::BlockNumberOrTag, Commitment, Contract};
-use risc0_zkvm::{default_prover, Digest, ExecutorEnv, ProverOpts, VerifierContext};
-use tokio::task;
+use risc0_steel::{ethereum::EthEvmEnv, host::BlockNumberOrTag, Contract};
+use risc0_zkvm::Digest;
 use tracing_subscriber::EnvFilter;
 use url::Url;
 
-sol! {
-    /// Interface to be called by the guest.
-    interface IERC20 {
-        function balanceOf(address account) external view returns (uint);
-        function totalSupply() external view returns (uint256);
-    }
-
-    /// Data committed to by the guest.
-    struct Journal {
-        Commitment commitment;
-        address tokenContract;
-        uint256 circulatingSupply;
-        uint256 pastCirculatingSupply;
-        uint256 inflationBasisPoints;
-    }
-}
-
 /// Simple program to create a proof to increment the Counter contract.
 #[derive(Parser)]
 struct Args {
@@ -71,6 +64,15 @@ struct Args {
     /// Address to query the token balance of
     #[arg(long)]
     account_2: Address,
+
+    #[command(flatten)]
+    batch: BatchArgs,
+
+    #[command(flatten)]
+    output: OutputArgs,
+
+    #[command(flatten)]
+    inspect: InspectArgs,
 }
 
 #[tokio::main]
@@ -78,31 +80,110 @@ async fn main() -> Result<()> {
     // Initialize tracing. In order to view logs, run `RUST_LOG=info cargo run`
     tracing_subscriber::fmt()
         .with_env_filter(EnvFilter::from_default_env())
+        .with_writer(std::io::stderr)
         .init();
     // Parse the command line arguments.
     let args = Args::try_parse()?;
 
+    let report = Report::new(&args.output, "balance_of", BALANCE_OF_ID)
+        .rpc(&args.eth_rpc_url)
+        .input("token_contract", args.token_contract)
+        .input("account", args.account)
+        .input("account_2", args.account_2);
+
+    if let Some(samples) = args.batch.windows() {
+        return batch::run(&args.batch, &report, BALANCE_OF_ELF, samples, |sample| {
+            let past_block = sample.past_block.expect("windows have a past block");
+            preflight(
+                &args,
+                BlockNumberOrTag::Number(sample.block),
+                BlockNumberOrTag::Number(past_block),
+            )
+        })
+        .await;
+    }
+
+    let input = profile::timed(preflight(
+        &args,
+        args.execution_block,
+        args.past_execution_block,
+    ))
+    .await?;
+    if args.inspect.is_enabled() {
+        return args.inspect.print(&input);
+    }
+    let input_hash = input.digest();
+    let proof = prove(BALANCE_OF_ELF, input).await?;
+    let receipt = &proof.receipt;
+    let journal = &receipt.journal.bytes;
+
+    // Decode and log the commitment
+    let (header, journal) = Journal::decode_with_header(journal).context("invalid journal")?;
+    ensure!(
+        header.inputHash == input_hash,
+        "journal is of another input"
+    );
+    log::info!("Steel commitment: {:?}", journal.commitment);
+    report.publish(&proof).await?;
+
+    /*
+    // ABI encode the seal.
+    let seal = encode_seal(&receipt).context("invalid receipt")?;
+
+        // Create an alloy instance of the Counter contract.
+        let contract = ICounter::new(args.counter_address, &provider);
+
+        // Call ICounter::imageID() to check that the contract has been deployed correctly.
+        let contract_image_id = Digest::from(contract.imageID().call().await?._0.0);
+        ensure!(contract_image_id == BALANCE_OF_ID.into());
+
+        // Call the increment function of the contract and wait for confirmation.
+        log::info!(
+            "Sending Tx calling {} Function of {:#}...",
+            ICounter::incrementCall::SIGNATURE,
+            contract.address()
+        );
+        let call_builder = contract.increment(receipt.journal.bytes.into(), seal.into());
+        log::debug!("Send {} {}", contract.address(), call_builder.calldata());
+        let pending_tx = call_builder.send().await?;
+        let tx_hash = *pending_tx.tx_hash();
+        let receipt = pending_tx
+            .get_receipt()
+            .await
+            .with_context(|| format!("transaction did not confirm: {}", tx_hash))?;
+        ensure!(receipt.status(), "transaction failed: {}", tx_hash);
+    */
+
+    Ok(())
+}
+
+/// Preflights the circulating supply at both blocks and returns the input of the guest.
+async fn preflight(
+    args: &Args,
+    execution_block: BlockNumberOrTag,
+    past_execution_block: BlockNumberOrTag,
+) -> Result<GuestInput> {
     // Create an alloy provider for that private key and URL.
-    let wallet = EthereumWallet::from(args.eth_wallet_private_key);
+    let wallet = EthereumWallet::from(args.eth_wallet_private_key.clone());
     let provider = ProviderBuilder::new()
         .wallet(wallet)
-        .on_http(args.eth_rpc_url);
+        .on_client(args.inspect.client(&args.eth_rpc_url));
 
     #[cfg(feature = "beacon")]
-    log::info!("Beacon commitment to block {}", args.execution_block);
+    log::info!("Beacon commitment to block {}", execution_block);
     #[cfg(feature = "history")]
     log::info!("History commitment to block {}", args.commitment_block);
 
     let builder = EthEvmEnv::builder()
         .provider(provider.clone())
-        .block_number_or_tag(args.execution_block);
+        .block_number_or_tag(execution_block);
 
     let past_builder = EthEvmEnv::builder()
         .provider(provider.clone())
-        .block_number_or_tag(args.past_execution_block);
+        .block_number_or_tag(past_execution_block);
 
     #[cfg(any(feature = "beacon", feature = "history"))]
-    let builder = builder.beacon_api(args.beacon_api_url);
+    let builder = builder.beacon_api(args.beacon_api_url.clone());
     #[cfg(feature = "history")]
     let builder = builder.commitment_block_number_or_tag(args.commitment_block);
 
@@ -132,109 +213,98 @@ async fn main() -> Result<()> {
         account: args.account_2,
     };
 
-    ///// Present Supply
-    let mut token_contract = Contract::preflight(args.token_contract, &mut env);
-    // let returns = Contract::new(contract, &env).call_builder(&call).call();
-    let total_supply = token_contract.call_builder(&supply_call).call().await?._0;
-    let balance = token_contract
-        .call_builder(&balance_of_call)
-        .call()
-        .await?
-        ._0;
-    let balance_2 = token_contract
-        .call_builder(&balance_of_call_2)
-        .call()
-        .await?
-        ._0;
-
-    let circulating_supply = total_supply - balance - balance_2;
-
-    ///// Past Supply
-    let mut token_contract = Contract::preflight(args.token_contract, &mut past_env);
-    // let returns = Contract::new(contract, &env).call_builder(&call).call();
-    let past_total_supply = token_contract.call_builder(&supply_call).call().await?._0;
-    let past_balance = token_contract
-        .call_builder(&balance_of_call)
-        .call()
-        .await?
-        ._0;
-    let past_balance_2 = token_contract
-        .call_builder(&balance_of_call_2)
-        .call()
-        .await?
-        ._0;
-
-    let past_circulating_supply = past_total_supply - past_balance - past_balance_2;
-
-    let inflation_basis_points = ((circulating_supply - past_circulating_supply)
-        * U256::from(10000))
-        / past_circulating_supply;
+    let outcome = async {
+        ///// Present Supply
+        let mut token_contract = Contract::preflight(args.token_contract, &mut env);
+        // let returns = Contract::new(contract, &env).call_builder(&call).call();
+        let total_supply = token_contract
+            .call_builder(&supply_call)
+            .call()
+            .await
+            .map_err(reverted)?
+            ._0;
+        let balance = token_contract
+            .call_builder(&balance_of_call)
+            .call()
+            .await
+            .map_err(reverted)?
+            ._0;
+        let balance_2 = token_contract
+            .call_builder(&balance_of_call_2)
+            .call()
+            .await
+            .map_err(reverted)?
+            ._0;
+
+        let circulating_supply = erc20::circulating_supply(total_supply, [balance, balance_2])?;
+
+        ///// Past Supply
+        let mut token_contract = Contract::preflight(args.token_contract, &mut past_env);
+        let past_total_supply = token_contract
+            .call_builder(&supply_call)
+            .call()
+            .await
+            .map_err(reverted)?
+            ._0;
+        let past_balance = token_contract
+            .call_builder(&balance_of_call)
+            .call()
+            .await
+            .map_err(reverted)?
+            ._0;
+        let past_balance_2 = token_contract
+            .call_builder(&balance_of_call_2)
+            .call()
+            .await
+            .map_err(reverted)?
+            ._0;
+
+        let past_circulating_supply =
+            erc20::circulating_supply(past_total_supply, [past_balance, past_balance_2])?;
+        if past_circulating_supply.is_zero() {
+            return Err(Status::NoSupply);
+        }
+
+        let inflation_basis_points =
+            erc20::inflation_basis_points(past_circulating_supply, circulating_supply)?;
+        Ok::<_, Status>((
+            circulating_supply,
+            past_circulating_supply,
+            inflation_basis_points,
+        ))
+    }
+    .await;
+    let (status, (circulating_supply, past_circulating_supply, inflation_basis_points)) =
+        Status::split(outcome);
+
+    let token = units::token(&args.eth_rpc_url, Denomination::Token(args.token_contract)).await;
+    log::info!("Curve token: {:?}", args.token_contract);
+    log::info!("Status: {}", status);
+    log::info!("Circulating Supply: {}", token.format(circulating_supply));
+    log::info!(
+        "Past Circulating Supply: {}",
+        token.format(past_circulating_supply)
+    );
+    log::info!("Inflation: {}", Unit::BPS.format(inflation_basis_points));
 
     // Finally, construct the input from the environment.
     // There are two options: Use EIP-4788 for verification by providing a Beacon API endpoint,
     // or use the regular `blockhash' opcode.
+    let block = Block::of(env.header());
     let evm_input = env.into_input().await?;
     let past_evm_input = past_env.into_input().await?;
 
-    // Create the steel proof.
-    let prove_info = task::spawn_blocking(move || {
-        let env = ExecutorEnv::builder()
-            .write(&evm_input)?
-            .write(&past_evm_input)?
-            .write(&args.token_contract)?
-            .write(&args.account)?
-            .write(&args.account_2)?
-            .build()
-            .unwrap();
-
-        default_prover().prove_with_ctx(
-            env,
-            &VerifierContext::default(),
-            BALANCE_OF_ELF,
-            &ProverOpts::groth16(),
+    GuestInput::default()
+        .at(block)
+        .expect(
+            Expected::new(status)
+                .value("circulatingSupply", circulating_supply)
+                .value("pastCirculatingSupply", past_circulating_supply)
+                .value("inflationBasisPoints", inflation_basis_points),
         )
-    })
-    .await?
-    .context("failed to create proof")?;
-    let receipt = prove_info.receipt;
-    let journal = &receipt.journal.bytes;
-
-    // Decode and log the commitment
-    let journal = Journal::abi_decode(journal, true).context("invalid journal")?;
-    log::info!("Curve token: {:?}", args.token_contract);
-    log::info!("Total Supply: {:?}", total_supply);
-    log::info!("Circulating Supply: {:?}", circulating_supply);
-    log::info!("Past Circulating Supply: {:?}", past_circulating_supply);
-    log::info!("Inflation Basis Points: {:?}", inflation_basis_points);
-    log::info!("Steel commitment: {:?}", journal.commitment);
-
-    /*
-    // ABI encode the seal.
-    let seal = encode_seal(&receipt).context("invalid receipt")?;
-
-        // Create an alloy instance of the Counter contract.
-        let contract = ICounter::new(args.counter_address, &provider);
-
-        // Call ICounter::imageID() to check that the contract has been deployed correctly.
-        let contract_image_id = Digest::from(contract.imageID().call().await?._0.0);
-        ensure!(contract_image_id == BALANCE_OF_ID.into());
-
-        // Call the increment function of the contract and wait for confirmation.
-        log::info!(
-            "Sending Tx calling {} Function of {:#}...",
-            ICounter::incrementCall::SIGNATURE,
-            contract.address()
-        );
-        let call_builder = contract.increment(receipt.journal.bytes.into(), seal.into());
-        log::debug!("Send {} {}", contract.address(), call_builder.calldata());
-        let pending_tx = call_builder.send().await?;
-        let tx_hash = *pending_tx.tx_hash();
-        let receipt = pending_tx
-            .get_receipt()
-            .await
-            .with_context(|| format!("transaction did not confirm: {}", tx_hash))?;
-        ensure!(receipt.status(), "transaction failed: {}", tx_hash);
-    */
-
-    Ok(())
+        .write(&evm_input)?
+        .write(&past_evm_input)?
+        .write(&args.token_contract)?
+        .write(&args.account)?
+        .write(&args.account_2)
 }
@@ -0,0 +1,279 @@
+use alloy_primitives::{Address, B256, U256};
+use anyhow::{ensure, Context, Result};
+use apps::{
+    batch::{self, BatchArgs},
+    check::Expected,
+    inspect::InspectArgs,
+    output::{OutputArgs, Report},
+    profile, prove, reverted,
+    units::Unit,
+    Block, GuestInput,
+};
+use clap::{ArgGroup, Parser};
+use erc20_counter_core::{
+    compound::CometMainInterface,
+    journal::MetricJournal,
+    math::{self, MathError},
+    morpho::{self, IMorpho, MORPHO_BLUE},
+    realized::{Journal, LendingMarket},
+    status::Status,
+};
+use erc20_counter_methods::{REALIZED_APR_ELF, REALIZED_APR_ID};
+use risc0_steel::alloy::{
+    network::EthereumWallet, providers::ProviderBuilder, signers::local::PrivateKeySigner,
+};
+use risc0_steel::{
+    ethereum::{EthEvmEnv, ETH_MAINNET_CHAIN_SPEC},
+    host::BlockNumberOrTag,
+    Contract, EvmBlockHeader,
+};
+use tracing_subscriber::EnvFilter;
+use url::Url;
+
+/// Proves the supply and borrow APR realized by a lending market between two blocks, from the
+/// growth of its interest indices.
+#[derive(Parser)]
+#[command(group(ArgGroup::new("market").required(true)))]
+struct Args {
+    /// Ethereum private key
+    #[arg(long, env = "ETH_WALLET_PRIVATE_KEY")]
+    eth_wallet_private_key: PrivateKeySigner,
+
+    /// Ethereum RPC endpoint URL
+    #[arg(long, env = "ETH_RPC_URL")]
+    eth_rpc_url: Url,
+
+    /// Beacon API endpoint URL
+    ///
+    /// Steel uses a beacon block commitment instead of the execution block.
+    /// This allows proofs to be validated using the EIP-4788 beacon roots contract.
+    #[cfg(any(feature = "beacon", feature = "history"))]
+    #[arg(long, env = "BEACON_API_URL")]
+    beacon_api_url: Url,
+
+    /// Ethereum block to use as the state for the contract call
+    #[arg(long, env = "EXECUTION_BLOCK", default_value_t = BlockNumberOrTag::Parent)]
+    execution_block: BlockNumberOrTag,
+
+    /// Ethereum block at the start of the measured window
+    #[arg(
+        long,
+        env = "PAST_EXECUTION_BLOCK",
+        required_unless_present = "from_block"
+    )]
+    past_execution_block: Option<BlockNumberOrTag>,
+
+    /// Ethereum block to use for the beacon block commitment.
+    #[cfg(feature = "history")]
+    #[arg(long, env = "COMMITMENT_BLOCK")]
+    commitment_block: BlockNumberOrTag,
+
+    /// Address of a Compound III (Comet) market
+    #[arg(long, group = "market")]
+    comet: Option<Address>,
+
+    /// Id of a Morpho Blue market
+    #[arg(long, group = "market")]
+    morpho_market: Option<B256>,
+
+    /// Address of Morpho Blue
+    #[arg(long, default_value_t = MORPHO_BLUE)]
+    morpho: Address,
+
+    #[command(flatten)]
+    batch: BatchArgs,
+
+    #[command(flatten)]
+    output: OutputArgs,
+
+    #[command(flatten)]
+    inspect: InspectArgs,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    // Initialize tracing. In order to view logs, run `RUST_LOG=info cargo run`
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .with_writer(std::io::stderr)
+        .init();
+    // Parse the command line arguments.
+    let args = Args::try_parse()?;
+
+    let report = Report::new(&args.output, "realized_apr", REALIZED_APR_ID).rpc(&args.eth_rpc_url);
+    let report = match (args.comet, args.morpho_market) {
+        (Some(comet), _) => report.input("comet", comet),
+        (None, Some(id)) => report
+            .input("morpho", args.morpho)
+            .input("morpho_market", id),
+        (None, None) => unreachable!("clap requires one market"),
+    };
+
+    if let Some(samples) = args.batch.windows() {
+        return batch::run(&args.batch, &report, REALIZED_APR_ELF, samples, |sample| {
+            let past_block = sample.past_block.expect("windows have a past block");
+            preflight(
+                &args,
+                BlockNumberOrTag::Number(sample.block),
+                BlockNumberOrTag::Number(past_block),
+            )
+        })
+        .await;
+    }
+
+    let past_execution_block = args
+        .past_execution_block
+        .context("missing past execution block")?;
+    let input =
+        profile::timed(preflight(&args, args.execution_block, past_execution_block)).await?;
+    if args.inspect.is_enabled() {
+        return args.inspect.print(&input);
+    }
+    let input_hash = input.digest();
+    let proof = prove(REALIZED_APR_ELF, input).await?;
+    let receipt = &proof.receipt;
+    let journal = &receipt.journal.bytes;
+
+    // Decode and log the commitment
+    let (header, journal) = Journal::decode_with_header(journal).context("invalid journal")?;
+    ensure!(
+        header.inputHash == input_hash,
+        "journal is of another input"
+    );
+    log::info!("Market: {:?} {:?}", journal.market, journal.marketId);
+    log::info!("Steel commitment: {:?}", journal.commitment);
+    log::info!("Past Steel commitment: {:?}", journal.pastCommitment);
+    report.publish(&proof).await?;
+
+    Ok(())
+}
+
+/// Preflights the interest indices at both blocks and returns the input of the guest.
+async fn preflight(
+    args: &Args,
+    execution_block: BlockNumberOrTag,
+    past_execution_block: BlockNumberOrTag,
+) -> Result<GuestInput> {
+    // Create an alloy provider for that private key and URL.
+    let wallet = EthereumWallet::from(args.eth_wallet_private_key.clone());
+    let provider = ProviderBuilder::new()
+        .wallet(wallet)
+        .on_client(args.inspect.client(&args.eth_rpc_url));
+
+    #[cfg(feature = "beacon")]
+    log::info!("Beacon commitment to block {}", execution_block);
+    #[cfg(feature = "history")]
+    log::info!("History commitment to block {}", args.commitment_block);
+
+    let builder = EthEvmEnv::builder()
+        .provider(provider.clone())
+        .block_number_or_tag(execution_block);
+    let past_builder = EthEvmEnv::builder()
+        .provider(provider.clone())
+        .block_number_or_tag(past_execution_block);
+
+    #[cfg(any(feature = "beacon", feature = "history"))]
+    let builder = builder.beacon_api(args.beacon_api_url.clone());
+    #[cfg(any(feature = "beacon", feature = "history"))]
+    let past_builder = past_builder.beacon_api(args.beacon_api_url.clone());
+    #[cfg(feature = "history")]
+    let builder = builder.commitment_block_number_or_tag(args.commitment_block);
+    #[cfg(feature = "history")]
+    let past_builder = past_builder.commitment_block_number_or_tag(args.commitment_block);
+
+    let mut env = builder.build().await?;
+    let mut past_env = past_builder.build().await?;
+    //  The `with_chain_spec` method is used to specify the chain configuration.
+    env = env.with_chain_spec(&ETH_MAINNET_CHAIN_SPEC);
+    past_env = past_env.with_chain_spec(&ETH_MAINNET_CHAIN_SPEC);
+
+    let market = match (args.comet, args.morpho_market) {
+        (Some(comet), _) => LendingMarket::Comet(comet),
+        (None, Some(id)) => LendingMarket::Morpho {
+            morpho: args.morpho,
+            id,
+        },
+        (None, None) => unreachable!("clap requires one market"),
+    };
+
+    ensure!(
+        past_env.header().timestamp() < env.header().timestamp(),
+        "past block must precede the execution block"
+    );
+
+    let outcome = async {
+        // Supply index, borrow index and the time they were last accrued, at each block.
+        let mut snapshots = Vec::with_capacity(2);
+        for env in [&mut env, &mut past_env] {
+            let mut contract = Contract::preflight(market.address(), env);
+            let snapshot = match market {
+                LendingMarket::Comet(_) => {
+                    let totals = contract
+                        .call_builder(&CometMainInterface::totalsBasicCall {})
+                        .call()
+                        .await
+                        .map_err(reverted)?
+                        ._0;
+                    (
+                        U256::from(totals.baseSupplyIndex),
+                        U256::from(totals.baseBorrowIndex),
+                        totals.lastAccrualTime.to::<u64>(),
+                    )
+                }
+                LendingMarket::Morpho { id, .. } => {
+                    let state = contract
+                        .call_builder(&IMorpho::marketCall { id })
+                        .call()
+                        .await
+                        .map_err(reverted)?
+                        ._0;
+                    (
+                        morpho::share_price(state.totalSupplyAssets, state.totalSupplyShares)?,
+                        morpho::share_price(state.totalBorrowAssets, state.totalBorrowShares)?,
+                        state.lastUpdate as u64,
+                    )
+                }
+            };
+            snapshots.push(snapshot);
+        }
+        let (supply_index, borrow_index, accrual_time) = snapshots[0];
+        let (past_supply_index, past_borrow_index, past_accrual_time) = snapshots[1];
+        let elapsed = accrual_time
+            .checked_sub(past_accrual_time)
+            .ok_or(MathError::Underflow)?;
+        let supply_apr = math::annualized_change(past_supply_index, supply_index, elapsed)?;
+        let borrow_apr = math::annualized_change(past_borrow_index, borrow_index, elapsed)?;
+        let supply_apy = math::realized_apy(past_supply_index, supply_index, elapsed)?;
+        let borrow_apy = math::realized_apy(past_borrow_index, borrow_index, elapsed)?;
+        Ok::<_, Status>((elapsed, supply_apr, supply_apy, borrow_apr, borrow_apy))
+    }
+    .await;
+    let (status, (elapsed, supply_apr, supply_apy, borrow_apr, borrow_apy)) =
+        Status::split(outcome);
+
+    log::info!("Status: {}", status);
+    log::info!("Accrual Window: {} seconds", elapsed);
+    log::info!("Realized Supply APR: {}", Unit::RATE.format(supply_apr));
+    log::info!("Realized Supply APY: {}", Unit::RATE.format(supply_apy));
+    log::info!("Realized Borrow APR: {}", Unit::RATE.format(borrow_apr));
+    log::info!("Realized Borrow APY: {}", Unit::RATE.format(borrow_apy));
+
+    // Finally, construct the input from the environment.
+    let block = Block::of(env.header());
+    let evm_input = env.into_input().await?;
+    let past_evm_input = past_env.into_input().await?;
+
+    GuestInput::default()
+        .at(block)
+        .expect(
+            Expected::new(status)
+                .value("realizedSupplyApr", supply_apr)
+                .value("realizedSupplyApy", supply_apy)
+                .value("realizedBorrowApr", borrow_apr)
+                .value("realizedBorrowApy", borrow_apy)
+                .value("elapsed", U256::from(elapsed)),
+        )
+        .write(&evm_input)?
+        .write(&past_evm_input)?
+        .write(&market)
+}
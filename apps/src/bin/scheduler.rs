@@ -0,0 +1,442 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use alloy_primitives::{Address, B256};
+use anyhow::{bail, ensure, Context, Result};
+use apps::{
+    output::Record,
+    runner::{self, Retry, Runner, Task},
+};
+use clap::Parser;
+use futures::future::join_all;
+use risc0_steel::alloy::{
+    providers::{Provider, ProviderBuilder},
+    signers::local::PrivateKeySigner,
+};
+use serde::{Deserialize, Serialize};
+use tokio::{signal, time};
+use tracing_subscriber::EnvFilter;
+
+/// Name of the status file in the state directory.
+const STATUS_FILE: &str = "status.json";
+/// Name of the file every job appends its results to, in its directory.
+const RESULTS_FILE: &str = "results.jsonl";
+/// Interval between two polls of the chain head, for jobs run every so many blocks.
+const BLOCK_POLL_INTERVAL: Duration = Duration::from_secs(12);
+
+/// Daemon proving metrics on a cadence, by running their publishers.
+///
+/// Every job of the configuration is run when enough blocks or seconds passed since its last run,
+/// retried with a backoff when it fails, and its records and receipts are kept in the state
+/// directory, together with a status file listing the state of every job.
+#[derive(Parser)]
+struct Args {
+    /// JSON file configuring the jobs
+    #[arg(long)]
+    config: PathBuf,
+
+    /// Directory to write the receipts, records and status to
+    #[arg(long, default_value = "scheduler")]
+    state_dir: PathBuf,
+
+    /// Directory of the publishers; defaults to the directory of this executable
+    #[arg(long)]
+    bin_dir: Option<PathBuf>,
+
+    /// Ethereum private key signing the submissions of the jobs that submit their receipts
+    #[arg(long, env = "ETH_WALLET_PRIVATE_KEY")]
+    eth_wallet_private_key: Option<PrivateKeySigner>,
+}
+
+/// Jobs of the daemon.
+#[derive(Debug, Deserialize)]
+struct Config {
+    jobs: Vec<Job>,
+}
+
+/// Metric proven on a cadence.
+#[derive(Clone, Debug, Deserialize)]
+struct Job {
+    /// Name of the job, naming its directory in the state directory.
+    name: String,
+    #[serde(flatten)]
+    task: Task,
+    cadence: Cadence,
+    #[serde(default)]
+    retry: Retry,
+    /// Contract to submit the receipts to, with `IMetricConsumer.submit`.
+    #[serde(default)]
+    submit: Option<Address>,
+}
+
+/// When a job is run.
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Cadence {
+    /// When the chain advanced this many blocks since the last run.
+    Blocks(u64),
+    /// When this many seconds passed since the last run.
+    Seconds(u64),
+}
+
+impl Job {
+    fn validate(&self, runner: &Runner, signer: Option<&PrivateKeySigner>) -> Result<()> {
+        ensure!(
+            !self.name.is_empty()
+                && self
+                    .name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'),
+            "job name {:?} is not made of letters, digits, '-' and '_'",
+            self.name
+        );
+        runner.check(&self.task)?;
+        match self.cadence {
+            Cadence::Blocks(0) | Cadence::Seconds(0) => bail!("cadence of zero"),
+            Cadence::Blocks(_) => ensure!(
+                self.task.eth_rpc_url.is_some(),
+                "cadence in blocks without eth_rpc_url"
+            ),
+            Cadence::Seconds(_) => {}
+        }
+        ensure!(self.retry.attempts > 0, "no attempts");
+        if self.submit.is_some() {
+            ensure!(
+                self.task.eth_rpc_url.is_some(),
+                "submission without eth_rpc_url"
+            );
+            ensure!(
+                signer.is_some(),
+                "submission without ETH_WALLET_PRIVATE_KEY"
+            );
+        }
+        Ok(())
+    }
+}
+
+/// State of the jobs, as written to the status file.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+struct Status {
+    /// Time of the last update, as a Unix timestamp.
+    updated: u64,
+    jobs: BTreeMap<String, JobStatus>,
+}
+
+/// State of a job.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+struct JobStatus {
+    /// Successful runs.
+    runs: u64,
+    /// Failed runs since the last successful one.
+    failures: u64,
+    /// Start of the last run, as a Unix timestamp.
+    last_run: Option<u64>,
+    /// Start of the last successful run, as a Unix timestamp.
+    last_success: Option<u64>,
+    /// Chain head at the start of the last run, for cadences in blocks.
+    last_head: Option<u64>,
+    /// Error of the last run, unless it succeeded.
+    last_error: Option<String>,
+    /// Record of the last successful run.
+    last_record: Option<Record>,
+    /// Transaction of the last submission.
+    last_tx: Option<B256>,
+}
+
+impl JobStatus {
+    /// Time at which a job run every `seconds` is due, as a Unix timestamp.
+    fn due_time(&self, seconds: u64) -> u64 {
+        self.last_run
+            .map_or(0, |last_run| last_run.saturating_add(seconds))
+    }
+
+    /// Whether a job run every `blocks` blocks is due at the chain `head`.
+    fn is_due_at(&self, blocks: u64, head: u64) -> bool {
+        self.last_head
+            .is_none_or(|last_head| head >= last_head.saturating_add(blocks))
+    }
+}
+
+/// Result of a run, as appended to the results of its job.
+#[derive(Serialize)]
+struct Published<'a> {
+    job: &'a str,
+    attempts: u32,
+    /// Transaction submitting the receipt, if it was submitted.
+    transaction: Option<B256>,
+    #[serde(flatten)]
+    record: &'a Record,
+}
+
+struct Daemon {
+    runner: Runner,
+    state_dir: PathBuf,
+    signer: Option<PrivateKeySigner>,
+    status: Mutex<Status>,
+}
+
+impl Daemon {
+    fn status(&self, job: &str) -> JobStatus {
+        let status = self.status.lock().unwrap();
+        status.jobs.get(job).cloned().unwrap_or_default()
+    }
+
+    /// Updates the status of `job` and rewrites the status file.
+    ///
+    /// A status file that cannot be written is logged, and written again with the next update.
+    fn update(&self, job: &str, update: impl FnOnce(&mut JobStatus)) {
+        let mut status = self.status.lock().unwrap();
+        update(status.jobs.entry(job.to_string()).or_default());
+        status.updated = now();
+        if let Err(err) = save_status(&self.state_dir.join(STATUS_FILE), &status) {
+            log::error!("Failed to save the status of job {job}: {err:#}");
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    // Initialize tracing. In order to view logs, run `RUST_LOG=info cargo run`
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .init();
+    // Parse the command line arguments.
+    let args = Args::parse();
+
+    let config: Config = serde_json::from_slice(
+        &fs::read(&args.config)
+            .with_context(|| format!("failed to read {}", args.config.display()))?,
+    )
+    .context("invalid config")?;
+    let runner = Runner::new(args.bin_dir)?;
+    let mut names = BTreeSet::new();
+    for job in &config.jobs {
+        ensure!(names.insert(&job.name), "duplicate job {}", job.name);
+        job.validate(&runner, args.eth_wallet_private_key.as_ref())
+            .with_context(|| format!("invalid job {}", job.name))?;
+    }
+
+    // Resume from the status of the previous daemon, for the jobs that are still configured.
+    fs::create_dir_all(&args.state_dir)?;
+    let mut status = load_status(&args.state_dir.join(STATUS_FILE))?;
+    status.jobs.retain(|name, _| names.contains(name));
+    let daemon = Arc::new(Daemon {
+        runner,
+        state_dir: args.state_dir,
+        signer: args.eth_wallet_private_key,
+        status: Mutex::new(status),
+    });
+
+    log::info!("Scheduling {} jobs", config.jobs.len());
+    let jobs = config
+        .jobs
+        .into_iter()
+        .map(|job| schedule(daemon.clone(), job));
+    // Jobs run until the daemon is stopped: their failures are logged and retried on the next
+    // run, so that one job cannot stop the others.
+    tokio::select! {
+        _ = join_all(jobs) => Ok(()),
+        result = signal::ctrl_c() => {
+            log::info!("Shutting down");
+            Ok(result?)
+        }
+    }
+}
+
+fn load_status(path: &Path) -> Result<Status> {
+    if !path.exists() {
+        return Ok(Status::default());
+    }
+    let bytes = fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+    serde_json::from_slice(&bytes).with_context(|| format!("invalid status {}", path.display()))
+}
+
+fn save_status(path: &Path, status: &Status) -> Result<()> {
+    // Replace the file at once, so that readers never see a partial status.
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, serde_json::to_vec_pretty(status)?)
+        .with_context(|| format!("failed to write {}", tmp.display()))?;
+    fs::rename(&tmp, path).with_context(|| format!("failed to write {}", path.display()))
+}
+
+/// Runs `job` on its cadence, forever.
+async fn schedule(daemon: Arc<Daemon>, job: Job) {
+    let dir = daemon.state_dir.join(&job.name);
+    loop {
+        let head = wait(&daemon, &job).await;
+        let started = now();
+        daemon.update(&job.name, |status| {
+            status.last_run = Some(started);
+            status.last_head = head.or(status.last_head);
+        });
+
+        log::info!("Running job {}", job.name);
+        match run(&daemon, &job, &dir, started).await {
+            Ok((record, tx)) => {
+                log::info!(
+                    "Job {} proved {} at block {:?}",
+                    job.name,
+                    record.metric,
+                    record.block_number
+                );
+                daemon.update(&job.name, |status| {
+                    status.runs += 1;
+                    status.failures = 0;
+                    status.last_success = Some(started);
+                    status.last_error = None;
+                    status.last_record = Some(record);
+                    status.last_tx = tx.or(status.last_tx);
+                });
+            }
+            Err(err) => {
+                log::error!("Job {} failed: {err:#}", job.name);
+                daemon.update(&job.name, |status| {
+                    status.failures += 1;
+                    status.last_error = Some(format!("{err:#}"));
+                });
+            }
+        }
+    }
+}
+
+/// Waits until `job` is due, returning the chain head for cadences in blocks.
+async fn wait(daemon: &Daemon, job: &Job) -> Option<u64> {
+    let status = daemon.status(&job.name);
+    match job.cadence {
+        Cadence::Seconds(seconds) => {
+            let delay = status.due_time(seconds).saturating_sub(now());
+            time::sleep(Duration::from_secs(delay)).await;
+            None
+        }
+        Cadence::Blocks(blocks) => {
+            let provider = ProviderBuilder::new().on_http(job.task.eth_rpc_url.clone()?);
+            loop {
+                match provider.get_block_number().await {
+                    Ok(head) if status.is_due_at(blocks, head) => return Some(head),
+                    Ok(_) => {}
+                    Err(err) => log::warn!("Job {} failed to get the chain head: {err}", job.name),
+                }
+                time::sleep(BLOCK_POLL_INTERVAL).await;
+            }
+        }
+    }
+}
+
+/// Proves `job`, submits its receipt if configured, and appends the result to its results.
+async fn run(
+    daemon: &Daemon,
+    job: &Job,
+    dir: &Path,
+    started: u64,
+) -> Result<(Record, Option<B256>)> {
+    fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    let receipt = dir.join(format!("{started}.bin"));
+    let run = daemon
+        .runner
+        .run_with_retries(&job.task, &receipt, &job.retry)
+        .await?;
+
+    let submission = match (job.submit, &job.task.eth_rpc_url, &daemon.signer) {
+        (Some(contract), Some(eth_rpc_url), Some(signer)) => Some(
+            runner::submit(eth_rpc_url, signer, contract, &run.receipt)
+                .await
+                .context("failed to submit the receipt"),
+        ),
+        _ => None,
+    };
+    let tx = submission.transpose();
+    append(
+        &dir.join(RESULTS_FILE),
+        &Published {
+            job: &job.name,
+            attempts: run.attempts,
+            transaction: tx.as_ref().ok().copied().flatten(),
+            record: &run.record,
+        },
+    )?;
+    Ok((run.record, tx?))
+}
+
+fn append(path: &Path, published: &Published) -> Result<()> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("failed to open {}", path.display()))?;
+    let mut line = serde_json::to_vec(published)?;
+    line.push(b'\n');
+    file.write_all(&line)
+        .with_context(|| format!("failed to write {}", path.display()))
+}
+
+/// Current time as a Unix timestamp.
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    fn ran(last_run: Option<u64>, last_head: Option<u64>) -> JobStatus {
+        JobStatus {
+            last_run,
+            last_head,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn jobs_are_due_a_cadence_after_their_last_run() {
+        assert_eq!(ran(None, None).due_time(60), 0);
+        assert_eq!(ran(Some(1_000), None).due_time(60), 1_060);
+        assert_eq!(ran(Some(u64::MAX), None).due_time(60), u64::MAX);
+
+        assert!(ran(None, None).is_due_at(100, 5));
+        assert!(!ran(None, Some(1_000)).is_due_at(100, 1_099));
+        assert!(ran(None, Some(1_000)).is_due_at(100, 1_100));
+        assert!(!ran(None, Some(u64::MAX)).is_due_at(100, u64::MAX - 1));
+    }
+
+    #[test]
+    fn status_survives_a_restart() {
+        let dir = env::temp_dir().join(format!("scheduler-status-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(STATUS_FILE);
+        assert!(load_status(&path).unwrap().jobs.is_empty());
+
+        let status = Status {
+            updated: 1_060,
+            jobs: BTreeMap::from([(
+                "lido".to_string(),
+                JobStatus {
+                    runs: 3,
+                    failures: 1,
+                    last_error: Some("rpc unavailable".to_string()),
+                    ..ran(Some(1_000), Some(21_000_000))
+                },
+            )]),
+        };
+        save_status(&path, &status).unwrap();
+        let loaded = load_status(&path).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(loaded.updated, 1_060);
+        let job = &loaded.jobs["lido"];
+        assert_eq!((job.runs, job.failures), (3, 1));
+        assert_eq!(job.last_run, Some(1_000));
+        assert_eq!(job.last_head, Some(21_000_000));
+        assert_eq!(job.last_error.as_deref(), Some("rpc unavailable"));
+        assert_eq!(job.due_time(60), 1_060);
+    }
+}
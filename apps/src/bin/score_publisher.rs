@@ -0,0 +1,97 @@
+use std::path::PathBuf;
+
+use anyhow::{ensure, Context, Result};
+use apps::{
+    check::Expected,
+    output::{OutputArgs, Report},
+    prove,
+    score::{self, PolicyFile, ProtocolReceipts},
+    units::Unit,
+};
+use clap::Parser;
+use erc20_counter_core::{journal::MetricJournal, score::Journal, status::Status};
+use erc20_counter_methods::{SCORE_ELF, SCORE_ID};
+use tracing_subscriber::EnvFilter;
+
+/// Proves an impact score per protocol, combining verified metrics with a weighting policy.
+#[derive(Parser)]
+struct Args {
+    /// JSON file with the weight and normalization of every metric
+    #[arg(long)]
+    policy: PathBuf,
+
+    /// Succinct receipts of a protocol, one per policy metric in policy order, as
+    /// `name=receipt[,receipt...]`
+    #[arg(long = "protocol", required = true)]
+    protocols: Vec<ProtocolReceipts>,
+
+    #[command(flatten)]
+    output: OutputArgs,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    // Initialize tracing. In order to view logs, run `RUST_LOG=info cargo run`
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .with_writer(std::io::stderr)
+        .init();
+    // Parse the command line arguments.
+    let args = Args::try_parse()?;
+
+    let names: Vec<&str> = args
+        .protocols
+        .iter()
+        .map(|protocol| protocol.name.as_str())
+        .collect();
+    let report = Report::new(&args.output, "score", SCORE_ID)
+        .input("policy", args.policy.display())
+        .inputs("protocols", &names);
+
+    let policy = PolicyFile::load(&args.policy)?.to_policy()?;
+    let (input, scores) = score::input(&policy, &args.protocols)?;
+    for (protocol, score) in args.protocols.iter().zip(&scores) {
+        match score {
+            Ok(score) => log::info!(
+                "Expected score of {}: {}",
+                protocol.name,
+                Unit::WAD.format(*score)
+            ),
+            Err(status) => log::warn!("Status of {}: {}", protocol.name, status),
+        }
+    }
+
+    // Each score is zero unless its status is ok, like in the guest.
+    let expected = args.protocols.iter().zip(&scores).fold(
+        Expected::default(),
+        |expected, (protocol, score)| {
+            let (_, score) = Status::split(*score);
+            expected.value(&format!("scores[{}].score", protocol.name), score)
+        },
+    );
+    let input = input.expect(expected);
+    let input_hash = input.digest();
+    let proof = prove(SCORE_ELF, input).await?;
+    let receipt = &proof.receipt;
+
+    // Decode and log the scores
+    let (header, journal) =
+        Journal::decode_with_header(&receipt.journal.bytes).context("invalid journal")?;
+    ensure!(
+        header.inputHash == input_hash,
+        "journal is of another input"
+    );
+    log::info!("Policy digest: {:?}", journal.policyDigest);
+    for score in &journal.scores {
+        log::info!(
+            "Score of {} ({}): {}, status {}",
+            score.name,
+            score.subject,
+            Unit::WAD.format(score.score),
+            score.status
+        );
+    }
+    report.publish(&proof).await?;
+
+    Ok(())
+}
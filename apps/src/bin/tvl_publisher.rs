@@ -0,0 +1,317 @@
+use std::collections::HashMap;
+
+use alloy_primitives::{Address, U256};
+use anyhow::{ensure, Context, Result};
+use apps::{
+    batch::{self, BatchArgs},
+    check::Expected,
+    inspect::InspectArgs,
+    output::{OutputArgs, Report},
+    price_routes, profile, prove, reverted,
+    units::{self, Denomination},
+    Block, GuestInput,
+};
+use clap::Parser;
+use erc20_counter_core::{
+    erc20::IERC20,
+    journal::MetricJournal,
+    math::{self, mul_div, Rounding},
+    pricing::{QuoterV2, SwapPath, QUOTER_V2},
+    status::Status,
+    tvl::{HoldingSpec, Journal, USDC},
+};
+use erc20_counter_methods::{TVL_ELF, TVL_ID};
+use risc0_steel::alloy::{
+    network::EthereumWallet, providers::ProviderBuilder, signers::local::PrivateKeySigner,
+};
+use risc0_steel::{
+    ethereum::{EthEvmEnv, ETH_MAINNET_CHAIN_SPEC},
+    host::BlockNumberOrTag,
+    Contract,
+};
+use tracing_subscriber::EnvFilter;
+use url::Url;
+
+/// Proves the total value locked in a set of protocol contracts at one or two blocks.
+#[derive(Parser)]
+struct Args {
+    /// Ethereum private key
+    #[arg(long, env = "ETH_WALLET_PRIVATE_KEY")]
+    eth_wallet_private_key: PrivateKeySigner,
+
+    /// Ethereum RPC endpoint URL
+    #[arg(long, env = "ETH_RPC_URL")]
+    eth_rpc_url: Url,
+
+    /// Beacon API endpoint URL
+    ///
+    /// Steel uses a beacon block commitment instead of the execution block.
+    /// This allows proofs to be validated using the EIP-4788 beacon roots contract.
+    #[cfg(any(feature = "beacon", feature = "history"))]
+    #[arg(long, env = "BEACON_API_URL")]
+    beacon_api_url: Url,
+
+    /// Ethereum block to use as the state for the contract call
+    #[arg(long, env = "EXECUTION_BLOCK", default_value_t = BlockNumberOrTag::Parent)]
+    execution_block: BlockNumberOrTag,
+
+    /// Optional Ethereum block to compare the TVL against
+    #[arg(long, env = "PAST_EXECUTION_BLOCK")]
+    past_execution_block: Option<BlockNumberOrTag>,
+
+    /// Ethereum block to use for the beacon block commitment.
+    #[cfg(feature = "history")]
+    #[arg(long, env = "COMMITMENT_BLOCK")]
+    commitment_block: BlockNumberOrTag,
+
+    /// Token balance to include in the TVL, as `holder:token`
+    #[arg(long = "holding", required = true)]
+    holdings: Vec<HoldingSpec>,
+
+    /// Token in which the TVL is valued
+    #[arg(long, default_value_t = USDC)]
+    numeraire: Address,
+
+    /// Uniswap V3 route pricing a token in the numeraire, as `token:fee:token...`
+    #[arg(long = "price-path")]
+    price_paths: Vec<SwapPath>,
+
+    #[command(flatten)]
+    batch: BatchArgs,
+
+    #[command(flatten)]
+    output: OutputArgs,
+
+    #[command(flatten)]
+    inspect: InspectArgs,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    // Initialize tracing. In order to view logs, run `RUST_LOG=info cargo run`
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .with_writer(std::io::stderr)
+        .init();
+    // Parse the command line arguments.
+    let args = Args::try_parse()?;
+
+    let holdings: Vec<String> = args
+        .holdings
+        .iter()
+        .map(|holding| format!("{}:{}", holding.holder, holding.token))
+        .collect();
+    let report = Report::new(&args.output, "tvl", TVL_ID)
+        .rpc(&args.eth_rpc_url)
+        .inputs("holdings", &holdings)
+        .input("numeraire", args.numeraire);
+
+    if let Some(samples) = args.batch.windows() {
+        return batch::run(&args.batch, &report, TVL_ELF, samples, |sample| {
+            preflight(
+                &args,
+                BlockNumberOrTag::Number(sample.block),
+                sample.past_block.map(BlockNumberOrTag::Number),
+            )
+        })
+        .await;
+    }
+
+    let input = profile::timed(preflight(
+        &args,
+        args.execution_block,
+        args.past_execution_block,
+    ))
+    .await?;
+    if args.inspect.is_enabled() {
+        return args.inspect.print(&input);
+    }
+    let input_hash = input.digest();
+    let proof = prove(TVL_ELF, input).await?;
+    let receipt = &proof.receipt;
+    let journal = &receipt.journal.bytes;
+
+    // Decode and log the commitment
+    let (header, journal) = Journal::decode_with_header(journal).context("invalid journal")?;
+    ensure!(
+        header.inputHash == input_hash,
+        "journal is of another input"
+    );
+    log::info!("Steel commitment: {:?}", journal.commitment);
+    log::info!("Past Steel commitment: {:?}", journal.pastCommitment);
+    report.publish(&proof).await?;
+
+    Ok(())
+}
+
+/// Preflights the holdings at the block and, if given, the past block, and returns the input of
+/// the guest.
+async fn preflight(
+    args: &Args,
+    execution_block: BlockNumberOrTag,
+    past_execution_block: Option<BlockNumberOrTag>,
+) -> Result<GuestInput> {
+    // Routes pricing every held token; the numeraire needs none.
+    let routes = price_routes(
+        &args.price_paths,
+        args.holdings.iter().map(|holding| holding.token),
+        args.numeraire,
+    )?;
+
+    // Create an alloy provider for that private key and URL.
+    let wallet = EthereumWallet::from(args.eth_wallet_private_key.clone());
+    let provider = ProviderBuilder::new()
+        .wallet(wallet)
+        .on_client(args.inspect.client(&args.eth_rpc_url));
+
+    #[cfg(feature = "beacon")]
+    log::info!("Beacon commitment to block {}", execution_block);
+    #[cfg(feature = "history")]
+    log::info!("History commitment to block {}", args.commitment_block);
+
+    let builder = EthEvmEnv::builder()
+        .provider(provider.clone())
+        .block_number_or_tag(execution_block);
+    #[cfg(any(feature = "beacon", feature = "history"))]
+    let builder = builder.beacon_api(args.beacon_api_url.clone());
+    #[cfg(feature = "history")]
+    let builder = builder.commitment_block_number_or_tag(args.commitment_block);
+    let mut env = builder.build().await?;
+    //  The `with_chain_spec` method is used to specify the chain configuration.
+    env = env.with_chain_spec(&ETH_MAINNET_CHAIN_SPEC);
+
+    let mut past_env = None;
+    if let Some(past_execution_block) = past_execution_block {
+        let past_builder = EthEvmEnv::builder()
+            .provider(provider.clone())
+            .block_number_or_tag(past_execution_block);
+        #[cfg(any(feature = "beacon", feature = "history"))]
+        let past_builder = past_builder.beacon_api(args.beacon_api_url.clone());
+        #[cfg(feature = "history")]
+        let past_builder = past_builder.commitment_block_number_or_tag(args.commitment_block);
+        past_env = Some(
+            past_builder
+                .build()
+                .await?
+                .with_chain_spec(&ETH_MAINNET_CHAIN_SPEC),
+        );
+    }
+
+    let numeraire = units::token(&args.eth_rpc_url, Denomination::Token(args.numeraire)).await;
+    let outcome = async {
+        // Balances and their values at the execution block and, if given, at the past block.
+        let mut valuations = Vec::new();
+        for env in [&mut env].into_iter().chain(past_env.as_mut()) {
+            // Price of one whole token in base units of the numeraire, quoted once per token.
+            let mut prices: HashMap<Address, (U256, U256)> = HashMap::new();
+            let mut valuation = Vec::with_capacity(args.holdings.len());
+            for holding in &args.holdings {
+                let mut token_contract = Contract::preflight(holding.token, &mut *env);
+                let balance = token_contract
+                    .call_builder(&IERC20::balanceOfCall {
+                        account: holding.holder,
+                    })
+                    .call()
+                    .await
+                    .map_err(reverted)?
+                    ._0;
+                let (price, unit) = match prices.get(&holding.token) {
+                    Some(price) => *price,
+                    None => {
+                        let decimals = token_contract
+                            .call_builder(&IERC20::decimalsCall {})
+                            .call()
+                            .await
+                            .map_err(reverted)?
+                            ._0;
+                        let unit = math::pow10(decimals)?;
+                        let price = match routes.get(&holding.token) {
+                            None => unit,
+                            Some(path) => {
+                                Contract::preflight(QUOTER_V2, &mut *env)
+                                    .call_builder(&QuoterV2::quoteExactInputCall {
+                                        path: path.encode(),
+                                        amountIn: unit,
+                                    })
+                                    .call()
+                                    .await
+                                    .map_err(reverted)?
+                                    .amountOut
+                            }
+                        };
+                        prices.insert(holding.token, (price, unit));
+                        (price, unit)
+                    }
+                };
+                let value = mul_div(balance, price, unit, Rounding::Down)?;
+                log::info!(
+                    "{} holds {} of {}, valued {}",
+                    holding.holder,
+                    balance,
+                    holding.token,
+                    numeraire.format(value)
+                );
+                valuation.push(value);
+            }
+            valuations.push(valuation);
+        }
+
+        let total_values = valuations
+            .iter()
+            .map(|valuation| math::sum(valuation.iter().copied()))
+            .collect::<math::Result<Vec<U256>>>()?;
+        let total_value_change = match total_values.get(1) {
+            Some(past_total_value) => {
+                Some(math::signed_change(*past_total_value, total_values[0])?)
+            }
+            None => None,
+        };
+        Ok::<_, Status>((total_values, total_value_change))
+    }
+    .await;
+    let (status, (total_values, total_value_change)) = Status::split(outcome);
+    log::info!("Status: {}", status);
+    if let Some(total_value) = total_values.first() {
+        log::info!("Total Value Locked: {}", numeraire.format(*total_value));
+    }
+    if let Some(past_total_value) = total_values.get(1) {
+        log::info!(
+            "Past Total Value Locked: {}",
+            numeraire.format(*past_total_value)
+        );
+    }
+    if let Some(total_value_change) = total_value_change {
+        log::info!(
+            "Total Value Locked Change: {}",
+            numeraire.format(total_value_change)
+        );
+    }
+
+    // Finally, construct the input from the environment.
+    let block = Block::of(env.header());
+    let evm_input = env.into_input().await?;
+    let past_evm_input = match past_env {
+        Some(past_env) => Some(past_env.into_input().await?),
+        None => None,
+    };
+
+    GuestInput::default()
+        .at(block)
+        .expect(
+            Expected::new(status)
+                .value(
+                    "totalValue",
+                    total_values.first().copied().unwrap_or_default(),
+                )
+                .value(
+                    "pastTotalValue",
+                    total_values.get(1).copied().unwrap_or_default(),
+                )
+                .value("totalValueChange", total_value_change.unwrap_or_default()),
+        )
+        .write(&evm_input)?
+        .write(&past_evm_input)?
+        .write(&args.holdings)?
+        .write(&args.numeraire)?
+        .write(&args.price_paths)
+}
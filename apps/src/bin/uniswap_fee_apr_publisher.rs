@@ -0,0 +1,368 @@
+use alloy_primitives::{Address, U256};
+use anyhow::{ensure, Context, Result};
+use apps::{
+    batch::{self, BatchArgs},
+    check::Expected,
+    inspect::InspectArgs,
+    output::{OutputArgs, Report},
+    price_routes, profile, prove, reverted,
+    units::{self, Denomination, Unit},
+    Block, GuestInput,
+};
+use clap::Parser;
+use erc20_counter_core::{
+    erc20::IERC20,
+    journal::MetricJournal,
+    math::{self, mul_div, Rounding},
+    pricing::{QuoterV2, SwapPath, QUOTER_V2},
+    status::Status,
+    uniswap::{self, IUniswapV3Pool, Journal},
+};
+use erc20_counter_methods::{UNISWAP_FEE_APR_ELF, UNISWAP_FEE_APR_ID};
+use risc0_steel::alloy::{
+    network::EthereumWallet, providers::ProviderBuilder, signers::local::PrivateKeySigner,
+};
+use risc0_steel::{
+    ethereum::{EthEvmEnv, ETH_MAINNET_CHAIN_SPEC},
+    host::BlockNumberOrTag,
+    Contract, EvmBlockHeader,
+};
+use tracing_subscriber::EnvFilter;
+use url::Url;
+
+/// Proves the fee APR earned by in-range liquidity of a Uniswap V3 pool between two blocks.
+#[derive(Parser)]
+struct Args {
+    /// Ethereum private key
+    #[arg(long, env = "ETH_WALLET_PRIVATE_KEY")]
+    eth_wallet_private_key: PrivateKeySigner,
+
+    /// Ethereum RPC endpoint URL
+    #[arg(long, env = "ETH_RPC_URL")]
+    eth_rpc_url: Url,
+
+    /// Beacon API endpoint URL
+    ///
+    /// Steel uses a beacon block commitment instead of the execution block.
+    /// This allows proofs to be validated using the EIP-4788 beacon roots contract.
+    #[cfg(any(feature = "beacon", feature = "history"))]
+    #[arg(long, env = "BEACON_API_URL")]
+    beacon_api_url: Url,
+
+    /// Ethereum block to use as the state for the contract call
+    #[arg(long, env = "EXECUTION_BLOCK", default_value_t = BlockNumberOrTag::Parent)]
+    execution_block: BlockNumberOrTag,
+
+    /// Ethereum block at the start of the measured window
+    #[arg(
+        long,
+        env = "PAST_EXECUTION_BLOCK",
+        required_unless_present = "from_block"
+    )]
+    past_execution_block: Option<BlockNumberOrTag>,
+
+    /// Ethereum block to use for the beacon block commitment.
+    #[cfg(feature = "history")]
+    #[arg(long, env = "COMMITMENT_BLOCK")]
+    commitment_block: BlockNumberOrTag,
+
+    /// Address of the Uniswap V3 pool
+    #[arg(long)]
+    pool: Address,
+
+    /// Token in which fees and liquidity are valued
+    #[arg(long)]
+    numeraire: Address,
+
+    /// Uniswap V3 route pricing a pool token in the numeraire, as `token:fee:token...`
+    #[arg(long = "price-path")]
+    price_paths: Vec<SwapPath>,
+
+    #[command(flatten)]
+    batch: BatchArgs,
+
+    #[command(flatten)]
+    output: OutputArgs,
+
+    #[command(flatten)]
+    inspect: InspectArgs,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    // Initialize tracing. In order to view logs, run `RUST_LOG=info cargo run`
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .with_writer(std::io::stderr)
+        .init();
+    // Parse the command line arguments.
+    let args = Args::try_parse()?;
+
+    let report = Report::new(&args.output, "uniswap_fee_apr", UNISWAP_FEE_APR_ID)
+        .rpc(&args.eth_rpc_url)
+        .input("pool", args.pool)
+        .input("numeraire", args.numeraire);
+
+    if let Some(samples) = args.batch.windows() {
+        return batch::run(
+            &args.batch,
+            &report,
+            UNISWAP_FEE_APR_ELF,
+            samples,
+            |sample| {
+                let past_block = sample.past_block.expect("windows have a past block");
+                preflight(
+                    &args,
+                    BlockNumberOrTag::Number(sample.block),
+                    BlockNumberOrTag::Number(past_block),
+                )
+            },
+        )
+        .await;
+    }
+
+    let past_execution_block = args
+        .past_execution_block
+        .context("missing past execution block")?;
+    let input =
+        profile::timed(preflight(&args, args.execution_block, past_execution_block)).await?;
+    if args.inspect.is_enabled() {
+        return args.inspect.print(&input);
+    }
+    let input_hash = input.digest();
+    let proof = prove(UNISWAP_FEE_APR_ELF, input).await?;
+    let receipt = &proof.receipt;
+    let journal = &receipt.journal.bytes;
+
+    // Decode and log the commitment
+    let (header, journal) = Journal::decode_with_header(journal).context("invalid journal")?;
+    ensure!(
+        header.inputHash == input_hash,
+        "journal is of another input"
+    );
+    log::info!("Pool: {:?}", journal.pool);
+    log::info!("Steel commitment: {:?}", journal.commitment);
+    log::info!("Past Steel commitment: {:?}", journal.pastCommitment);
+    report.publish(&proof).await?;
+
+    Ok(())
+}
+
+/// Preflights the pool fee growth at both blocks and returns the input of the guest.
+async fn preflight(
+    args: &Args,
+    execution_block: BlockNumberOrTag,
+    past_execution_block: BlockNumberOrTag,
+) -> Result<GuestInput> {
+    // Create an alloy provider for that private key and URL.
+    let wallet = EthereumWallet::from(args.eth_wallet_private_key.clone());
+    let provider = ProviderBuilder::new()
+        .wallet(wallet)
+        .on_client(args.inspect.client(&args.eth_rpc_url));
+
+    #[cfg(feature = "beacon")]
+    log::info!("Beacon commitment to block {}", execution_block);
+    #[cfg(feature = "history")]
+    log::info!("History commitment to block {}", args.commitment_block);
+
+    let builder = EthEvmEnv::builder()
+        .provider(provider.clone())
+        .block_number_or_tag(execution_block);
+    let past_builder = EthEvmEnv::builder()
+        .provider(provider.clone())
+        .block_number_or_tag(past_execution_block);
+
+    #[cfg(any(feature = "beacon", feature = "history"))]
+    let builder = builder.beacon_api(args.beacon_api_url.clone());
+    #[cfg(any(feature = "beacon", feature = "history"))]
+    let past_builder = past_builder.beacon_api(args.beacon_api_url.clone());
+    #[cfg(feature = "history")]
+    let builder = builder.commitment_block_number_or_tag(args.commitment_block);
+    #[cfg(feature = "history")]
+    let past_builder = past_builder.commitment_block_number_or_tag(args.commitment_block);
+
+    let mut env = builder.build().await?;
+    let mut past_env = past_builder.build().await?;
+    //  The `with_chain_spec` method is used to specify the chain configuration.
+    env = env.with_chain_spec(&ETH_MAINNET_CHAIN_SPEC);
+    past_env = past_env.with_chain_spec(&ETH_MAINNET_CHAIN_SPEC);
+
+    let timestamp = env.header().timestamp();
+    let past_timestamp = past_env.header().timestamp();
+    ensure!(
+        past_timestamp < timestamp,
+        "past block must precede the execution block"
+    );
+
+    // Routes pricing the pool tokens; the numeraire needs none. The tokens of a pool never
+    // change, so a missing route fails before the pool state is preflighted.
+    let mut pool_contract = Contract::preflight(args.pool, &mut env);
+    let token0 = pool_contract
+        .call_builder(&IUniswapV3Pool::token0Call {})
+        .call()
+        .await
+        .with_context(|| format!("{} is not a Uniswap V3 pool", args.pool))?
+        ._0;
+    let token1 = pool_contract
+        .call_builder(&IUniswapV3Pool::token1Call {})
+        .call()
+        .await
+        .with_context(|| format!("{} is not a Uniswap V3 pool", args.pool))?
+        ._0;
+    let routes = price_routes(&args.price_paths, [token0, token1], args.numeraire)?;
+
+    let outcome = async {
+        ///// Present pool state
+        let mut pool_contract = Contract::preflight(args.pool, &mut env);
+        let tick_spacing = pool_contract
+            .call_builder(&IUniswapV3Pool::tickSpacingCall {})
+            .call()
+            .await
+            .map_err(reverted)?
+            ._0;
+        let liquidity = pool_contract
+            .call_builder(&IUniswapV3Pool::liquidityCall {})
+            .call()
+            .await
+            .map_err(reverted)?
+            ._0;
+        if liquidity == 0 {
+            return Err(Status::NoSupply);
+        }
+        let slot0 = pool_contract
+            .call_builder(&IUniswapV3Pool::slot0Call {})
+            .call()
+            .await
+            .map_err(reverted)?;
+        let fee_growth_0 = pool_contract
+            .call_builder(&IUniswapV3Pool::feeGrowthGlobal0X128Call {})
+            .call()
+            .await
+            .map_err(reverted)?
+            ._0;
+        let fee_growth_1 = pool_contract
+            .call_builder(&IUniswapV3Pool::feeGrowthGlobal1X128Call {})
+            .call()
+            .await
+            .map_err(reverted)?
+            ._0;
+
+        ///// Past pool state
+        let mut pool_contract = Contract::preflight(args.pool, &mut past_env);
+        let past_fee_growth_0 = pool_contract
+            .call_builder(&IUniswapV3Pool::feeGrowthGlobal0X128Call {})
+            .call()
+            .await
+            .map_err(reverted)?
+            ._0;
+        let past_fee_growth_1 = pool_contract
+            .call_builder(&IUniswapV3Pool::feeGrowthGlobal1X128Call {})
+            .call()
+            .await
+            .map_err(reverted)?
+            ._0;
+
+        // The accumulators are allowed to overflow, so the growth is computed modulo 2^256.
+        let fee_growth_0_delta = fee_growth_0.wrapping_sub(past_fee_growth_0);
+        let fee_growth_1_delta = fee_growth_1.wrapping_sub(past_fee_growth_1);
+
+        // Active tick range, i.e. the initializable ticks around the current tick.
+        let tick = i32::try_from(slot0.tick).unwrap();
+        let tick_spacing = i32::try_from(tick_spacing).unwrap();
+        let tick_lower = tick.div_euclid(tick_spacing) * tick_spacing;
+        let tick_upper = tick_lower + tick_spacing;
+
+        // Price of one whole token in base units of the numeraire.
+        let mut prices = Vec::new();
+        for token in [token0, token1] {
+            let decimals = Contract::preflight(token, &mut env)
+                .call_builder(&IERC20::decimalsCall {})
+                .call()
+                .await
+                .map_err(reverted)?
+                ._0;
+            let unit = math::pow10(decimals)?;
+            let price = match routes.get(&token) {
+                None => unit,
+                Some(path) => {
+                    Contract::preflight(QUOTER_V2, &mut env)
+                        .call_builder(&QuoterV2::quoteExactInputCall {
+                            path: path.encode(),
+                            amountIn: unit,
+                        })
+                        .call()
+                        .await
+                        .map_err(reverted)?
+                        .amountOut
+                }
+            };
+            log::info!("Price of {token}: {:?}", price);
+            prices.push((price, unit));
+        }
+        let value = |amount0: U256, amount1: U256| -> math::Result<U256> {
+            math::add(
+                mul_div(amount0, prices[0].0, prices[0].1, Rounding::Down)?,
+                mul_div(amount1, prices[1].0, prices[1].1, Rounding::Down)?,
+            )
+        };
+
+        let liquidity_u256 = U256::from(liquidity);
+        let (amount0, amount1) = uniswap::amounts_for_liquidity(
+            U256::from(slot0.sqrtPriceX96),
+            uniswap::sqrt_ratio_at_tick(tick_lower)?,
+            uniswap::sqrt_ratio_at_tick(tick_upper)?,
+            liquidity_u256,
+        )?;
+        let (fees0, fees1) =
+            uniswap::fees_for_liquidity(fee_growth_0_delta, fee_growth_1_delta, liquidity_u256)?;
+        let active_liquidity_value = value(amount0, amount1)?;
+        let fees_value = value(fees0, fees1)?;
+        let fee_apr = uniswap::fee_apr(
+            fees_value,
+            active_liquidity_value,
+            timestamp - past_timestamp,
+        )?;
+        Ok::<_, Status>((
+            liquidity,
+            tick_lower,
+            tick_upper,
+            active_liquidity_value,
+            fees_value,
+            fee_apr,
+        ))
+    }
+    .await;
+    let (status, (liquidity, tick_lower, tick_upper, active_liquidity_value, fees_value, fee_apr)) =
+        Status::split(outcome);
+
+    let numeraire = units::token(&args.eth_rpc_url, Denomination::Token(args.numeraire)).await;
+    log::info!("Status: {}", status);
+    log::info!("Active Tick Range: [{}, {})", tick_lower, tick_upper);
+    log::info!("In-range Liquidity: {:?}", liquidity);
+    log::info!(
+        "Active Liquidity Value: {}",
+        numeraire.format(active_liquidity_value)
+    );
+    log::info!("Fees Value: {}", numeraire.format(fees_value));
+    log::info!("Fee APR: {}", Unit::RATE.format(fee_apr));
+
+    // Finally, construct the input from the environment.
+    let block = Block::of(env.header());
+    let evm_input = env.into_input().await?;
+    let past_evm_input = past_env.into_input().await?;
+
+    GuestInput::default()
+        .at(block)
+        .expect(
+            Expected::new(status)
+                .value("liquidity", U256::from(liquidity))
+                .value("activeLiquidityValue", active_liquidity_value)
+                .value("feesValue", fees_value)
+                .value("feeApr", fee_apr),
+        )
+        .write(&evm_input)?
+        .write(&past_evm_input)?
+        .write(&args.pool)?
+        .write(&args.numeraire)?
+        .write(&args.price_paths)
+}
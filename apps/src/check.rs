@@ -0,0 +1,147 @@
+//! Cross-check of the metric computed by the host during the preflight against the journal
+//! committed by the guest.
+//!
+//! Publishers compute each metric twice: on the host, to log it and to access the state the guest
+//! needs, and in the guest, which commits it. If the two computations diverge, the logs show
+//! values that were never proven, so the journal is checked against the host values after proving.
+
+use std::collections::HashMap;
+
+use alloy_primitives::utils::ParseUnits;
+use anyhow::{bail, ensure, Context, Result};
+use erc20_counter_core::status::Status;
+
+use crate::{journal::AnyJournal, output, units};
+
+/// Status and values of a journal as computed by the host, by the name of their journal field.
+#[derive(Clone, Debug, Default)]
+pub struct Expected {
+    status: Option<Status>,
+    values: Vec<(String, String)>,
+}
+
+impl Expected {
+    /// Expects the journal to commit `status`.
+    pub fn new(status: Status) -> Self {
+        Self {
+            status: Some(status),
+            values: Vec::new(),
+        }
+    }
+
+    /// Expects the journal field `name` to be `value`.
+    pub fn value(mut self, name: &str, value: impl Into<ParseUnits>) -> Self {
+        self.values.push((name.to_string(), units::fixed(value, 0)));
+        self
+    }
+
+    /// Whether nothing is expected, as for guests that only verify other receipts.
+    pub fn is_empty(&self) -> bool {
+        self.status.is_none() && self.values.is_empty()
+    }
+
+    /// Checks that `journal` commits the expected status and values, failing with all that differ.
+    pub fn check(&self, journal: &[u8]) -> Result<()> {
+        if self.is_empty() {
+            return Ok(());
+        }
+        let (_, journal) = AnyJournal::decode(journal).context("invalid journal")?;
+        let committed: HashMap<String, String> = output::raw_values(&journal).collect();
+
+        let mut mismatches = Vec::new();
+        if let Some(status) = self.status {
+            match journal.status() {
+                Ok(committed) if committed == status => {}
+                Ok(committed) => {
+                    mismatches.push(format!("status: host {status}, guest {committed}"))
+                }
+                Err(committed) => {
+                    mismatches.push(format!("status: host {status}, guest unknown {committed}"))
+                }
+            }
+        }
+        for (name, value) in &self.values {
+            match committed.get(name) {
+                Some(committed) if committed == value => {}
+                Some(committed) => {
+                    mismatches.push(format!("{name}: host {value}, guest {committed}"))
+                }
+                None => bail!("journal has no value {name}"),
+            }
+        }
+        ensure!(
+            mismatches.is_empty(),
+            "host preflight and guest journal disagree on {}",
+            mismatches.join(", ")
+        );
+        log::info!("Host preflight matches the journal");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::{B256, I256, U256};
+    use erc20_counter_core::{journal::MetricJournal, lido};
+    use risc0_steel::Commitment;
+
+    use super::*;
+
+    fn journal(status: Status, apr: i64) -> Vec<u8> {
+        let commitment = |block: u64| Commitment {
+            id: U256::from(block),
+            digest: B256::repeat_byte(0xaa),
+            configID: B256::repeat_byte(0xcc),
+        };
+        lido::Journal {
+            commitment: commitment(21_000_000),
+            pastCommitment: commitment(20_990_000),
+            status: status.into(),
+            stETH: lido::STETH,
+            shareRate: U256::from(1_100),
+            pastShareRate: U256::from(1_000),
+            apr: I256::try_from(apr).unwrap(),
+            apy: I256::try_from(apr + 1).unwrap(),
+        }
+        .encode_with_header(1, B256::repeat_byte(0x11))
+    }
+
+    fn expected(status: Status, apr: i64) -> Expected {
+        Expected::new(status)
+            .value("shareRate", U256::from(1_100))
+            .value("pastShareRate", U256::from(1_000))
+            .value("apr", I256::try_from(apr).unwrap())
+    }
+
+    #[test]
+    fn matching_journal_passes() {
+        expected(Status::Ok, -7)
+            .check(&journal(Status::Ok, -7))
+            .unwrap();
+        Expected::default().check(&[]).unwrap();
+    }
+
+    #[test]
+    fn mismatches_are_all_reported() {
+        let error = expected(Status::Overflow, 7)
+            .check(&journal(Status::Ok, -7))
+            .unwrap_err()
+            .to_string();
+        assert!(
+            error.contains("status: host arithmetic overflow, guest"),
+            "{error}"
+        );
+        assert!(error.contains("apr: host 7, guest -7"), "{error}");
+        assert!(!error.contains("shareRate"), "{error}");
+    }
+
+    #[test]
+    fn missing_values_and_invalid_journals_fail() {
+        let error = Expected::new(Status::Ok)
+            .value("tvl", U256::ZERO)
+            .check(&journal(Status::Ok, 0))
+            .unwrap_err();
+        assert_eq!(error.to_string(), "journal has no value tvl");
+        assert!(expected(Status::Ok, 0).check(&[0; 32]).is_err());
+    }
+}
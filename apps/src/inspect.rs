@@ -0,0 +1,242 @@
+//! Preflight-only mode, reporting what the guest input of a publisher contains.
+//!
+//! Steel fetches the state of the input with `eth_getProof`, the code of the contracts with
+//! `eth_getCode` and the headers with `eth_getBlockBy*`. In `--inspect` mode, the RPC client of the
+//! preflight records these requests, so that the accounts, storage slots, contracts and headers
+//! that end up in the input can be listed after it was built, without proving.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write as _,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+};
+
+use anyhow::Result;
+use risc0_steel::alloy::{
+    rpc::{
+        client::{ClientBuilder, RpcClient},
+        json_rpc::{RequestPacket, ResponsePacket, ResponsePayload},
+    },
+    transports::{TransportError, TransportFut},
+};
+use serde_json::Value;
+use tower::{Layer, Service};
+use url::Url;
+
+use crate::GuestInput;
+
+/// Command line arguments of the preflight-only mode.
+#[derive(Clone, Debug, Default, clap::Args)]
+pub struct InspectArgs {
+    /// Only preflight, and print the accounts, storage slots, contracts and block headers in the
+    /// guest input and its size instead of proving
+    #[arg(long, conflicts_with = "from_block")]
+    pub inspect: bool,
+
+    #[arg(skip)]
+    calls: Recorder,
+}
+
+impl InspectArgs {
+    /// Whether the input is inspected instead of proven.
+    pub fn is_enabled(&self) -> bool {
+        self.inspect
+    }
+
+    /// RPC client of the preflight, recording its requests in `--inspect` mode.
+    pub fn client(&self, rpc_url: &Url) -> RpcClient {
+        let recorder = self.is_enabled().then(|| self.calls.clone());
+        ClientBuilder::default()
+            .layer(RecorderLayer(recorder))
+            .http(rpc_url.clone())
+    }
+
+    /// Prints what `input` contains, from the requests of its preflight.
+    pub fn print(&self, input: &GuestInput) -> Result<()> {
+        let calls = self.calls.0.lock().expect("recorder poisoned");
+        let inspection = Inspection::of(&calls);
+        print!("{}", inspection.render(input.size()));
+        Ok(())
+    }
+}
+
+/// An RPC request of the preflight, with the size of its result.
+#[derive(Clone, Debug)]
+struct Call {
+    method: String,
+    params: Value,
+    result_size: Option<usize>,
+}
+
+/// Shared list of the requests made by a client.
+#[derive(Clone, Debug, Default)]
+struct Recorder(Arc<Mutex<Vec<Call>>>);
+
+/// Layer recording the requests of a transport, if a recorder is set.
+struct RecorderLayer(Option<Recorder>);
+
+impl<S> Layer<S> for RecorderLayer {
+    type Service = RecordingService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RecordingService {
+            inner,
+            recorder: self.0.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+struct RecordingService<S> {
+    inner: S,
+    recorder: Option<Recorder>,
+}
+
+impl<S> Service<RequestPacket> for RecordingService<S>
+where
+    S: Service<
+            RequestPacket,
+            Response = ResponsePacket,
+            Error = TransportError,
+            Future = TransportFut<'static>,
+        > + Send
+        + 'static,
+{
+    type Response = ResponsePacket;
+    type Error = TransportError;
+    type Future = TransportFut<'static>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: RequestPacket) -> Self::Future {
+        let Some(recorder) = self.recorder.clone() else {
+            return self.inner.call(request);
+        };
+        let requests: Vec<_> = request
+            .requests()
+            .iter()
+            .map(|request| {
+                let params = request
+                    .params()
+                    .and_then(|params| serde_json::from_str(params.get()).ok())
+                    .unwrap_or_default();
+                (request.id().clone(), request.method().to_string(), params)
+            })
+            .collect();
+        let response = self.inner.call(request);
+        Box::pin(async move {
+            let response = response.await?;
+            let responses = match &response {
+                ResponsePacket::Single(response) => std::slice::from_ref(response),
+                ResponsePacket::Batch(responses) => responses.as_slice(),
+            };
+            let mut calls = recorder.0.lock().expect("recorder poisoned");
+            for (id, method, params) in requests {
+                let result_size = responses
+                    .iter()
+                    .find(|response| response.id == id)
+                    .and_then(|response| match &response.payload {
+                        ResponsePayload::Success(result) => Some(result.get().len()),
+                        ResponsePayload::Failure(_) => None,
+                    });
+                calls.push(Call {
+                    method,
+                    params,
+                    result_size,
+                });
+            }
+            Ok(response)
+        })
+    }
+}
+
+/// State and headers fetched for a guest input, by block.
+#[derive(Debug, Default)]
+struct Inspection {
+    /// Storage slots of every account proven with `eth_getProof`, by block.
+    accounts: BTreeMap<String, BTreeMap<String, BTreeSet<String>>>,
+    /// Size of the code of every contract, in bytes.
+    contracts: BTreeMap<String, usize>,
+    /// Blocks whose header was fetched.
+    headers: BTreeSet<String>,
+}
+
+impl Inspection {
+    fn of(calls: &[Call]) -> Self {
+        let param = |call: &Call, i: usize| call.params.get(i).cloned().unwrap_or_default();
+        let text = |value: Value| match value {
+            Value::String(value) => value,
+            value => value.to_string(),
+        };
+
+        let mut inspection = Self::default();
+        for call in calls {
+            match call.method.as_str() {
+                "eth_getProof" => {
+                    let slots = inspection
+                        .accounts
+                        .entry(text(param(call, 2)))
+                        .or_default()
+                        .entry(text(param(call, 0)))
+                        .or_default();
+                    if let Value::Array(keys) = param(call, 1) {
+                        slots.extend(keys.into_iter().map(text));
+                    }
+                }
+                "eth_getCode" => {
+                    // The result is a quoted hex string.
+                    let size = call.result_size.unwrap_or(4).saturating_sub(4) / 2;
+                    inspection.contracts.insert(text(param(call, 0)), size);
+                }
+                "eth_getBlockByNumber"
+                | "eth_getBlockByHash"
+                | "eth_getHeaderByNumber"
+                | "eth_getHeaderByHash" => {
+                    inspection.headers.insert(text(param(call, 0)));
+                }
+                _ => {}
+            }
+        }
+        inspection
+    }
+
+    fn render(&self, input_size: usize) -> String {
+        let mut out = String::new();
+        for (block, accounts) in &self.accounts {
+            let slots: usize = accounts.values().map(BTreeSet::len).sum();
+            let _ = writeln!(
+                out,
+                "Block {block}: {} accounts, {slots} storage slots",
+                accounts.len()
+            );
+            for (address, slots) in accounts {
+                let code = match self.contracts.get(address) {
+                    Some(size) => format!(", {size} bytes of code"),
+                    None => String::new(),
+                };
+                let _ = writeln!(out, "  {address}: {} slots{code}", slots.len());
+                for slot in slots {
+                    let _ = writeln!(out, "    {slot}");
+                }
+            }
+        }
+        let code_size: usize = self.contracts.values().sum();
+        let _ = writeln!(
+            out,
+            "Contracts: {}, {code_size} bytes of code",
+            self.contracts.len()
+        );
+        let headers: Vec<&str> = self.headers.iter().map(String::as_str).collect();
+        let _ = writeln!(
+            out,
+            "Block headers: {}, {}",
+            headers.len(),
+            headers.join(", ")
+        );
+        let _ = writeln!(out, "Guest input: {input_size} bytes");
+        out
+    }
+}
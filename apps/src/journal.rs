@@ -0,0 +1,137 @@
+//! Decoding of journals of any guest, dispatching on their header.
+
+use erc20_counter_core::{
+    aave, aggregate, comparison, compound, curve, erc20, erc4626,
+    journal::{self, Header, JournalError, MetricJournal, MetricType},
+    lido, morpho, realized, score,
+    status::Status,
+    tvl, uniswap,
+};
+use risc0_steel::Commitment;
+
+/// Journal of one of the guests, as named by its header.
+pub enum AnyJournal {
+    BalanceOf(erc20::Journal),
+    CompoundApr(compound::Journal),
+    AaveApr(aave::Journal),
+    LidoApr(lido::Journal),
+    Erc4626Apy(erc4626::Journal),
+    MetaMorphoApr(morpho::Journal),
+    UniswapFeeApr(uniswap::Journal),
+    Tvl(tvl::Journal),
+    CurveGaugeApr(curve::Journal),
+    CometRevenue(compound::RevenueJournal),
+    RealizedApr(realized::Journal),
+    Aggregate(aggregate::Journal),
+    Comparison(comparison::Journal),
+    Score(score::Journal),
+}
+
+impl AnyJournal {
+    /// Decodes a journal of any guest, checking its header.
+    pub fn decode(bytes: &[u8]) -> Result<(Header, Self), JournalError> {
+        fn decode<T: MetricJournal>(
+            bytes: &[u8],
+            variant: fn(T) -> AnyJournal,
+        ) -> Result<(Header, AnyJournal), JournalError> {
+            let (header, journal) = T::decode_with_header(bytes)?;
+            Ok((header, variant(journal)))
+        }
+
+        let (_, metric_type) = journal::decode_header(bytes)?;
+        match metric_type {
+            MetricType::BalanceOf => decode(bytes, Self::BalanceOf),
+            MetricType::CompoundApr => decode(bytes, Self::CompoundApr),
+            MetricType::AaveApr => decode(bytes, Self::AaveApr),
+            MetricType::LidoApr => decode(bytes, Self::LidoApr),
+            MetricType::Erc4626Apy => decode(bytes, Self::Erc4626Apy),
+            MetricType::MetaMorphoApr => decode(bytes, Self::MetaMorphoApr),
+            MetricType::UniswapFeeApr => decode(bytes, Self::UniswapFeeApr),
+            MetricType::Tvl => decode(bytes, Self::Tvl),
+            MetricType::CurveGaugeApr => decode(bytes, Self::CurveGaugeApr),
+            MetricType::CometRevenue => decode(bytes, Self::CometRevenue),
+            MetricType::RealizedApr => decode(bytes, Self::RealizedApr),
+            MetricType::Aggregate => decode(bytes, Self::Aggregate),
+            MetricType::Comparison => decode(bytes, Self::Comparison),
+            MetricType::Score => decode(bytes, Self::Score),
+        }
+    }
+
+    /// Guest that committed the journal.
+    pub fn metric_type(&self) -> MetricType {
+        match self {
+            Self::BalanceOf(_) => MetricType::BalanceOf,
+            Self::CompoundApr(_) => MetricType::CompoundApr,
+            Self::AaveApr(_) => MetricType::AaveApr,
+            Self::LidoApr(_) => MetricType::LidoApr,
+            Self::Erc4626Apy(_) => MetricType::Erc4626Apy,
+            Self::MetaMorphoApr(_) => MetricType::MetaMorphoApr,
+            Self::UniswapFeeApr(_) => MetricType::UniswapFeeApr,
+            Self::Tvl(_) => MetricType::Tvl,
+            Self::CurveGaugeApr(_) => MetricType::CurveGaugeApr,
+            Self::CometRevenue(_) => MetricType::CometRevenue,
+            Self::RealizedApr(_) => MetricType::RealizedApr,
+            Self::Aggregate(_) => MetricType::Aggregate,
+            Self::Comparison(_) => MetricType::Comparison,
+            Self::Score(_) => MetricType::Score,
+        }
+    }
+
+    /// Status committed by the guest, or the raw value if it is unknown to this build.
+    pub fn status(&self) -> Result<Status, u8> {
+        let status = match self {
+            Self::BalanceOf(journal) => journal.status,
+            Self::CompoundApr(journal) => journal.status,
+            Self::AaveApr(journal) => journal.status,
+            Self::LidoApr(journal) => journal.status,
+            Self::Erc4626Apy(journal) => journal.status,
+            Self::MetaMorphoApr(journal) => journal.status,
+            Self::UniswapFeeApr(journal) => journal.status,
+            Self::Tvl(journal) => journal.status,
+            Self::CurveGaugeApr(journal) => journal.status,
+            Self::CometRevenue(journal) => journal.status,
+            Self::RealizedApr(journal) => journal.status,
+            Self::Aggregate(journal) => journal.status,
+            Self::Comparison(journal) => journal.status,
+            Self::Score(journal) => journal.status,
+        };
+        Status::try_from(status)
+    }
+
+    /// Steel commitment to the block the values were computed at, the last one for series.
+    ///
+    /// Scores are computed from the commitments of their metrics and have none of their own.
+    pub fn commitment(&self) -> Option<&Commitment> {
+        Some(match self {
+            Self::BalanceOf(journal) => &journal.commitment,
+            Self::CompoundApr(journal) => &journal.commitment,
+            Self::AaveApr(journal) => &journal.commitment,
+            Self::LidoApr(journal) => &journal.commitment,
+            Self::Erc4626Apy(journal) => &journal.commitment,
+            Self::MetaMorphoApr(journal) => &journal.commitment,
+            Self::UniswapFeeApr(journal) => &journal.commitment,
+            Self::Tvl(journal) => &journal.commitment,
+            Self::CurveGaugeApr(journal) => &journal.commitment,
+            Self::CometRevenue(journal) => &journal.commitment,
+            Self::RealizedApr(journal) => &journal.commitment,
+            Self::Aggregate(journal) => return journal.commitments.last(),
+            Self::Comparison(journal) => &journal.commitment,
+            Self::Score(_) => return None,
+        })
+    }
+
+    /// Steel commitment to the start of the window of metrics over two blocks, or to the first
+    /// sample of a series.
+    pub fn past_commitment(&self) -> Option<&Commitment> {
+        match self {
+            Self::LidoApr(journal) => Some(&journal.pastCommitment),
+            Self::Erc4626Apy(journal) => Some(&journal.pastCommitment),
+            Self::UniswapFeeApr(journal) => Some(&journal.pastCommitment),
+            Self::Tvl(journal) => Some(&journal.pastCommitment),
+            Self::CometRevenue(journal) => Some(&journal.pastCommitment),
+            Self::RealizedApr(journal) => Some(&journal.pastCommitment),
+            Self::Aggregate(journal) => journal.commitments.first(),
+            _ => None,
+        }
+    }
+}
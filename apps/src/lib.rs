@@ -0,0 +1,209 @@
+//! Helpers shared by the publishers.
+
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use alloy_primitives::{Address, B256};
+use anyhow::{Context, Result};
+use erc20_counter_core::{
+    pricing::{self, SwapPath},
+    status::Status,
+};
+use erc20_counter_methods::{
+    AAVE_APR_ID, AGGREGATE_ID, BALANCE_OF_ID, COMET_REVENUE_ID, COMPARISON_ID, COMPOUND_APR_ID,
+    CURVE_GAUGE_APR_ID, ERC4626_APY_ID, LIDO_APR_ID, METAMORPHO_APR_ID, REALIZED_APR_ID, SCORE_ID,
+    TVL_ID, UNISWAP_FEE_APR_ID,
+};
+use risc0_steel::EvmBlockHeader;
+use risc0_zkvm::{
+    sha::{Impl, Sha256},
+    Digest, ProverOpts, Receipt,
+};
+use serde::Serialize;
+use tokio::task;
+
+use crate::{check::Expected, profile::Profile, prover::Prover};
+
+pub mod batch;
+pub mod check;
+pub mod inspect;
+pub mod journal;
+pub mod output;
+pub mod profile;
+pub mod prover;
+pub mod runner;
+pub mod score;
+pub mod units;
+
+/// Maps the error of a reverted preflight call to [`Status::CallReverted`].
+///
+/// The preflight keeps the state accessed by the reverting call, so the guest reverts in the same
+/// way and commits the same status.
+pub fn reverted(err: anyhow::Error) -> Status {
+    log::warn!("Call reverted: {err:#}");
+    Status::CallReverted
+}
+
+/// Image ID of the guest named `guest`, as written to batch manifests.
+pub fn image_id(guest: &str) -> Option<Digest> {
+    let image_id = match guest {
+        "balance_of" => BALANCE_OF_ID,
+        "compound_apr" => COMPOUND_APR_ID,
+        "aave_apr" => AAVE_APR_ID,
+        "lido_apr" => LIDO_APR_ID,
+        "erc4626_apy" => ERC4626_APY_ID,
+        "metamorpho_apr" => METAMORPHO_APR_ID,
+        "uniswap_fee_apr" => UNISWAP_FEE_APR_ID,
+        "tvl" => TVL_ID,
+        "curve_gauge_apr" => CURVE_GAUGE_APR_ID,
+        "comet_revenue" => COMET_REVENUE_ID,
+        "realized_apr" => REALIZED_APR_ID,
+        "aggregate" => AGGREGATE_ID,
+        "comparison" => COMPARISON_ID,
+        "score" => SCORE_ID,
+        _ => return None,
+    };
+    Some(image_id.into())
+}
+
+/// Routes of `paths` pricing each of `tokens` in `numeraire`, by token, for the tokens other than
+/// the numeraire itself.
+///
+/// Guests cannot price a token without a route, so a missing one fails before proving.
+pub fn price_routes(
+    paths: &[SwapPath],
+    tokens: impl IntoIterator<Item = Address>,
+    numeraire: Address,
+) -> Result<HashMap<Address, SwapPath>> {
+    tokens
+        .into_iter()
+        .filter(|&token| token != numeraire)
+        .map(|token| {
+            let path = pricing::find_route(paths, token, numeraire).with_context(|| {
+                format!("no --price-path from token {token} to the numeraire {numeraire}")
+            })?;
+            Ok((token, path.clone()))
+        })
+        .collect()
+}
+
+/// Execution block a guest input was preflighted at.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Block {
+    pub number: u64,
+    pub timestamp: u64,
+}
+
+impl Block {
+    /// Number and timestamp of `header`.
+    pub fn of(header: &impl EvmBlockHeader) -> Self {
+        Self {
+            number: header.number(),
+            timestamp: header.timestamp(),
+        }
+    }
+}
+
+/// Input of a guest, serialized in the order the guest reads it, and the receipts it verifies.
+///
+/// Unlike an `ExecutorEnv`, the input is `Send` and can be kept until a prover is available.
+#[derive(Clone, Debug, Default)]
+pub struct GuestInput {
+    words: Vec<u32>,
+    assumptions: Vec<Receipt>,
+    block: Option<Block>,
+    expected: Expected,
+    preflight_time: Option<Duration>,
+}
+
+impl GuestInput {
+    /// Records the execution block the input was preflighted at, which is not part of the input.
+    pub fn at(mut self, block: Block) -> Self {
+        self.block = Some(block);
+        self
+    }
+
+    /// Execution block the input was preflighted at, if recorded.
+    pub fn block(&self) -> Option<Block> {
+        self.block
+    }
+
+    /// Records the status and values the host computed for the journal, which [`prove`] checks
+    /// the journal against.
+    pub fn expect(mut self, expected: Expected) -> Self {
+        self.expected = expected;
+        self
+    }
+
+    /// Appends a value, to be read by the guest with `env::read`.
+    pub fn write<T: Serialize>(mut self, value: &T) -> Result<Self> {
+        self.words.extend(risc0_zkvm::serde::to_vec(value)?);
+        Ok(self)
+    }
+
+    /// Size of the serialized input in bytes.
+    pub fn size(&self) -> usize {
+        self.words.len() * 4
+    }
+
+    /// SHA-256 digest of the serialized input, committed by the guest as the `inputHash` of the
+    /// journal header.
+    pub fn digest(&self) -> B256 {
+        let bytes: Vec<u8> = self
+            .words
+            .iter()
+            .flat_map(|word| word.to_le_bytes())
+            .collect();
+        B256::from_slice(Impl::hash_bytes(&bytes).as_bytes())
+    }
+
+    /// Adds a receipt whose journal the guest verifies with `env::verify`.
+    pub fn assume(mut self, receipt: Receipt) -> Self {
+        self.assumptions.push(receipt);
+        self
+    }
+}
+
+/// Receipt of a guest, with the block its input was preflighted at and the cost of proving it.
+#[derive(Clone, Debug)]
+pub struct Proof {
+    pub receipt: Receipt,
+    pub block: Option<Block>,
+    pub profile: Profile,
+}
+
+/// Creates a Groth16 proof of running `elf` on `input`, checking its journal against the values
+/// the host expects.
+pub async fn prove(elf: &'static [u8], input: GuestInput) -> Result<Proof> {
+    prove_with_opts(elf, input, ProverOpts::groth16()).await
+}
+
+/// Creates a proof of running `elf` on `input`, of the kind selected by `opts`, checking its
+/// journal against the values the host expects.
+pub async fn prove_with_opts(
+    elf: &'static [u8],
+    input: GuestInput,
+    opts: ProverOpts,
+) -> Result<Proof> {
+    let expected = input.expected.clone();
+    let (block, preflight_time) = (input.block, input.preflight_time);
+    let prover = Prover::from_env()?;
+    let start = Instant::now();
+    let (receipt, cycles) =
+        task::spawn_blocking(move || prover.prove(elf, &input.words, input.assumptions, &opts))
+            .await?
+            .context("failed to create proof")?;
+    let proving_time = start.elapsed();
+    expected.check(&receipt.journal.bytes)?;
+
+    let receipt_size = bincode::serialized_size(&receipt)? as usize;
+    let profile = Profile::new(preflight_time, proving_time, cycles, receipt_size);
+    profile.log();
+    Ok(Proof {
+        receipt,
+        block,
+        profile,
+    })
+}
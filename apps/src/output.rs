@@ -0,0 +1,674 @@
+//! Machine-readable records of proven metrics, printed by the publishers with `--output`.
+//!
+//! A [`Record`] is built from the decoded journal of a receipt, so it only holds values the
+//! guest committed to, together with the inputs and the receipt they were proven from.
+
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use alloy_primitives::{utils::ParseUnits, B256, U256};
+use anyhow::{Context, Result};
+use erc20_counter_core::{aggregate::Metric, journal::Header};
+use risc0_steel::Commitment;
+use risc0_zkvm::{sha::Digest, Receipt};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::{
+    journal::AnyJournal,
+    profile::Profile,
+    units::{self, Denomination, Unit},
+    Proof,
+};
+
+/// Format of the records printed to stdout.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// Aligned columns for reading in a terminal
+    Table,
+    /// One JSON object per line
+    Json,
+    /// One row per value, with a header row
+    Csv,
+}
+
+/// Command line arguments selecting how the proven metric is reported.
+#[derive(Clone, Debug, clap::Args)]
+pub struct OutputArgs {
+    /// Print the decoded journal to stdout in this format
+    #[arg(long, value_enum)]
+    pub output: Option<OutputFormat>,
+
+    /// Write the bincode encoded receipt of a single run to this file
+    #[arg(long)]
+    pub receipt: Option<PathBuf>,
+}
+
+/// Proven metric as printed by the publishers.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Record {
+    /// Name of the guest that committed the journal.
+    pub metric: String,
+    /// Arguments of the publisher identifying what was measured.
+    pub inputs: BTreeMap<String, String>,
+    pub status: String,
+    pub chain_id: u64,
+    pub schema_version: u16,
+    /// Execution block the values were computed at.
+    pub block_number: Option<u64>,
+    /// Hash of the execution block, known for block commitments only.
+    pub block_hash: Option<B256>,
+    pub timestamp: Option<u64>,
+    /// Start of the window of metrics over two blocks, or first sample of a series.
+    pub past_block_number: Option<u64>,
+    pub values: Vec<Value>,
+    pub image_id: String,
+    pub input_hash: B256,
+    /// Path of the bincode encoded receipt, if it was written.
+    pub receipt: Option<PathBuf>,
+    pub profile: Profile,
+}
+
+/// Value committed to by a guest.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Value {
+    /// Name of the journal field.
+    pub name: String,
+    /// Integer as committed, in base 10.
+    pub raw: String,
+    /// Number of decimals of the fixed-point `raw` value.
+    pub decimals: u8,
+    /// `raw` scaled down by its decimals, exactly.
+    pub decimal: String,
+    /// Unit of the value: `%` for fractions, the symbol of the token for amounts, or the scale
+    /// of other fixed-point numbers.
+    pub unit: String,
+    /// Value rendered for people, e.g. `4.5123%` or `1250.5 USDC`.
+    pub formatted: String,
+}
+
+impl Value {
+    fn new(name: impl Into<String>, raw: impl Into<ParseUnits>, unit: &Unit) -> Self {
+        let raw = raw.into();
+        Self {
+            name: name.into(),
+            raw: units::fixed(raw, 0),
+            decimals: unit.decimals(),
+            decimal: units::fixed(raw, unit.decimals()),
+            unit: unit.name(),
+            formatted: unit.format(raw),
+        }
+    }
+}
+
+/// Units of the amounts of a journal, read from the chain when an RPC endpoint is known.
+struct Amounts {
+    /// Measured token or numeraire.
+    token: Unit,
+    /// Shares of a vault.
+    shares: Unit,
+}
+
+impl Amounts {
+    async fn of(journal: &AnyJournal, rpc_url: Option<&Url>) -> Self {
+        let (token, shares) = match journal {
+            AnyJournal::BalanceOf(journal) => {
+                (Some(Denomination::Token(journal.tokenAddress)), None)
+            }
+            AnyJournal::Erc4626Apy(journal) => (
+                Some(Denomination::Token(journal.asset)),
+                Some(Denomination::Token(journal.vault)),
+            ),
+            AnyJournal::MetaMorphoApr(journal) => {
+                (Some(Denomination::VaultAsset(journal.vault)), None)
+            }
+            AnyJournal::UniswapFeeApr(journal) => {
+                (Some(Denomination::Token(journal.numeraire)), None)
+            }
+            AnyJournal::Tvl(journal) => (Some(Denomination::Token(journal.numeraire)), None),
+            AnyJournal::CurveGaugeApr(journal) => {
+                (Some(Denomination::Token(journal.numeraire)), None)
+            }
+            AnyJournal::CometRevenue(journal) => {
+                (Some(Denomination::CometBase(journal.comet)), None)
+            }
+            _ => (None, None),
+        };
+        let unit = |denomination: Option<Denomination>| async move {
+            match (rpc_url, denomination) {
+                (Some(rpc_url), Some(denomination)) => units::token(rpc_url, denomination).await,
+                _ => Unit::Integer,
+            }
+        };
+        Self {
+            token: unit(token).await,
+            shares: unit(shares).await,
+        }
+    }
+}
+
+/// Reports the metric proven by a publisher, the same way for a single run and for a batch.
+#[derive(Clone, Debug)]
+pub struct Report {
+    args: OutputArgs,
+    guest: &'static str,
+    image_id: Digest,
+    inputs: BTreeMap<String, String>,
+    rpc_url: Option<Url>,
+}
+
+impl Report {
+    pub fn new(args: &OutputArgs, guest: &'static str, image_id: impl Into<Digest>) -> Self {
+        Self {
+            args: args.clone(),
+            guest,
+            image_id: image_id.into(),
+            inputs: BTreeMap::new(),
+            rpc_url: None,
+        }
+    }
+
+    /// Reads the decimals and symbols of the tokens of the amounts from `rpc_url`.
+    pub fn rpc(mut self, rpc_url: &Url) -> Self {
+        self.rpc_url = Some(rpc_url.clone());
+        self
+    }
+
+    /// Adds an argument identifying what is measured.
+    pub fn input(mut self, name: &str, value: impl Display) -> Self {
+        self.inputs.insert(name.to_string(), value.to_string());
+        self
+    }
+
+    /// Adds an argument given several times, with its values separated by commas.
+    pub fn inputs(self, name: &str, values: &[impl Display]) -> Self {
+        let values: Vec<String> = values.iter().map(ToString::to_string).collect();
+        self.input(name, values.join(","))
+    }
+
+    /// Name of the guest that is proven.
+    pub fn guest(&self) -> &'static str {
+        self.guest
+    }
+
+    /// Image ID of the guest that is proven.
+    pub fn image_id(&self) -> Digest {
+        self.image_id
+    }
+
+    /// Whether records are printed, so that batches only decode their journals if they are.
+    pub fn is_enabled(&self) -> bool {
+        self.args.output.is_some()
+    }
+
+    /// File the receipt of a single run is written to.
+    pub fn receipt_path(&self) -> Option<&Path> {
+        self.args.receipt.as_deref()
+    }
+
+    /// Record of the journal of `proof`, whose receipt is stored at `receipt`.
+    pub async fn record(&self, proof: &Proof, receipt: Option<PathBuf>) -> Result<Record> {
+        let (header, journal) =
+            AnyJournal::decode(&proof.receipt.journal.bytes).context("invalid journal")?;
+        let amounts = Amounts::of(&journal, self.rpc_url.as_ref()).await;
+        Ok(Record::new(
+            self, &header, &journal, &amounts, proof, receipt,
+        ))
+    }
+
+    /// Writes the receipt of a single run if `--receipt` is set and prints its record if
+    /// `--output` is set.
+    pub async fn publish(&self, proof: &Proof) -> Result<()> {
+        if let Some(path) = &self.args.receipt {
+            write_receipt(path, &proof.receipt)?;
+            log::info!("Wrote the receipt to {}", path.display());
+        }
+        if self.is_enabled() {
+            let record = self.record(proof, self.args.receipt.clone()).await?;
+            self.print(&[record])?;
+        }
+        Ok(())
+    }
+
+    /// Prints `records` to stdout in the selected format, if any.
+    pub fn print(&self, records: &[Record]) -> Result<()> {
+        let Some(format) = self.args.output else {
+            return Ok(());
+        };
+        let mut stdout = io::stdout().lock();
+        match format {
+            OutputFormat::Table => write_table(&mut stdout, records)?,
+            OutputFormat::Json => {
+                for record in records {
+                    serde_json::to_writer(&mut stdout, record)?;
+                    writeln!(stdout)?;
+                }
+            }
+            OutputFormat::Csv => write_csv(&mut stdout, records)?,
+        }
+        Ok(stdout.flush()?)
+    }
+}
+
+/// Writes `receipt` bincode encoded to `path`, the encoding read by the aggregation and scoring
+/// publishers.
+pub fn write_receipt(path: &Path, receipt: &Receipt) -> Result<()> {
+    fs::write(path, bincode::serialize(receipt)?)
+        .with_context(|| format!("failed to write {}", path.display()))
+}
+
+impl Record {
+    fn new(
+        report: &Report,
+        header: &Header,
+        journal: &AnyJournal,
+        amounts: &Amounts,
+        proof: &Proof,
+        receipt: Option<PathBuf>,
+    ) -> Self {
+        let block = proof.block;
+        let commitment = journal.commitment().map(CommitmentKind::of);
+        let past_commitment = journal.past_commitment().map(CommitmentKind::of);
+        Self {
+            metric: report.guest.to_string(),
+            inputs: report.inputs.clone(),
+            status: match journal.status() {
+                Ok(status) => status.to_string(),
+                Err(status) => format!("unknown status {status}"),
+            },
+            chain_id: header.chainId,
+            schema_version: header.schemaVersion,
+            block_number: block.map(|block| block.number).or(match commitment {
+                Some(CommitmentKind::Block { number, .. }) => Some(number),
+                _ => None,
+            }),
+            block_hash: match commitment {
+                Some(CommitmentKind::Block { hash, .. }) => Some(hash),
+                _ => None,
+            },
+            timestamp: block.map(|block| block.timestamp).or(match commitment {
+                Some(CommitmentKind::Beacon { timestamp }) => Some(timestamp),
+                _ => None,
+            }),
+            past_block_number: match past_commitment {
+                Some(CommitmentKind::Block { number, .. }) => Some(number),
+                _ => None,
+            },
+            values: values(journal, amounts),
+            image_id: report.image_id.to_string(),
+            input_hash: header.inputHash,
+            receipt,
+            profile: proof.profile,
+        }
+    }
+}
+
+/// Block a Steel commitment is made to, as far as it can be told from the commitment alone.
+#[derive(Clone, Copy)]
+enum CommitmentKind {
+    /// Commitment to an execution block by its hash.
+    Block { number: u64, hash: B256 },
+    /// Commitment to a beacon block root by its timestamp.
+    Beacon { timestamp: u64 },
+    /// Unset commitment, such as the past commitment of a TVL without a past block, or a
+    /// commitment of an unknown version.
+    Other,
+}
+
+impl CommitmentKind {
+    fn of(commitment: &Commitment) -> Self {
+        if commitment.digest == B256::ZERO {
+            return Self::Other;
+        }
+        let (id, version) = commitment.decode_id();
+        match (version, u64::try_from(id)) {
+            (0, Ok(number)) => Self::Block {
+                number,
+                hash: commitment.digest,
+            },
+            (1, Ok(timestamp)) => Self::Beacon { timestamp },
+            _ => Self::Other,
+        }
+    }
+}
+
+/// Names and raw values committed to by the guest, in journal order.
+pub(crate) fn raw_values(journal: &AnyJournal) -> impl Iterator<Item = (String, String)> {
+    let amounts = Amounts {
+        token: Unit::Integer,
+        shares: Unit::Integer,
+    };
+    values(journal, &amounts)
+        .into_iter()
+        .map(|value| (value.name, value.raw))
+}
+
+/// Values committed to by the guest, in journal order.
+///
+/// Addresses and ids identifying what was measured are reported as inputs instead.
+fn values(journal: &AnyJournal, amounts: &Amounts) -> Vec<Value> {
+    let token = &amounts.token;
+    match journal {
+        AnyJournal::BalanceOf(journal) => vec![
+            Value::new("circulatingSupply", journal.circulatingSupply, token),
+            Value::new(
+                "pastCirculatingSupply",
+                journal.pastCirculatingSupply,
+                token,
+            ),
+            Value::new(
+                "inflationBasisPoints",
+                journal.inflationBasisPoints,
+                &Unit::BPS,
+            ),
+        ],
+        AnyJournal::CompoundApr(journal) => vec![
+            Value::new(
+                "annualBaseSupplyRate",
+                U256::from(journal.annualBaseSupplyRate),
+                &Unit::RATE,
+            ),
+            Value::new(
+                "annualBaseSupplyApy",
+                journal.annualBaseSupplyApy,
+                &Unit::RATE,
+            ),
+            Value::new(
+                "annualCompRewardsSupplyRate",
+                journal.annualCompRewardsSupplyRate,
+                &Unit::RATE,
+            ),
+            Value::new(
+                "annualBaseBorrowRate",
+                U256::from(journal.annualBaseBorrowRate),
+                &Unit::RATE,
+            ),
+            Value::new(
+                "annualBaseBorrowApy",
+                journal.annualBaseBorrowApy,
+                &Unit::RATE,
+            ),
+            Value::new(
+                "annualCompRewardsBorrowRate",
+                journal.annualCompRewardsBorrowRate,
+                &Unit::RATE,
+            ),
+        ],
+        AnyJournal::AaveApr(journal) => vec![
+            Value::new("supplyApr", journal.supplyApr, &Unit::RATE),
+            Value::new("supplyApy", journal.supplyApy, &Unit::RATE),
+            Value::new("borrowApr", journal.borrowApr, &Unit::RATE),
+            Value::new("borrowApy", journal.borrowApy, &Unit::RATE),
+            Value::new(
+                "supplyIncentivesApr",
+                journal.supplyIncentivesApr,
+                &Unit::RATE,
+            ),
+            Value::new(
+                "borrowIncentivesApr",
+                journal.borrowIncentivesApr,
+                &Unit::RATE,
+            ),
+        ],
+        AnyJournal::LidoApr(journal) => vec![
+            Value::new("shareRate", journal.shareRate, &Unit::RAY),
+            Value::new("pastShareRate", journal.pastShareRate, &Unit::RAY),
+            Value::new("apr", journal.apr, &Unit::RATE),
+            Value::new("apy", journal.apy, &Unit::RATE),
+        ],
+        AnyJournal::Erc4626Apy(journal) => vec![
+            Value::new("sharePrice", journal.sharePrice, token),
+            Value::new("pastSharePrice", journal.pastSharePrice, token),
+            Value::new("totalAssets", journal.totalAssets, token),
+            Value::new("totalSupply", journal.totalSupply, &amounts.shares),
+            Value::new("pastTotalAssets", journal.pastTotalAssets, token),
+            Value::new("pastTotalSupply", journal.pastTotalSupply, &amounts.shares),
+            Value::new("apr", journal.apr, &Unit::RATE),
+            Value::new("apy", journal.apy, &Unit::RATE),
+        ],
+        AnyJournal::MetaMorphoApr(journal) => vec![
+            Value::new("fee", journal.fee, &Unit::RATE),
+            Value::new("totalAssets", journal.totalAssets, token),
+            Value::new("grossApr", journal.grossApr, &Unit::RATE),
+            Value::new("netApr", journal.netApr, &Unit::RATE),
+            Value::new("netApy", journal.netApy, &Unit::RATE),
+        ],
+        AnyJournal::UniswapFeeApr(journal) => vec![
+            Value::new("liquidity", U256::from(journal.liquidity), &Unit::Integer),
+            Value::new("activeLiquidityValue", journal.activeLiquidityValue, token),
+            Value::new("feesValue", journal.feesValue, token),
+            Value::new("feeApr", journal.feeApr, &Unit::RATE),
+        ],
+        AnyJournal::Tvl(journal) => vec![
+            Value::new("totalValue", journal.totalValue, token),
+            Value::new("pastTotalValue", journal.pastTotalValue, token),
+            Value::new("totalValueChange", journal.totalValueChange, token),
+        ],
+        AnyJournal::CurveGaugeApr(journal) => vec![
+            // CRV emitted per second and LP token supplies, all with 18 decimals.
+            Value::new("inflationRate", journal.inflationRate, &Unit::WAD),
+            Value::new("relativeWeight", journal.relativeWeight, &Unit::RATE),
+            Value::new("workingSupply", journal.workingSupply, &Unit::WAD),
+            Value::new("totalSupply", journal.totalSupply, &Unit::WAD),
+            Value::new("virtualPrice", journal.virtualPrice, &Unit::WAD),
+            Value::new("crvPrice", journal.crvPrice, token),
+            Value::new("lpPrice", journal.lpPrice, token),
+            Value::new("baseApr", journal.baseApr, &Unit::RATE),
+            Value::new("maxBoostApr", journal.maxBoostApr, &Unit::RATE),
+        ],
+        AnyJournal::CometRevenue(journal) => vec![
+            Value::new("reserves", journal.reserves, token),
+            Value::new("pastReserves", journal.pastReserves, token),
+            Value::new("reserveGrowth", journal.reserveGrowth, token),
+            Value::new("borrowInterest", journal.borrowInterest, token),
+            Value::new("supplyInterest", journal.supplyInterest, token),
+            Value::new("interestRevenue", journal.interestRevenue, token),
+        ],
+        AnyJournal::RealizedApr(journal) => {
+            // Comet is the only market without an id; Morpho Blue share prices are in RAY.
+            let index = if journal.marketId == B256::ZERO {
+                Unit::COMET_INDEX
+            } else {
+                Unit::RAY
+            };
+            vec![
+                Value::new("supplyIndex", journal.supplyIndex, &index),
+                Value::new("pastSupplyIndex", journal.pastSupplyIndex, &index),
+                Value::new("borrowIndex", journal.borrowIndex, &index),
+                Value::new("pastBorrowIndex", journal.pastBorrowIndex, &index),
+                Value::new("elapsed", U256::from(journal.elapsed), &Unit::Integer),
+                Value::new("realizedSupplyApr", journal.realizedSupplyApr, &Unit::RATE),
+                Value::new("realizedSupplyApy", journal.realizedSupplyApy, &Unit::RATE),
+                Value::new("realizedBorrowApr", journal.realizedBorrowApr, &Unit::RATE),
+                Value::new("realizedBorrowApy", journal.realizedBorrowApy, &Unit::RATE),
+            ]
+        }
+        AnyJournal::Aggregate(journal) => {
+            // The token of aggregated amounts is not committed.
+            let unit = match Metric::try_from(journal.metric) {
+                Ok(Metric::CirculatingSupply | Metric::TotalValueLocked) => Unit::Integer,
+                Ok(Metric::InflationBasisPoints) => Unit::BPS,
+                _ => Unit::RATE,
+            };
+            vec![
+                Value::new("samples", U256::from(journal.samples), &Unit::Integer),
+                Value::new("skipped", U256::from(journal.skipped), &Unit::Integer),
+                Value::new("min", journal.min, &unit),
+                Value::new("max", journal.max, &unit),
+                Value::new("mean", journal.mean, &unit),
+                Value::new("timeWeightedMean", journal.timeWeightedMean, &unit),
+            ]
+        }
+        AnyJournal::Comparison(journal) => journal
+            .ranking
+            .iter()
+            .enumerate()
+            .map(|(rank, market)| {
+                Value::new(
+                    format!("ranking[{rank}].supplyApr"),
+                    market.supplyApr,
+                    &Unit::RATE,
+                )
+            })
+            .chain([Value::new(
+                "winner.supplyApr",
+                journal.winner.supplyApr,
+                &Unit::RATE,
+            )])
+            .collect(),
+        AnyJournal::Score(journal) => journal
+            .scores
+            .iter()
+            .map(|score| {
+                Value::new(
+                    format!("scores[{}].score", score.name),
+                    score.score,
+                    &Unit::WAD,
+                )
+            })
+            .collect(),
+    }
+}
+
+/// Writes the records as a list of fields followed by a table of values.
+fn write_table(out: &mut impl Write, records: &[Record]) -> io::Result<()> {
+    for (i, record) in records.iter().enumerate() {
+        if i > 0 {
+            writeln!(out)?;
+        }
+        let mut fields = vec![
+            ("metric", record.metric.clone()),
+            ("status", record.status.clone()),
+            ("chain id", record.chain_id.to_string()),
+        ];
+        fields.extend(
+            record
+                .inputs
+                .iter()
+                .map(|(name, value)| (name.as_str(), value.clone())),
+        );
+        fields.extend([
+            ("block number", display(&record.block_number)),
+            ("block hash", display(&record.block_hash)),
+            ("timestamp", display(&record.timestamp)),
+            ("past block number", display(&record.past_block_number)),
+            ("image id", record.image_id.clone()),
+            ("input hash", record.input_hash.to_string()),
+            (
+                "receipt",
+                display(&record.receipt.as_ref().map(|path| path.display())),
+            ),
+            (
+                "preflight time",
+                display(&record.profile.preflight_ms.map(|ms| format!("{ms} ms"))),
+            ),
+            ("proving time", format!("{} ms", record.profile.proving_ms)),
+            ("total cycles", record.profile.total_cycles.to_string()),
+            ("user cycles", record.profile.user_cycles.to_string()),
+            ("paging cycles", record.profile.paging_cycles.to_string()),
+            ("segments", record.profile.segments.to_string()),
+            (
+                "receipt size",
+                format!("{} bytes", record.profile.receipt_size),
+            ),
+        ]);
+        let width = fields.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+        for (name, value) in &fields {
+            writeln!(out, "{name:<width$}  {value}")?;
+        }
+
+        let name_width = column_width("field", record.values.iter().map(|v| v.name.len()));
+        let raw_width = column_width("raw", record.values.iter().map(|v| v.raw.len()));
+        writeln!(out)?;
+        writeln!(
+            out,
+            "{:<name_width$}  {:>raw_width$}  value",
+            "field", "raw"
+        )?;
+        for value in &record.values {
+            writeln!(
+                out,
+                "{:<name_width$}  {:>raw_width$}  {}",
+                value.name, value.raw, value.formatted
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes one row per value of every record, repeating the fields of the record on each row.
+fn write_csv(out: &mut impl Write, records: &[Record]) -> io::Result<()> {
+    writeln!(
+        out,
+        "metric,status,chain_id,inputs,block_number,block_hash,timestamp,past_block_number,\
+         image_id,input_hash,receipt,preflight_ms,proving_ms,total_cycles,user_cycles,\
+         paging_cycles,segments,receipt_size,value,raw,decimals,decimal,unit,formatted"
+    )?;
+    for record in records {
+        let inputs: Vec<String> = record
+            .inputs
+            .iter()
+            .map(|(name, value)| format!("{name}={value}"))
+            .collect();
+        let fields = [
+            record.metric.clone(),
+            record.status.clone(),
+            record.chain_id.to_string(),
+            inputs.join(";"),
+            display(&record.block_number),
+            display(&record.block_hash),
+            display(&record.timestamp),
+            display(&record.past_block_number),
+            record.image_id.clone(),
+            record.input_hash.to_string(),
+            display(&record.receipt.as_ref().map(|path| path.display())),
+            display(&record.profile.preflight_ms),
+            record.profile.proving_ms.to_string(),
+            record.profile.total_cycles.to_string(),
+            record.profile.user_cycles.to_string(),
+            record.profile.paging_cycles.to_string(),
+            record.profile.segments.to_string(),
+            record.profile.receipt_size.to_string(),
+        ];
+        for value in &record.values {
+            let row: Vec<String> = fields
+                .iter()
+                .cloned()
+                .chain([
+                    value.name.clone(),
+                    value.raw.clone(),
+                    value.decimals.to_string(),
+                    value.decimal.clone(),
+                    value.unit.clone(),
+                    value.formatted.clone(),
+                ])
+                .map(|field| csv_field(&field))
+                .collect();
+            writeln!(out, "{}", row.join(","))?;
+        }
+    }
+    Ok(())
+}
+
+/// Value of an optional field, empty if it is unknown.
+fn display(value: &Option<impl Display>) -> String {
+    value.as_ref().map(ToString::to_string).unwrap_or_default()
+}
+
+fn column_width(header: &str, widths: impl Iterator<Item = usize>) -> usize {
+    widths.max().unwrap_or(0).max(header.len())
+}
+
+/// Quotes a CSV field if it contains a separator, a quote or a line break.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
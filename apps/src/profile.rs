@@ -0,0 +1,101 @@
+//! Cost of proving a metric: time spent preflighting and proving, cycles and receipt size.
+//!
+//! Every proof is profiled, so that alternative implementations of a guest can be compared on
+//! cost. A pprof profile of the guest execution is written by the zkVM itself when the
+//! `RISC0_PPROF_OUT` environment variable is set to a file.
+
+use std::{
+    future::Future,
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use risc0_zkvm::SessionStats;
+use serde::{Deserialize, Serialize};
+
+use crate::GuestInput;
+
+/// Cost of a proof of a guest.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Profile {
+    /// Time spent preflighting the input, in milliseconds, unless the input was not preflighted.
+    pub preflight_ms: Option<u64>,
+    /// Time spent executing the guest and proving it, in milliseconds.
+    pub proving_ms: u64,
+    /// Cycles of all segments, including paging and padding.
+    pub total_cycles: u64,
+    /// Cycles spent executing the guest.
+    pub user_cycles: u64,
+    /// Cycles spent paging memory in and out, zero if the prover does not report them.
+    pub paging_cycles: u64,
+    pub segments: usize,
+    /// Size of the bincode encoded receipt, in bytes.
+    pub receipt_size: usize,
+}
+
+/// Cycles and segments of a proven execution.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Cycles {
+    pub total: u64,
+    pub user: u64,
+    pub paging: u64,
+    pub segments: usize,
+}
+
+impl From<&SessionStats> for Cycles {
+    fn from(stats: &SessionStats) -> Self {
+        Self {
+            total: stats.total_cycles,
+            user: stats.user_cycles,
+            paging: stats.paging_cycles,
+            segments: stats.segments,
+        }
+    }
+}
+
+impl Profile {
+    pub(crate) fn new(
+        preflight: Option<Duration>,
+        proving: Duration,
+        cycles: Cycles,
+        receipt_size: usize,
+    ) -> Self {
+        Self {
+            preflight_ms: preflight.map(millis),
+            proving_ms: millis(proving),
+            total_cycles: cycles.total,
+            user_cycles: cycles.user,
+            paging_cycles: cycles.paging,
+            segments: cycles.segments,
+            receipt_size,
+        }
+    }
+
+    /// Logs the cost of the proof.
+    pub(crate) fn log(&self) {
+        log::info!(
+            "Proved in {} ms: {} total cycles, {} user cycles, {} paging cycles, {} segments, \
+             receipt of {} bytes",
+            self.proving_ms,
+            self.total_cycles,
+            self.user_cycles,
+            self.paging_cycles,
+            self.segments,
+            self.receipt_size
+        );
+    }
+}
+
+/// Runs `preflight`, recording how long it took in the input it returns.
+pub async fn timed(preflight: impl Future<Output = Result<GuestInput>>) -> Result<GuestInput> {
+    let start = Instant::now();
+    let mut input = preflight.await?;
+    let elapsed = start.elapsed();
+    log::info!("Preflighted in {} ms", millis(elapsed));
+    input.preflight_time = Some(elapsed);
+    Ok(input)
+}
+
+fn millis(duration: Duration) -> u64 {
+    duration.as_millis().try_into().unwrap_or(u64::MAX)
+}
@@ -0,0 +1,176 @@
+//! Provers of the guests: in this process, or by a Bonsai-compatible proving service.
+//!
+//! Remote proving is selected like `default_prover` does, with `BONSAI_API_URL` and
+//! `BONSAI_API_KEY`, but goes through the upload, session and poll API of Bonsai with any base
+//! URL. The `prover_server` binary implements the same API on top of the local prover, so remote
+//! flows can be run without network access.
+
+use std::{env, thread, time::Duration};
+
+use anyhow::{bail, Context, Result};
+use bonsai_sdk::blocking::Client;
+use risc0_zkvm::{
+    compute_image_id, default_prover, ExecutorEnv, ProverOpts, Receipt, ReceiptKind,
+    VerifierContext,
+};
+
+use crate::profile::Cycles;
+
+/// Interval between two polls of the status of a remote session.
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Where guests are proven.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Prover {
+    /// The prover of `default_prover`, which proves in this process or with `r0vm`, or fakes
+    /// receipts in dev mode.
+    Local,
+    /// A Bonsai-compatible proving service.
+    Remote { url: String, api_key: String },
+}
+
+impl Prover {
+    /// Remote prover at `BONSAI_API_URL` if it is set, authenticated with `BONSAI_API_KEY`, local
+    /// prover otherwise.
+    pub fn from_env() -> Result<Self> {
+        match env::var("BONSAI_API_URL") {
+            Ok(url) => Ok(Self::Remote {
+                url,
+                api_key: env::var("BONSAI_API_KEY")
+                    .context("BONSAI_API_URL is set without BONSAI_API_KEY")?,
+            }),
+            Err(_) => Ok(Self::Local),
+        }
+    }
+
+    /// Proves running `elf` on the serialized `input`, with the receipts of `assumptions`, into
+    /// a receipt of the kind selected by `opts`.
+    ///
+    /// Blocks until the proof is done.
+    pub fn prove(
+        &self,
+        elf: &[u8],
+        input: &[u32],
+        assumptions: Vec<Receipt>,
+        opts: &ProverOpts,
+    ) -> Result<(Receipt, Cycles)> {
+        match self {
+            Self::Local => {
+                let mut builder = ExecutorEnv::builder();
+                for receipt in assumptions {
+                    builder.add_assumption(receipt);
+                }
+                let env = builder.write_slice(input).build()?;
+                let prove_info =
+                    default_prover().prove_with_ctx(env, &VerifierContext::default(), elf, opts)?;
+                Ok((prove_info.receipt, Cycles::from(&prove_info.stats)))
+            }
+            Self::Remote { url, api_key } => {
+                let client = Client::from_parts(url.clone(), api_key.clone(), risc0_zkvm::VERSION)
+                    .context("failed to create the proving service client")?;
+                prove_remotely(&client, elf, input, assumptions, opts)
+            }
+        }
+    }
+}
+
+fn prove_remotely(
+    client: &Client,
+    elf: &[u8],
+    input: &[u32],
+    assumptions: Vec<Receipt>,
+    opts: &ProverOpts,
+) -> Result<(Receipt, Cycles)> {
+    let image_id = compute_image_id(elf)?;
+    client.upload_img(&image_id.to_string(), elf.to_vec())?;
+    let input_id = client.upload_input(bytemuck::cast_slice(input).to_vec())?;
+    let assumption_ids = assumptions
+        .iter()
+        .map(|receipt| Ok(client.upload_receipt(bincode::serialize(receipt)?)?))
+        .collect::<Result<Vec<_>>>()?;
+
+    let session = client.create_session(image_id.to_string(), input_id, assumption_ids, false)?;
+    log::info!("Proving session {}", session.uuid);
+    let (receipt_url, stats) = loop {
+        let status = session.status(client)?;
+        if succeeded("session", &session.uuid, &status.status, status.error_msg)? {
+            let receipt_url = status
+                .receipt_url
+                .context("session succeeded without a receipt")?;
+            break (receipt_url, status.stats);
+        }
+        thread::sleep(POLL_INTERVAL);
+    };
+    let cycles = stats
+        .map(|stats| Cycles {
+            total: stats.total_cycles,
+            user: stats.cycles,
+            paging: 0,
+            segments: stats.segments,
+        })
+        .unwrap_or_default();
+    // The service is not trusted: its receipts must prove the uploaded image.
+    let receipt: Receipt = bincode::deserialize(&client.download(&receipt_url)?)?;
+    receipt
+        .verify(image_id)
+        .with_context(|| format!("invalid receipt of session {}", session.uuid))?;
+    if opts.receipt_kind != ReceiptKind::Groth16 {
+        return Ok((receipt, cycles));
+    }
+
+    let snark = client.create_snark(session.uuid)?;
+    log::info!("Compressing to Groth16 in session {}", snark.uuid);
+    let receipt_url = loop {
+        let status = snark.status(client)?;
+        if succeeded(
+            "snark session",
+            &snark.uuid,
+            &status.status,
+            status.error_msg,
+        )? {
+            break status.output.context("snark succeeded without a receipt")?;
+        }
+        thread::sleep(POLL_INTERVAL);
+    };
+    let receipt: Receipt = bincode::deserialize(&client.download(&receipt_url)?)?;
+    receipt
+        .verify(image_id)
+        .with_context(|| format!("invalid receipt of snark session {}", snark.uuid))?;
+    Ok((receipt, cycles))
+}
+
+/// Whether a remote session in `status` succeeded, `false` while it is still running.
+///
+/// Fails if the session ended otherwise, or with a status this client does not know, rather than
+/// polling it forever.
+fn succeeded(kind: &str, uuid: &str, status: &str, error_msg: Option<String>) -> Result<bool> {
+    match status {
+        "RUNNING" => Ok(false),
+        "SUCCEEDED" => Ok(true),
+        "FAILED" | "TIMED_OUT" | "ABORTED" => bail!(
+            "{kind} {uuid} {}: {}",
+            status.to_lowercase().replace('_', " "),
+            error_msg.unwrap_or_default()
+        ),
+        _ => bail!("{kind} {uuid} has the unknown status {status}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_running_sessions_are_polled_again() {
+        assert!(!succeeded("session", "1", "RUNNING", None).unwrap());
+        assert!(succeeded("session", "1", "SUCCEEDED", None).unwrap());
+        let error = succeeded("session", "1", "TIMED_OUT", Some("too slow".to_string()));
+        assert_eq!(
+            error.unwrap_err().to_string(),
+            "session 1 timed out: too slow"
+        );
+        for status in ["FAILED", "ABORTED", "PENDING", ""] {
+            assert!(succeeded("session", "1", status, None).is_err(), "{status}");
+        }
+    }
+}
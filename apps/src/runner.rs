@@ -0,0 +1,254 @@
+//! Runs of the publishers as child processes, for the daemons proving metrics unattended.
+//!
+//! A run invokes a publisher built next to the current executable with `--output json` and
+//! `--receipt`, and reads back the [`Record`] it prints and the receipt it writes. Metrics are
+//! thus proven by the same code as on the command line, and a failing run cannot take the daemon
+//! down with it.
+
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    path::{Path, PathBuf},
+    process::Stdio,
+    time::Duration,
+};
+
+use alloy_primitives::{Address, B256};
+use anyhow::{ensure, Context, Result};
+use risc0_ethereum_contracts::encode_seal;
+use risc0_steel::alloy::{
+    network::EthereumWallet, providers::ProviderBuilder, signers::local::PrivateKeySigner, sol,
+    sol_types::SolCall,
+};
+use risc0_zkvm::Receipt;
+use serde::{Deserialize, Serialize};
+use tokio::process::Command;
+use url::Url;
+
+use crate::{image_id, output::Record};
+
+sol! {
+    /// Contract accepting proven metrics, which verifies the seal against the image ID of the
+    /// metric before storing its journal.
+    #[sol(rpc)]
+    interface IMetricConsumer {
+        function submit(bytes calldata journalData, bytes calldata seal) external;
+    }
+}
+
+/// Invocation of a publisher proving a metric.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Task {
+    /// Publisher binary, such as `compound_apr_publisher`.
+    pub publisher: String,
+    /// Ethereum RPC endpoint URL, passed as `--eth-rpc-url` if set.
+    #[serde(default)]
+    pub eth_rpc_url: Option<Url>,
+    /// Further command line arguments of the publisher, such as the markets to measure.
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Environment variables of the publisher, such as `BONSAI_API_URL`.
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+}
+
+impl Task {
+    /// Task running the publisher of the guest named `metric`, unless there is no such guest.
+    pub fn of_metric(metric: &str) -> Option<Self> {
+        image_id(metric)?;
+        let publisher = match metric {
+            "balance_of" => "publisher".to_string(),
+            metric => format!("{metric}_publisher"),
+        };
+        Some(Self {
+            publisher,
+            eth_rpc_url: None,
+            args: Vec::new(),
+            env: BTreeMap::new(),
+        })
+    }
+}
+
+/// Retries of a failed run, with an exponential backoff.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Retry {
+    /// Attempts of a run, including the first one.
+    pub attempts: u32,
+    /// Delay before the first retry in seconds, doubled before every further retry.
+    pub backoff_secs: u64,
+    /// Longest delay before a retry, in seconds.
+    pub max_backoff_secs: u64,
+}
+
+impl Default for Retry {
+    fn default() -> Self {
+        Self {
+            attempts: 3,
+            backoff_secs: 30,
+            max_backoff_secs: 600,
+        }
+    }
+}
+
+impl Retry {
+    /// Delay before retrying a run that failed `failures` times.
+    fn backoff(&self, failures: u32) -> Duration {
+        let factor = 2u64.saturating_pow(failures.saturating_sub(1));
+        Duration::from_secs(
+            self.backoff_secs
+                .saturating_mul(factor)
+                .min(self.max_backoff_secs),
+        )
+    }
+}
+
+/// Metric proven by a run.
+#[derive(Clone, Debug)]
+pub struct Run {
+    pub record: Record,
+    pub receipt: Receipt,
+    /// Attempts it took to prove it.
+    pub attempts: u32,
+}
+
+/// Runs the publishers in a directory.
+#[derive(Clone, Debug)]
+pub struct Runner {
+    bin_dir: PathBuf,
+}
+
+impl Runner {
+    /// Runner of the publishers in `bin_dir`, or else in the directory of the current executable,
+    /// where cargo builds them.
+    pub fn new(bin_dir: Option<PathBuf>) -> Result<Self> {
+        let bin_dir = match bin_dir {
+            Some(bin_dir) => bin_dir,
+            None => env::current_exe()?
+                .parent()
+                .context("executable has no directory")?
+                .to_path_buf(),
+        };
+        Ok(Self { bin_dir })
+    }
+
+    /// Checks that the publisher of `task` exists.
+    pub fn check(&self, task: &Task) -> Result<()> {
+        let program = self.program(task);
+        ensure!(
+            program.is_file(),
+            "publisher {} not found in {}",
+            task.publisher,
+            self.bin_dir.display()
+        );
+        Ok(())
+    }
+
+    fn program(&self, task: &Task) -> PathBuf {
+        self.bin_dir
+            .join(format!("{}{}", task.publisher, env::consts::EXE_SUFFIX))
+    }
+
+    /// Runs `task` once, with its receipt written to `receipt`.
+    pub async fn run(&self, task: &Task, receipt: &Path) -> Result<(Record, Receipt)> {
+        self.check(task)?;
+        let mut command = Command::new(self.program(task));
+        if let Some(eth_rpc_url) = &task.eth_rpc_url {
+            command.arg("--eth-rpc-url").arg(eth_rpc_url.as_str());
+        }
+        command
+            .args(&task.args)
+            .args(["--output", "json", "--receipt"])
+            .arg(receipt)
+            .envs(&task.env)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .kill_on_drop(true);
+
+        let output = command
+            .output()
+            .await
+            .with_context(|| format!("failed to run {}", task.publisher))?;
+        ensure!(
+            output.status.success(),
+            "{} failed with {}",
+            task.publisher,
+            output.status
+        );
+        let stdout = String::from_utf8(output.stdout).context("invalid output")?;
+        let line = stdout
+            .lines()
+            .rev()
+            .find(|line| !line.trim().is_empty())
+            .with_context(|| format!("{} printed no record", task.publisher))?;
+        let record = serde_json::from_str(line).context("invalid record")?;
+        let bytes =
+            fs::read(receipt).with_context(|| format!("failed to read {}", receipt.display()))?;
+        Ok((record, bincode::deserialize(&bytes)?))
+    }
+
+    /// Runs `task` until it succeeds or it failed `retry.attempts` times.
+    pub async fn run_with_retries(
+        &self,
+        task: &Task,
+        receipt: &Path,
+        retry: &Retry,
+    ) -> Result<Run> {
+        let mut attempts = 1;
+        loop {
+            match self.run(task, receipt).await {
+                Ok((record, receipt)) => {
+                    return Ok(Run {
+                        record,
+                        receipt,
+                        attempts,
+                    })
+                }
+                Err(err) if attempts < retry.attempts => {
+                    let delay = retry.backoff(attempts);
+                    log::warn!(
+                        "Attempt {attempts} of {} failed, retrying in {} s: {err:#}",
+                        task.publisher,
+                        delay.as_secs()
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempts += 1;
+                }
+                Err(err) => return Err(err.context(format!("failed after {attempts} attempts"))),
+            }
+        }
+    }
+}
+
+/// Submits the journal and seal of `receipt` to the [`IMetricConsumer`] at `contract` and waits
+/// for the transaction to confirm, returning its hash.
+pub async fn submit(
+    eth_rpc_url: &Url,
+    signer: &PrivateKeySigner,
+    contract: Address,
+    receipt: &Receipt,
+) -> Result<B256> {
+    // ABI encode the seal.
+    let seal = encode_seal(receipt).context("invalid receipt")?;
+    let provider = ProviderBuilder::new()
+        .wallet(EthereumWallet::from(signer.clone()))
+        .on_http(eth_rpc_url.clone());
+    let consumer = IMetricConsumer::new(contract, &provider);
+
+    log::info!(
+        "Sending Tx calling {} Function of {:#}...",
+        IMetricConsumer::submitCall::SIGNATURE,
+        consumer.address()
+    );
+    let call_builder = consumer.submit(receipt.journal.bytes.clone().into(), seal.into());
+    log::debug!("Send {} {}", consumer.address(), call_builder.calldata());
+    let pending_tx = call_builder.send().await?;
+    let tx_hash = *pending_tx.tx_hash();
+    let receipt = pending_tx
+        .get_receipt()
+        .await
+        .with_context(|| format!("transaction did not confirm: {}", tx_hash))?;
+    ensure!(receipt.status(), "transaction failed: {}", tx_hash);
+    Ok(tx_hash)
+}
@@ -0,0 +1,163 @@
+//! Host side of the `score` guest: loading a scoring policy and the receipts of each protocol.
+//!
+//! The policy is written as JSON, with metrics named like the `--metric` of the
+//! `aggregate_publisher`:
+//!
+//! ```json
+//! {
+//!   "weights": [
+//!     { "metric": "vault-apr", "weight": 2, "floor": "0", "cap": "100000000000000000" },
+//!     { "metric": "metamorpho-net-apr", "weight": 1, "floor": "0", "cap": "100000000000000000" }
+//!   ]
+//! }
+//! ```
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use alloy_primitives::{B256, I256, U256};
+use anyhow::{anyhow, ensure, Context, Result};
+use erc20_counter_core::{
+    aggregate::{Metric, Sample},
+    score::{self, Policy, ProtocolJournals, Weight},
+    status::Status,
+};
+use risc0_steel::alloy::sol_types::SolValue;
+use risc0_zkvm::Receipt;
+use serde::Deserialize;
+
+use crate::{image_id, GuestInput};
+
+/// Scoring policy as written in a policy file.
+#[derive(Clone, Debug, Deserialize)]
+pub struct PolicyFile {
+    pub weights: Vec<WeightEntry>,
+}
+
+/// Weight of a metric as written in a policy file, see [`Weight`].
+#[derive(Clone, Debug, Deserialize)]
+pub struct WeightEntry {
+    pub metric: String,
+    pub weight: u32,
+    pub floor: I256,
+    pub cap: I256,
+    #[serde(default)]
+    pub inverted: bool,
+}
+
+impl PolicyFile {
+    /// Reads a policy file.
+    pub fn load(path: &Path) -> Result<Self> {
+        let json = fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+        serde_json::from_slice(&json).with_context(|| format!("invalid policy {}", path.display()))
+    }
+
+    /// Converts the policy into the committed [`Policy`], pinning every metric to the image ID
+    /// of its guest.
+    pub fn to_policy(&self) -> Result<Policy> {
+        let weights = self
+            .weights
+            .iter()
+            .map(|entry| {
+                let metric = Metric::from_str(&entry.metric).map_err(anyhow::Error::msg)?;
+                let image_id = image_id(metric.guest()).context("unknown guest")?;
+                Ok(Weight {
+                    metric: metric.into(),
+                    imageId: B256::from_slice(image_id.as_bytes()),
+                    weight: entry.weight,
+                    floor: entry.floor,
+                    cap: entry.cap,
+                    inverted: entry.inverted,
+                })
+            })
+            .collect::<Result<_>>()?;
+        let policy = Policy { weights };
+        score::check_policy(&policy).map_err(anyhow::Error::msg)?;
+        Ok(policy)
+    }
+}
+
+/// Receipts of the policy metrics of one protocol, written as `name=receipt[,receipt...]` in
+/// policy order. The journals must all measure the same subject, like one vault.
+#[derive(Clone, Debug)]
+pub struct ProtocolReceipts {
+    pub name: String,
+    pub receipts: Vec<PathBuf>,
+}
+
+impl FromStr for ProtocolReceipts {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((name, receipts)) = s.split_once('=') else {
+            return Err(format!("invalid protocol, expected name=receipt,...: {s}"));
+        };
+        Ok(Self {
+            name: name.to_string(),
+            receipts: receipts.split(',').map(PathBuf::from).collect(),
+        })
+    }
+}
+
+/// Verifies the receipts of every protocol against the policy and returns the guest input,
+/// together with the scores the guest is expected to commit.
+pub fn input(
+    policy: &Policy,
+    protocols: &[ProtocolReceipts],
+) -> Result<(GuestInput, Vec<Result<U256, Status>>)> {
+    ensure!(!protocols.is_empty(), "no protocols to score");
+    score::check_names(protocols.iter().map(|protocol| protocol.name.as_str()))
+        .map_err(anyhow::Error::msg)?;
+
+    let mut input = GuestInput::default();
+    let mut journals = Vec::with_capacity(protocols.len());
+    let mut scores = Vec::with_capacity(protocols.len());
+    for protocol in protocols {
+        ensure!(
+            protocol.receipts.len() == policy.weights.len(),
+            "protocol {} has {} receipts for {} metrics",
+            protocol.name,
+            protocol.receipts.len(),
+            policy.weights.len()
+        );
+        let mut samples: Vec<Sample> = Vec::with_capacity(policy.weights.len());
+        let mut protocol_journals = Vec::with_capacity(policy.weights.len());
+        for (weight, path) in policy.weights.iter().zip(&protocol.receipts) {
+            let bytes =
+                fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+            let receipt: Receipt = bincode::deserialize(&bytes)
+                .with_context(|| format!("invalid receipt {}", path.display()))?;
+            receipt
+                .verify(weight.imageId.0)
+                .with_context(|| format!("receipt {} is not of its metric", path.display()))?;
+            // Groth16 receipts cannot be used as assumptions.
+            ensure!(
+                receipt.inner.succinct().is_ok(),
+                "receipt {} is not succinct, prove its batch with --succinct",
+                path.display()
+            );
+
+            let metric = Metric::try_from(weight.metric).expect("checked policy");
+            let journal = receipt.journal.bytes.clone();
+            samples.push(
+                metric
+                    .decode(&journal)
+                    .with_context(|| format!("invalid journal {}", path.display()))?,
+            );
+            protocol_journals.push(journal);
+            input = input.assume(receipt);
+        }
+        score::subject(&samples).map_err(|err| anyhow!("protocol {}: {err}", protocol.name))?;
+        scores.push(score::score(policy, &samples));
+        journals.push(ProtocolJournals {
+            name: protocol.name.clone(),
+            journals: protocol_journals,
+        });
+    }
+
+    let input = input.write(&policy.abi_encode())?.write(&journals)?;
+    Ok((input, scores))
+}
@@ -0,0 +1,276 @@
+//! Units of the values committed by the guests, and their rendering in logs and records.
+//!
+//! Journals commit fixed-point integers. A [`Unit`] knows the scale of such a value and whether
+//! it is a fraction, rendered as a percentage, or an amount of a token, whose decimals and symbol
+//! are read from the chain.
+
+use alloy_primitives::{utils::ParseUnits, Address, U256};
+use erc20_counter_core::{compound::CometMainInterface, erc20::IERC20, erc4626::IERC4626};
+use risc0_steel::{alloy::providers::ProviderBuilder, ethereum::EthEvmEnv, Contract};
+use url::Url;
+
+/// Number of decimal places of rendered percentages.
+const PERCENT_PLACES: u8 = 4;
+/// Maximum number of decimal places of rendered token amounts and other fixed-point numbers.
+const AMOUNT_PLACES: u8 = 6;
+
+/// Scale and meaning of a value committed by a guest.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Unit {
+    /// Fraction in fixed point with `decimals`, such as a rate, rendered as a percentage.
+    Percent { decimals: u8 },
+    /// Number in fixed point with `decimals`, such as a share rate or an interest index.
+    Fixed { decimals: u8 },
+    /// Amount in base units of a token.
+    Token { decimals: u8, symbol: String },
+    /// Integer without a scale, such as a count, a duration in seconds or an amount of a token
+    /// whose decimals are unknown.
+    Integer,
+}
+
+impl Unit {
+    /// Annual rate or other fraction in WAD.
+    pub const RATE: Self = Self::Percent { decimals: 18 };
+    /// Fraction in basis points.
+    pub const BPS: Self = Self::Percent { decimals: 4 };
+    /// Number in WAD.
+    pub const WAD: Self = Self::Fixed { decimals: 18 };
+    /// Number in RAY.
+    pub const RAY: Self = Self::Fixed { decimals: 27 };
+    /// Compound III interest index.
+    pub const COMET_INDEX: Self = Self::Fixed { decimals: 15 };
+
+    /// Number of decimals of the fixed-point values.
+    pub fn decimals(&self) -> u8 {
+        match self {
+            Self::Percent { decimals }
+            | Self::Fixed { decimals }
+            | Self::Token { decimals, .. } => *decimals,
+            Self::Integer => 0,
+        }
+    }
+
+    /// Short name of the unit, e.g. `%`, `wad` or the token symbol.
+    pub fn name(&self) -> String {
+        match self {
+            Self::Percent { .. } => "%".to_string(),
+            Self::Fixed { decimals: 18 } => "wad".to_string(),
+            Self::Fixed { decimals: 27 } => "ray".to_string(),
+            Self::Fixed { decimals } => format!("1e{decimals}"),
+            Self::Token { symbol, .. } => symbol.clone(),
+            Self::Integer => String::new(),
+        }
+    }
+
+    /// Renders `value` for people: percentages and amounts rounded, with their symbol.
+    pub fn format(&self, value: impl Into<ParseUnits>) -> String {
+        let value = value.into();
+        match self {
+            Self::Percent { decimals } => {
+                // A percentage is the fraction with two decimals less.
+                let shown = round(value, decimals.saturating_sub(2), PERCENT_PLACES);
+                format!("{shown}%")
+            }
+            Self::Fixed { decimals } => round(value, *decimals, AMOUNT_PLACES),
+            Self::Token { decimals, symbol } if symbol.is_empty() => {
+                round(value, *decimals, AMOUNT_PLACES)
+            }
+            Self::Token { decimals, symbol } => {
+                format!("{} {symbol}", round(value, *decimals, AMOUNT_PLACES))
+            }
+            Self::Integer => fixed(value, 0),
+        }
+    }
+}
+
+/// Exact decimal representation of the fixed-point `value` with `decimals`.
+pub fn fixed(value: impl Into<ParseUnits>, decimals: u8) -> String {
+    let (negative, abs) = sign_and_abs(value.into());
+    let sign = if negative && !abs.is_zero() { "-" } else { "" };
+    if decimals == 0 {
+        return format!("{sign}{abs}");
+    }
+    let unit = pow10(decimals);
+    let fraction = (abs % unit).to_string();
+    format!(
+        "{sign}{}.{fraction:0>width$}",
+        abs / unit,
+        width = decimals as usize
+    )
+}
+
+/// Decimal representation of the fixed-point `value` with `decimals`, rounded half away from
+/// zero to at most `places` decimal places, without trailing zeros.
+fn round(value: ParseUnits, decimals: u8, places: u8) -> String {
+    let (negative, abs) = sign_and_abs(value);
+    let (abs, decimals) = if decimals > places {
+        let unit = pow10(decimals - places);
+        let (quotient, remainder) = (abs / unit, abs % unit);
+        let rounded = if remainder >= unit - remainder {
+            quotient.saturating_add(U256::from(1))
+        } else {
+            quotient
+        };
+        (rounded, places)
+    } else {
+        (abs, decimals)
+    };
+    let shown = fixed(abs, decimals);
+    let shown = if shown.contains('.') {
+        shown.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        &shown
+    };
+    if negative && shown != "0" {
+        format!("-{shown}")
+    } else {
+        shown.to_string()
+    }
+}
+
+fn sign_and_abs(value: ParseUnits) -> (bool, U256) {
+    match value {
+        ParseUnits::U256(value) => (false, value),
+        ParseUnits::I256(value) => (value.is_negative(), value.unsigned_abs()),
+    }
+}
+
+fn pow10(exponent: u8) -> U256 {
+    U256::from(10).pow(U256::from(exponent))
+}
+
+/// Token whose base units amounts are in, possibly named through the contract holding it.
+#[derive(Clone, Copy, Debug)]
+pub enum Denomination {
+    /// The token itself.
+    Token(Address),
+    /// The asset of an ERC-4626 vault.
+    VaultAsset(Address),
+    /// The base asset of a Compound III market.
+    CometBase(Address),
+}
+
+/// Unit of amounts in `denomination`, from the decimals and symbol of its token at the latest
+/// block.
+///
+/// These calls are only used for rendering and are not part of any proof. If the decimals cannot
+/// be read, amounts are rendered as integers in base units.
+pub async fn token(rpc_url: &Url, denomination: Denomination) -> Unit {
+    match token_metadata(rpc_url, denomination).await {
+        Ok(unit) => unit,
+        Err(err) => {
+            log::warn!("Failed to read the decimals of {denomination:?}: {err:#}");
+            Unit::Integer
+        }
+    }
+}
+
+async fn token_metadata(rpc_url: &Url, denomination: Denomination) -> anyhow::Result<Unit> {
+    let provider = ProviderBuilder::new().on_http(rpc_url.clone());
+    let mut env = EthEvmEnv::builder().provider(provider).build().await?;
+
+    let token = match denomination {
+        Denomination::Token(token) => token,
+        Denomination::VaultAsset(vault) => {
+            Contract::preflight(vault, &mut env)
+                .call_builder(&IERC4626::assetCall {})
+                .call()
+                .await?
+                ._0
+        }
+        Denomination::CometBase(comet) => {
+            Contract::preflight(comet, &mut env)
+                .call_builder(&CometMainInterface::baseTokenCall {})
+                .call()
+                .await?
+                ._0
+        }
+    };
+    let decimals = Contract::preflight(token, &mut env)
+        .call_builder(&IERC20::decimalsCall {})
+        .call()
+        .await?
+        ._0;
+    // Some tokens, like MKR, return their symbol as `bytes32`; their amounts go without one.
+    let symbol = Contract::preflight(token, &mut env)
+        .call_builder(&IERC20::symbolCall {})
+        .call()
+        .await
+        .map(|symbol| symbol._0)
+        .unwrap_or_default();
+    Ok(Unit::Token { decimals, symbol })
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::I256;
+
+    use super::*;
+
+    fn signed(value: i64) -> I256 {
+        I256::try_from(value).unwrap()
+    }
+
+    #[test]
+    fn percentages_are_rounded() {
+        let wad = |value: u128| U256::from(value);
+        assert_eq!(Unit::RATE.format(wad(50_000_000_000_000_000)), "5%");
+        assert_eq!(Unit::RATE.format(wad(123_456_789_000_000_000)), "12.3457%");
+        assert_eq!(Unit::RATE.format(wad(123_456_749_000_000_000)), "12.3457%");
+        assert_eq!(Unit::RATE.format(wad(123_456_000_000_000)), "0.0123%");
+        assert_eq!(Unit::RATE.format(wad(0)), "0%");
+        assert_eq!(Unit::BPS.format(wad(250)), "2.5%");
+        assert_eq!(Unit::BPS.format(signed(-250)), "-2.5%");
+        assert_eq!(Unit::RATE.format(signed(-1)), "0%");
+    }
+
+    #[test]
+    fn fixed_point_numbers_drop_trailing_zeros() {
+        let ray = U256::from(10).pow(U256::from(27));
+        assert_eq!(Unit::RAY.format(ray), "1");
+        assert_eq!(Unit::RAY.format(ray + ray / U256::from(8)), "1.125");
+        assert_eq!(
+            Unit::WAD.format(U256::from(1_234_567_890_000_000_000u64)),
+            "1.234568"
+        );
+        assert_eq!(
+            Unit::COMET_INDEX.format(U256::from(1_000_000_000_000_001u64)),
+            "1"
+        );
+        assert_eq!(Unit::Integer.format(signed(-42)), "-42");
+    }
+
+    #[test]
+    fn token_amounts_carry_their_symbol() {
+        let usdc = Unit::Token {
+            decimals: 6,
+            symbol: "USDC".to_string(),
+        };
+        assert_eq!(usdc.format(U256::from(1_500_000)), "1.5 USDC");
+        assert_eq!(usdc.format(signed(-2_000_000)), "-2 USDC");
+        assert_eq!(usdc.name(), "USDC");
+        let anonymous = Unit::Token {
+            decimals: 6,
+            symbol: String::new(),
+        };
+        assert_eq!(anonymous.format(U256::from(1_500_000)), "1.5");
+    }
+
+    #[test]
+    fn fixed_is_exact() {
+        assert_eq!(fixed(U256::from(1_500_000), 6), "1.500000");
+        assert_eq!(fixed(signed(-1), 6), "-0.000001");
+        assert_eq!(fixed(signed(0), 6), "0.000000");
+        assert_eq!(fixed(U256::MAX, 0), U256::MAX.to_string());
+    }
+
+    #[test]
+    fn units_are_named() {
+        assert_eq!(Unit::RATE.name(), "%");
+        assert_eq!(Unit::WAD.name(), "wad");
+        assert_eq!(Unit::RAY.name(), "ray");
+        assert_eq!(Unit::COMET_INDEX.name(), "1e15");
+        assert_eq!(Unit::Integer.name(), "");
+        assert_eq!(Unit::Integer.decimals(), 0);
+    }
+}
@@ -0,0 +1,150 @@
+//! Round trip of the remote prover through the `prover_server` stand-in.
+
+use std::{
+    env,
+    net::{TcpListener, TcpStream},
+    process::{Child, Command},
+    thread,
+    time::Duration,
+};
+
+use alloy_primitives::{B256, I256, U256};
+use apps::prover::Prover;
+use erc20_counter_core::{
+    aggregate::{self, Metric},
+    journal::MetricJournal,
+    lido,
+};
+use erc20_counter_methods::{AGGREGATE_ELF, AGGREGATE_ID, BALANCE_OF_ELF, LIDO_APR_ID};
+use risc0_steel::Commitment;
+use risc0_zkvm::{
+    serde::to_vec, sha::Digest, FakeReceipt, InnerReceipt, ProverOpts, Receipt, ReceiptClaim,
+};
+
+const API_KEY: &str = "test-key";
+
+/// Stand-in listening on a free port, killed when dropped.
+struct Server {
+    process: Child,
+    url: String,
+}
+
+impl Server {
+    fn start() -> Self {
+        let address = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let process = Command::new(env!("CARGO_BIN_EXE_prover_server"))
+            .arg("--listen")
+            .arg(address.to_string())
+            .env("BONSAI_API_KEY", API_KEY)
+            .env("RISC0_DEV_MODE", "1")
+            .spawn()
+            .unwrap();
+        let server = Self {
+            process,
+            url: format!("http://{address}"),
+        };
+        for _ in 0..100 {
+            if TcpStream::connect(address).is_ok() {
+                return server;
+            }
+            thread::sleep(Duration::from_millis(50));
+        }
+        panic!("prover_server did not start listening on {address}");
+    }
+
+    fn prover(&self, api_key: &str) -> Prover {
+        Prover::Remote {
+            url: self.url.clone(),
+            api_key: api_key.to_string(),
+        }
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+    }
+}
+
+#[test]
+fn failed_sessions_are_reported_to_the_client() {
+    let server = Server::start();
+    // The guest panics reading an empty input, which ends the session as failed after it was
+    // uploaded, created and polled through the API of the stand-in.
+    let error = server
+        .prover(API_KEY)
+        .prove(BALANCE_OF_ELF, &[], Vec::new(), &ProverOpts::succinct())
+        .unwrap_err();
+    let error = format!("{error:#}");
+    assert!(error.starts_with("session "), "{error}");
+    assert!(error.contains(" failed: "), "{error}");
+}
+
+#[test]
+fn clients_with_another_key_are_rejected() {
+    let server = Server::start();
+    let result = server.prover("another-key").prove(
+        BALANCE_OF_ELF,
+        &[],
+        Vec::new(),
+        &ProverOpts::succinct(),
+    );
+    assert!(result.is_err());
+}
+
+#[test]
+fn receipts_are_downloaded_and_verified() {
+    // Fake receipts only verify in dev mode, in the client as well as in the stand-in.
+    env::set_var("RISC0_DEV_MODE", "1");
+    let server = Server::start();
+
+    // Aggregate a single Lido journal, whose receipt is uploaded as an assumption.
+    let commitment = |block: u64| Commitment {
+        id: U256::from(block),
+        digest: B256::repeat_byte(0xaa),
+        configID: B256::repeat_byte(0xcc),
+    };
+    let apr = I256::try_from(30_000_000_000_000_000i64).unwrap();
+    let journal = lido::Journal {
+        commitment: commitment(21_000_000),
+        pastCommitment: commitment(20_990_000),
+        status: 0,
+        stETH: lido::STETH,
+        shareRate: U256::ZERO,
+        pastShareRate: U256::ZERO,
+        apr,
+        apy: apr,
+    }
+    .encode_with_header(1, B256::ZERO);
+    let assumption = Receipt::new(
+        InnerReceipt::Fake(FakeReceipt::new(ReceiptClaim::ok(
+            LIDO_APR_ID,
+            journal.clone(),
+        ))),
+        journal.clone(),
+    );
+    let input = [
+        to_vec(&Digest::from(LIDO_APR_ID)).unwrap(),
+        to_vec(&Metric::LidoApr).unwrap(),
+        to_vec(&vec![journal]).unwrap(),
+    ]
+    .concat();
+
+    let (receipt, _) = server
+        .prover(API_KEY)
+        .prove(
+            AGGREGATE_ELF,
+            &input,
+            vec![assumption],
+            &ProverOpts::succinct(),
+        )
+        .unwrap();
+    receipt.verify(AGGREGATE_ID).unwrap();
+    let (_, journal) = aggregate::Journal::decode_with_header(&receipt.journal.bytes).unwrap();
+    assert_eq!(journal.samples, 1);
+    assert_eq!(journal.mean, apr);
+}
@@ -38,10 +38,31 @@ contract Counter is ICounter {
     /// @notice Counter to track the number of successful verifications.
     uint256 public counter;
 
-    /// @notice Journal that is committed to by the guest.
+    /// @notice Metric type of the `balance_of` guest in the journal header.
+    uint8 public constant BALANCE_OF_METRIC_TYPE = 0;
+
+    /// @notice Version of the `balance_of` journal layout this contract decodes.
+    uint16 public constant SCHEMA_VERSION = 1;
+
+    /// @notice Size of the ABI encoded header starting every journal.
+    uint256 internal constant HEADER_SIZE = 4 * 32;
+
+    /// @notice Header starting every journal, identifying the guest that committed it.
+    struct Header {
+        uint8 metricType;
+        uint16 schemaVersion;
+        uint64 chainId;
+        bytes32 inputHash;
+    }
+
+    /// @notice Journal that is committed to by the guest, after the header.
     struct Journal {
         Steel.Commitment commitment;
-        address tokenContract;
+        uint8 status;
+        address tokenAddress;
+        uint256 circulatingSupply;
+        uint256 pastCirculatingSupply;
+        uint256 inflationBasisPoints;
     }
 
     /// @notice Initialize the contract, binding it to a specified RISC Zero verifier and ERC-20 token address.
@@ -57,9 +78,15 @@ contract Counter is ICounter {
         bytes calldata seal
     ) external {
         // Decode and validate the journal data
-        Journal memory journal = abi.decode(journalData, (Journal));
+        Header memory header = abi.decode(journalData[:HEADER_SIZE], (Header));
+        require(
+            header.metricType == BALANCE_OF_METRIC_TYPE &&
+                header.schemaVersion == SCHEMA_VERSION,
+            "Invalid journal header"
+        );
+        Journal memory journal = abi.decode(journalData[HEADER_SIZE:], (Journal));
         require(
-            journal.tokenContract == tokenContract,
+            journal.tokenAddress == tokenContract,
             "Invalid token address"
         );
         require(
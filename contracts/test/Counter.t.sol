@@ -57,14 +57,19 @@ contract CounterTest is Test {
         // mock the Journal
         Counter.Journal memory journal = Counter.Journal({
             commitment: Steel.Commitment(Encoding.encodeVersionedID(blockNumber, 0), blockHash, bytes32(0x0)),
-            tokenContract: address(token)
+            status: 0,
+            tokenAddress: address(token),
+            circulatingSupply: 0,
+            pastCirculatingSupply: 0,
+            inflationBasisPoints: 0
         });
+        bytes memory journalData = bytes.concat(abi.encode(header()), abi.encode(journal));
         // create a mock proof
-        RiscZeroReceipt memory receipt = verifier.mockProve(imageId, sha256(abi.encode(journal)));
+        RiscZeroReceipt memory receipt = verifier.mockProve(imageId, sha256(journalData));
 
         uint256 previous_count = counter.get();
 
-        counter.increment(abi.encode(journal), receipt.seal);
+        counter.increment(journalData, receipt.seal);
 
         // check that the counter was incremented
         assert(counter.get() == previous_count + 1);
@@ -78,16 +83,30 @@ contract CounterTest is Test {
         // mock the Journal
         Counter.Journal memory journal = Counter.Journal({
             commitment: Steel.Commitment(Encoding.encodeVersionedID(beaconTimestamp, 1), beaconRoot, bytes32(0x0)),
-            tokenContract: address(token)
+            status: 0,
+            tokenAddress: address(token),
+            circulatingSupply: 0,
+            pastCirculatingSupply: 0,
+            inflationBasisPoints: 0
         });
+        bytes memory journalData = bytes.concat(abi.encode(header()), abi.encode(journal));
         // create a mock proof
-        RiscZeroReceipt memory receipt = verifier.mockProve(imageId, sha256(abi.encode(journal)));
+        RiscZeroReceipt memory receipt = verifier.mockProve(imageId, sha256(journalData));
 
         uint256 previous_count = counter.get();
 
-        counter.increment(abi.encode(journal), receipt.seal);
+        counter.increment(journalData, receipt.seal);
 
         // check that the counter was incremented
         assert(counter.get() == previous_count + 1);
     }
+
+    function header() internal view returns (Counter.Header memory) {
+        return Counter.Header({
+            metricType: counter.BALANCE_OF_METRIC_TYPE(),
+            schemaVersion: counter.SCHEMA_VERSION(),
+            chainId: uint64(block.chainid),
+            inputHash: bytes32(0x0)
+        });
+    }
 }
@@ -0,0 +1,98 @@
+use alloy_primitives::{address, Address, U256};
+use alloy_sol_types::sol;
+use risc0_steel::Commitment;
+
+use crate::{
+    math::{self, Rounding, WAD},
+    SECONDS_PER_YEAR,
+};
+
+sol! {
+    struct ReserveConfigurationMap {
+        uint256 data;
+    }
+
+    /// Reserve state as returned by `IPool.getReserveData` (`ReserveDataLegacy` since v3.1).
+    struct ReserveData {
+        ReserveConfigurationMap configuration;
+        uint128 liquidityIndex;
+        uint128 currentLiquidityRate;
+        uint128 variableBorrowIndex;
+        uint128 currentVariableBorrowRate;
+        uint128 currentStableBorrowRate;
+        uint40 lastUpdateTimestamp;
+        uint16 id;
+        address aTokenAddress;
+        address stableDebtTokenAddress;
+        address variableDebtTokenAddress;
+        address interestRateStrategyAddress;
+        uint128 accruedToTreasury;
+        uint128 unbacked;
+        uint128 isolationModeTotalDebt;
+    }
+
+    interface IPool {
+        function getReserveData(address asset) external view returns (ReserveData memory);
+    }
+
+    interface IRewardsController {
+        function getRewardsByAsset(address asset) external view returns (address[] memory);
+        function getRewardsData(address asset, address reward) external view returns (
+            uint256 index,
+            uint256 emissionPerSecond,
+            uint256 lastUpdateTimestamp,
+            uint256 distributionEnd
+        );
+    }
+}
+
+sol! {
+    /// Data committed to by the `aave_apr` guest. All rates are annual and in WAD.
+    ///
+    /// `pool` is the Pool the `reserve` was read from, and `rewardsController` the controller the
+    /// incentives were read from, zero if they were not counted.
+    struct Journal {
+        Commitment commitment;
+        uint8 status;
+        address pool;
+        address reserve;
+        address rewardsController;
+        uint256 supplyApr;
+        uint256 supplyApy;
+        uint256 borrowApr;
+        uint256 borrowApy;
+        uint256 supplyIncentivesApr;
+        uint256 borrowIncentivesApr;
+    }
+}
+
+/// Aave V3 Pool on Ethereum mainnet.
+pub const AAVE_V3_POOL: Address = address!("87870Bca3F3fD6335C3F4ce8392D69350B4fA4E2");
+/// Aave V3 RewardsController on Ethereum mainnet.
+pub const AAVE_V3_REWARDS_CONTROLLER: Address =
+    address!("8164Cc65827dcFe994AB23944CBC90e0aa80bFcb");
+
+/// Annual APR, in WAD, paid by a reward stream to the holders of `total_supply` tokens.
+///
+/// `reward_price` is the value of one whole reward token expressed in base units of the token
+/// held, so the result does not depend on the decimals of either token.
+pub fn incentive_apr(
+    emission_per_second: U256,
+    distribution_end: U256,
+    timestamp: u64,
+    reward_price: U256,
+    reward_decimals: u8,
+    total_supply: U256,
+) -> math::Result<U256> {
+    if distribution_end <= U256::from(timestamp) || total_supply.is_zero() {
+        return Ok(U256::ZERO);
+    }
+    let yearly_emission = math::mul(emission_per_second, U256::from(SECONDS_PER_YEAR))?;
+    let yearly_value = math::mul_div(
+        yearly_emission,
+        reward_price,
+        math::pow10(reward_decimals)?,
+        Rounding::Down,
+    )?;
+    math::mul_div(yearly_value, WAD, total_supply, Rounding::Down)
+}
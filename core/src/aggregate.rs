@@ -0,0 +1,503 @@
+use alloy_primitives::{keccak256, Address, B256, I256, U256};
+use alloy_sol_types::{sol, SolValue};
+use risc0_steel::Commitment;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    aave, compound, curve, erc20, erc4626,
+    journal::{JournalError, MetricJournal, MetricType},
+    lido,
+    math::{self, MathError},
+    morpho,
+    status::Status,
+    tvl, uniswap,
+};
+
+sol! {
+    /// Data committed to by the `aggregate` guest.
+    ///
+    /// `samples` journals of the guest with image ID `imageId` were verified, whose commitments
+    /// are listed in `commitments` in block order; the statistics are over those with an ok
+    /// status. The time weighted mean weighs each value by the distance to
+    /// the next sample, measured in commitment IDs, i.e. in blocks, or in seconds for beacon
+    /// commitments. All samples share the `subject`, see [`Sample::subject`].
+    struct Journal {
+        Commitment[] commitments;
+        uint8 status;
+        bytes32 imageId;
+        uint8 metric;
+        bytes32 subject;
+        uint32 samples;
+        uint32 skipped;
+        int256 min;
+        int256 max;
+        int256 mean;
+        int256 timeWeightedMean;
+    }
+}
+
+/// Journal value of a metric guest, aggregated by the `aggregate` guest and scored by the `score`
+/// guest.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum Metric {
+    /// `annualBaseSupplyRate` of the `compound_apr` guest.
+    CompoundSupplyApr = 0,
+    /// `annualBaseSupplyApy` of the `compound_apr` guest.
+    CompoundSupplyApy = 1,
+    /// `annualBaseBorrowRate` of the `compound_apr` guest.
+    CompoundBorrowApr = 2,
+    /// `annualBaseBorrowApy` of the `compound_apr` guest.
+    CompoundBorrowApy = 3,
+    /// `circulatingSupply` of the `balance_of` guest.
+    CirculatingSupply = 4,
+    /// `inflationBasisPoints` of the `balance_of` guest.
+    InflationBasisPoints = 5,
+    /// `supplyApr` of the `aave_apr` guest.
+    AaveSupplyApr = 6,
+    /// `apr` of the `lido_apr` guest.
+    LidoApr = 7,
+    /// `apr` of the `erc4626_apy` guest.
+    VaultApr = 8,
+    /// `netApr` of the `metamorpho_apr` guest.
+    MetaMorphoNetApr = 9,
+    /// `baseApr` of the `curve_gauge_apr` guest.
+    CurveBaseApr = 10,
+    /// `feeApr` of the `uniswap_fee_apr` guest.
+    UniswapFeeApr = 11,
+    /// `totalValue` of the `tvl` guest.
+    TotalValueLocked = 12,
+}
+
+/// Metric value of one verified journal.
+#[derive(Clone, Debug)]
+pub struct Sample {
+    pub chain_id: u64,
+    pub commitment: Commitment,
+    pub status: u8,
+    /// Digest of what the metric is measured on, like the vault or pool of the journal, so
+    /// that samples of different vaults are not mixed. Metrics of the same vault share it.
+    pub subject: B256,
+    pub value: I256,
+}
+
+impl Sample {
+    /// Whether the value of the sample was computed.
+    pub fn is_ok(&self) -> bool {
+        self.status == u8::from(Status::Ok)
+    }
+}
+
+impl Metric {
+    /// Guest committing the metric.
+    pub fn metric_type(&self) -> MetricType {
+        match self {
+            Self::CompoundSupplyApr
+            | Self::CompoundSupplyApy
+            | Self::CompoundBorrowApr
+            | Self::CompoundBorrowApy => MetricType::CompoundApr,
+            Self::CirculatingSupply | Self::InflationBasisPoints => MetricType::BalanceOf,
+            Self::AaveSupplyApr => MetricType::AaveApr,
+            Self::LidoApr => MetricType::LidoApr,
+            Self::VaultApr => MetricType::Erc4626Apy,
+            Self::MetaMorphoNetApr => MetricType::MetaMorphoApr,
+            Self::CurveBaseApr => MetricType::CurveGaugeApr,
+            Self::UniswapFeeApr => MetricType::UniswapFeeApr,
+            Self::TotalValueLocked => MetricType::Tvl,
+        }
+    }
+
+    /// Name of the guest committing the metric.
+    pub fn guest(&self) -> &'static str {
+        self.metric_type().guest()
+    }
+
+    /// Decodes a journal of [`Metric::guest`] into a sample of the metric.
+    ///
+    /// Metrics over two blocks are sampled at the later block. An unsigned value beyond the range
+    /// of `int256` is sampled with an overflow status.
+    pub fn decode(&self, journal: &[u8]) -> Result<Sample, JournalError> {
+        let (header, commitment, status, subject, value) = match self {
+            Self::CompoundSupplyApr
+            | Self::CompoundSupplyApy
+            | Self::CompoundBorrowApr
+            | Self::CompoundBorrowApy => {
+                let (header, journal) = compound::Journal::decode_with_header(journal)?;
+                let value = match self {
+                    Self::CompoundSupplyApr => signed(U256::from(journal.annualBaseSupplyRate)),
+                    Self::CompoundSupplyApy => signed(journal.annualBaseSupplyApy),
+                    Self::CompoundBorrowApr => signed(U256::from(journal.annualBaseBorrowRate)),
+                    _ => signed(journal.annualBaseBorrowApy),
+                };
+                let subject = subject((compound::CUSDC_V3,));
+                (header, journal.commitment, journal.status, subject, value)
+            }
+            Self::CirculatingSupply | Self::InflationBasisPoints => {
+                let (header, journal) = erc20::Journal::decode_with_header(journal)?;
+                let value = match self {
+                    Self::CirculatingSupply => signed(journal.circulatingSupply),
+                    _ => Ok(journal.inflationBasisPoints),
+                };
+                let subject = subject((journal.tokenAddress,));
+                (header, journal.commitment, journal.status, subject, value)
+            }
+            Self::AaveSupplyApr => {
+                let (header, journal) = aave::Journal::decode_with_header(journal)?;
+                (
+                    header,
+                    journal.commitment,
+                    journal.status,
+                    subject((journal.pool, journal.reserve)),
+                    signed(journal.supplyApr),
+                )
+            }
+            Self::LidoApr => {
+                let (header, journal) = lido::Journal::decode_with_header(journal)?;
+                (
+                    header,
+                    journal.commitment,
+                    journal.status,
+                    subject((journal.stETH,)),
+                    Ok(journal.apr),
+                )
+            }
+            Self::VaultApr => {
+                let (header, journal) = erc4626::Journal::decode_with_header(journal)?;
+                (
+                    header,
+                    journal.commitment,
+                    journal.status,
+                    subject((journal.vault,)),
+                    Ok(journal.apr),
+                )
+            }
+            Self::MetaMorphoNetApr => {
+                let (header, journal) = morpho::Journal::decode_with_header(journal)?;
+                (
+                    header,
+                    journal.commitment,
+                    journal.status,
+                    subject((journal.vault,)),
+                    signed(journal.netApr),
+                )
+            }
+            Self::CurveBaseApr => {
+                let (header, journal) = curve::Journal::decode_with_header(journal)?;
+                (
+                    header,
+                    journal.commitment,
+                    journal.status,
+                    subject((journal.gauge,)),
+                    signed(journal.baseApr),
+                )
+            }
+            Self::UniswapFeeApr => {
+                let (header, journal) = uniswap::Journal::decode_with_header(journal)?;
+                (
+                    header,
+                    journal.commitment,
+                    journal.status,
+                    subject((journal.pool,)),
+                    signed(journal.feeApr),
+                )
+            }
+            Self::TotalValueLocked => {
+                let (header, journal) = tvl::Journal::decode_with_header(journal)?;
+                let holdings: Vec<(Address, Address)> = journal
+                    .holdings
+                    .iter()
+                    .map(|holding| (holding.holder, holding.token))
+                    .collect();
+                (
+                    header,
+                    journal.commitment,
+                    journal.status,
+                    subject((journal.numeraire, holdings)),
+                    signed(journal.totalValue),
+                )
+            }
+        };
+        let (status, value) = match value {
+            Ok(value) => (status, value),
+            Err(err) => (Status::from(err).into(), I256::ZERO),
+        };
+        Ok(Sample {
+            chain_id: header.chainId,
+            commitment,
+            status,
+            subject,
+            value,
+        })
+    }
+}
+
+/// Digest of the journal fields naming what a metric is measured on.
+fn subject(fields: impl SolValue) -> B256 {
+    keccak256(fields.abi_encode())
+}
+
+/// Converts an unsigned journal value into a sample value.
+fn signed(value: U256) -> Result<I256, MathError> {
+    I256::try_from(value).map_err(|_| MathError::Overflow)
+}
+
+impl From<Metric> for u8 {
+    fn from(metric: Metric) -> Self {
+        metric as u8
+    }
+}
+
+impl TryFrom<u8> for Metric {
+    type Error = u8;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        Ok(match value {
+            0 => Self::CompoundSupplyApr,
+            1 => Self::CompoundSupplyApy,
+            2 => Self::CompoundBorrowApr,
+            3 => Self::CompoundBorrowApy,
+            4 => Self::CirculatingSupply,
+            5 => Self::InflationBasisPoints,
+            6 => Self::AaveSupplyApr,
+            7 => Self::LidoApr,
+            8 => Self::VaultApr,
+            9 => Self::MetaMorphoNetApr,
+            10 => Self::CurveBaseApr,
+            11 => Self::UniswapFeeApr,
+            12 => Self::TotalValueLocked,
+            _ => return Err(value),
+        })
+    }
+}
+
+/// Parses a metric written in kebab case, like `compound-supply-apr`.
+impl std::str::FromStr for Metric {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "compound-supply-apr" => Self::CompoundSupplyApr,
+            "compound-supply-apy" => Self::CompoundSupplyApy,
+            "compound-borrow-apr" => Self::CompoundBorrowApr,
+            "compound-borrow-apy" => Self::CompoundBorrowApy,
+            "circulating-supply" => Self::CirculatingSupply,
+            "inflation-basis-points" => Self::InflationBasisPoints,
+            "aave-supply-apr" => Self::AaveSupplyApr,
+            "lido-apr" => Self::LidoApr,
+            "vault-apr" => Self::VaultApr,
+            "metamorpho-net-apr" => Self::MetaMorphoNetApr,
+            "curve-base-apr" => Self::CurveBaseApr,
+            "uniswap-fee-apr" => Self::UniswapFeeApr,
+            "total-value-locked" => Self::TotalValueLocked,
+            _ => return Err(format!("unknown metric: {s}")),
+        })
+    }
+}
+
+/// Checks that the samples are of the same chain and subject, and strictly ordered by their
+/// commitments.
+pub fn check_order(samples: &[Sample]) -> Result<(), String> {
+    let first = samples.first().ok_or("no samples")?;
+    let (_, version) = first.commitment.decode_id();
+    let mut previous_id = None;
+    for sample in samples {
+        let commitment = &sample.commitment;
+        if sample.chain_id != first.chain_id || commitment.configID != first.commitment.configID {
+            return Err(format!("sample of another chain: {}", commitment.configID));
+        }
+        if sample.subject != first.subject {
+            return Err(format!("sample of another subject: {}", sample.subject));
+        }
+        let (id, sample_version) = commitment.decode_id();
+        if sample_version != version {
+            return Err(format!(
+                "mixed commitment versions: {version} and {sample_version}"
+            ));
+        }
+        if previous_id.is_some_and(|previous_id| id <= previous_id) {
+            return Err(format!("sample {id} is out of order"));
+        }
+        previous_id = Some(id);
+    }
+    Ok(())
+}
+
+/// Minimum, maximum, mean and time weighted mean of the samples with an ok status.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Summary {
+    pub min: I256,
+    pub max: I256,
+    pub mean: I256,
+    pub time_weighted_mean: I256,
+}
+
+/// Summarizes samples ordered by [`check_order`], leaving out those without an ok status.
+///
+/// Each value is weighted by the distance between its commitment ID and the next one, so the
+/// last sample only counts if it is the only one. Means round towards zero.
+pub fn summarize(samples: &[Sample]) -> Result<Summary, Status> {
+    let samples: Vec<&Sample> = samples.iter().filter(|sample| sample.is_ok()).collect();
+    let (first, last) = match (samples.first(), samples.last()) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return Err(Status::NoSamples),
+    };
+    let values = samples.iter().map(|sample| sample.value);
+    let min = values.clone().min().unwrap_or_default();
+    let max = values.clone().max().unwrap_or_default();
+    let mean = math::signed_div(
+        math::signed_sum(values)?,
+        I256::from_raw(U256::from(samples.len())),
+    )?;
+
+    let time_weighted_mean = if samples.len() == 1 {
+        first.value
+    } else {
+        let weighted = samples
+            .windows(2)
+            .map(|pair| {
+                let (id, _) = pair[0].commitment.decode_id();
+                let (next_id, _) = pair[1].commitment.decode_id();
+                math::signed_mul(pair[0].value, math::signed_change(id, next_id)?)
+            })
+            .collect::<math::Result<Vec<I256>>>()?;
+        let (first_id, _) = first.commitment.decode_id();
+        let (last_id, _) = last.commitment.decode_id();
+        math::signed_div(
+            math::signed_sum(weighted)?,
+            math::signed_change(first_id, last_id)?,
+        )?
+    };
+
+    Ok(Summary {
+        min,
+        max,
+        mean,
+        time_weighted_mean,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(block: u64, status: Status, value: i64) -> Sample {
+        Sample {
+            chain_id: 1,
+            commitment: Commitment {
+                id: U256::from(block),
+                digest: B256::ZERO,
+                configID: B256::ZERO,
+            },
+            status: status.into(),
+            subject: B256::repeat_byte(1),
+            value: I256::try_from(value).unwrap(),
+        }
+    }
+
+    fn int(value: i64) -> I256 {
+        I256::try_from(value).unwrap()
+    }
+
+    #[test]
+    fn values_are_weighted_by_the_gap_to_the_next_sample() {
+        let samples = [
+            sample(100, Status::Ok, 10),
+            sample(110, Status::Ok, 40),
+            sample(140, Status::Ok, 100),
+        ];
+        let summary = summarize(&samples).unwrap();
+        assert_eq!(summary.min, int(10));
+        assert_eq!(summary.max, int(100));
+        assert_eq!(summary.mean, int(50));
+        // (10 * 10 + 40 * 30) / 40, rounded towards zero.
+        assert_eq!(summary.time_weighted_mean, int(32));
+    }
+
+    #[test]
+    fn skipped_samples_are_covered_by_the_previous_value() {
+        let samples = [
+            sample(100, Status::Ok, 10),
+            sample(110, Status::NoSupply, 1_000),
+            sample(140, Status::Ok, 40),
+            sample(150, Status::Ok, 7),
+        ];
+        let summary = summarize(&samples).unwrap();
+        assert_eq!(summary.min, int(7));
+        assert_eq!(summary.max, int(40));
+        assert_eq!(summary.mean, int(19));
+        // (10 * 40 + 40 * 10) / 50
+        assert_eq!(summary.time_weighted_mean, int(16));
+    }
+
+    #[test]
+    fn single_and_missing_samples() {
+        let summary = summarize(&[sample(100, Status::Ok, -3)]).unwrap();
+        assert_eq!(summary.time_weighted_mean, int(-3));
+        assert_eq!(
+            summarize(&[sample(100, Status::CallReverted, 3)]),
+            Err(Status::NoSamples)
+        );
+    }
+
+    fn morpho_journal(vault: Address, net_apr: u64) -> Vec<u8> {
+        morpho::Journal {
+            commitment: sample(100, Status::Ok, 0).commitment,
+            status: Status::Ok.into(),
+            vault,
+            fee: U256::ZERO,
+            totalAssets: U256::ZERO,
+            markets: Vec::new(),
+            grossApr: U256::from(net_apr),
+            netApr: U256::from(net_apr),
+            netApy: U256::from(net_apr),
+        }
+        .encode_with_header(1, B256::ZERO)
+    }
+
+    #[test]
+    fn metrics_of_one_vault_share_its_subject() {
+        let vault = Address::repeat_byte(7);
+        let erc4626 = erc4626::Journal {
+            commitment: sample(100, Status::Ok, 0).commitment,
+            pastCommitment: sample(90, Status::Ok, 0).commitment,
+            status: Status::Ok.into(),
+            vault,
+            asset: Address::ZERO,
+            sharePrice: U256::ZERO,
+            pastSharePrice: U256::ZERO,
+            totalAssets: U256::ZERO,
+            totalSupply: U256::ZERO,
+            pastTotalAssets: U256::ZERO,
+            pastTotalSupply: U256::ZERO,
+            apr: int(5),
+            apy: int(5),
+        }
+        .encode_with_header(1, B256::ZERO);
+        let vault_apr = Metric::VaultApr.decode(&erc4626).unwrap();
+        let net_apr = Metric::MetaMorphoNetApr
+            .decode(&morpho_journal(vault, 6))
+            .unwrap();
+        assert_eq!(vault_apr.value, int(5));
+        assert_eq!(net_apr.value, int(6));
+        assert_eq!(vault_apr.subject, net_apr.subject);
+
+        let other = Metric::MetaMorphoNetApr
+            .decode(&morpho_journal(Address::repeat_byte(8), 6))
+            .unwrap();
+        assert_ne!(other.subject, net_apr.subject);
+    }
+
+    #[test]
+    fn samples_must_be_ordered_and_of_one_subject() {
+        let mut samples = vec![sample(100, Status::Ok, 1), sample(110, Status::Ok, 2)];
+        assert_eq!(check_order(&samples), Ok(()));
+
+        samples[1].subject = B256::repeat_byte(2);
+        assert!(check_order(&samples)
+            .unwrap_err()
+            .contains("another subject"));
+
+        samples[1] = sample(100, Status::Ok, 2);
+        assert!(check_order(&samples).unwrap_err().contains("out of order"));
+    }
+}
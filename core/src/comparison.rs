@@ -0,0 +1,189 @@
+use alloy_primitives::{Address, B256, U256};
+use alloy_sol_types::sol;
+use risc0_steel::Commitment;
+use serde::{Deserialize, Serialize};
+
+use crate::status::Status;
+
+sol! {
+    /// Supply APR of one market of a comparison, in WAD, excluding rewards and incentives.
+    ///
+    /// All three protocols are measured at the committed block and as simple, non-compounded
+    /// annual rates, so that they compare like for like: the Comet per-second supply rate times
+    /// the seconds of a year, the Morpho Blue borrow rate earned on the utilized share net of the
+    /// market fee, times the seconds of a year and rounded down, and the Aave
+    /// `currentLiquidityRate`, which is already a simple annual rate, rounded half up from RAY.
+    ///
+    /// `market` is the Comet, the Morpho Blue singleton or the Aave V3 Pool, and `marketId` the
+    /// Morpho Blue market ID or the Aave reserve asset, left-padded to 32 bytes; zero for Comet.
+    struct MarketApr {
+        uint8 protocol;
+        address market;
+        bytes32 marketId;
+        uint8 status;
+        uint256 supplyApr;
+    }
+
+    /// Data committed to by the `comparison` guest.
+    ///
+    /// `ranking` holds every compared market: those with an ok status by descending supply APR,
+    /// then the others in input order. Markets with the same APR keep their input order.
+    struct Journal {
+        Commitment commitment;
+        uint8 status;
+        MarketApr[] ranking;
+        MarketApr winner;
+    }
+}
+
+/// Protocol of a compared market, committed as the `protocol` field of a [`MarketApr`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Protocol {
+    Compound = 0,
+    Morpho = 1,
+    Aave = 2,
+}
+
+impl From<Protocol> for u8 {
+    fn from(protocol: Protocol) -> Self {
+        protocol as u8
+    }
+}
+
+/// Market whose supply APR is compared.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SupplyMarket {
+    /// A Compound III market.
+    Comet(Address),
+    /// A Morpho Blue market.
+    Morpho { morpho: Address, id: B256 },
+    /// An Aave V3 reserve.
+    Aave { pool: Address, asset: Address },
+}
+
+impl SupplyMarket {
+    /// Protocol of the market.
+    pub fn protocol(&self) -> Protocol {
+        match self {
+            Self::Comet(_) => Protocol::Compound,
+            Self::Morpho { .. } => Protocol::Morpho,
+            Self::Aave { .. } => Protocol::Aave,
+        }
+    }
+
+    /// Address of the contract holding the market state.
+    pub fn address(&self) -> Address {
+        match self {
+            Self::Comet(comet) => *comet,
+            Self::Morpho { morpho, .. } => *morpho,
+            Self::Aave { pool, .. } => *pool,
+        }
+    }
+
+    /// Identifier of the market within its contract, zero for Comet.
+    pub fn id(&self) -> B256 {
+        match self {
+            Self::Comet(_) => B256::ZERO,
+            Self::Morpho { id, .. } => *id,
+            Self::Aave { asset, .. } => asset.into_word(),
+        }
+    }
+
+    /// Entry of the market in a comparison, given the outcome of its supply APR computation.
+    pub fn with_apr(&self, supply_apr: Result<U256, Status>) -> MarketApr {
+        let (status, supply_apr) = Status::split(supply_apr);
+        MarketApr {
+            protocol: self.protocol().into(),
+            market: self.address(),
+            marketId: self.id(),
+            status: status.into(),
+            supplyApr: supply_apr,
+        }
+    }
+}
+
+/// Ranks the markets as committed in [`Journal::ranking`] and picks the winner, the first market
+/// with an ok status. Without one, the status is [`Status::NoSamples`] and the winner is empty.
+pub fn rank(mut markets: Vec<MarketApr>) -> (Status, Vec<MarketApr>, MarketApr) {
+    let ok = u8::from(Status::Ok);
+    // The sort is stable, so ties and failed markets keep their input order.
+    markets.sort_by(|a, b| {
+        (b.status == ok)
+            .cmp(&(a.status == ok))
+            .then(b.supplyApr.cmp(&a.supplyApr))
+    });
+    match markets
+        .first()
+        .filter(|market| market.status == ok)
+        .cloned()
+    {
+        Some(winner) => (Status::Ok, markets, winner),
+        None => {
+            let winner = MarketApr {
+                protocol: 0,
+                market: Address::ZERO,
+                marketId: B256::ZERO,
+                status: Status::NoSamples.into(),
+                supplyApr: U256::ZERO,
+            };
+            (Status::NoSamples, markets, winner)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn market(id: u8, supply_apr: Result<u64, Status>) -> MarketApr {
+        SupplyMarket::Comet(Address::repeat_byte(id)).with_apr(supply_apr.map(U256::from))
+    }
+
+    fn ids(markets: &[MarketApr]) -> Vec<u8> {
+        markets.iter().map(|market| market.market[0]).collect()
+    }
+
+    #[test]
+    fn markets_are_ranked_by_descending_apr_with_ties_in_input_order() {
+        let (status, ranking, winner) = rank(vec![
+            market(1, Ok(3)),
+            market(2, Ok(5)),
+            market(3, Ok(3)),
+            market(4, Ok(5)),
+        ]);
+        assert_eq!(status, Status::Ok);
+        assert_eq!(ids(&ranking), [2, 4, 1, 3]);
+        assert_eq!(winner.market, Address::repeat_byte(2));
+        assert_eq!(winner.supplyApr, U256::from(5));
+    }
+
+    #[test]
+    fn failed_markets_go_last_in_input_order() {
+        let (status, ranking, winner) = rank(vec![
+            market(1, Err(Status::CallReverted)),
+            market(2, Ok(0)),
+            market(3, Err(Status::Overflow)),
+            market(4, Ok(1)),
+        ]);
+        assert_eq!(status, Status::Ok);
+        assert_eq!(ids(&ranking), [4, 2, 1, 3]);
+        assert_eq!(winner.market, Address::repeat_byte(4));
+        assert_eq!(ranking[2].status, u8::from(Status::CallReverted));
+        assert_eq!(ranking[2].supplyApr, U256::ZERO);
+    }
+
+    #[test]
+    fn comparisons_without_an_ok_market_have_no_winner() {
+        let (status, ranking, winner) = rank(vec![
+            market(1, Err(Status::NoSupply)),
+            market(2, Err(Status::CallReverted)),
+        ]);
+        assert_eq!(status, Status::NoSamples);
+        assert_eq!(ids(&ranking), [1, 2]);
+        assert_eq!(winner.status, u8::from(Status::NoSamples));
+        assert_eq!(winner.market, Address::ZERO);
+        assert_eq!(winner.marketId, B256::ZERO);
+        assert_eq!(winner.supplyApr, U256::ZERO);
+    }
+}
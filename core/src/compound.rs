@@ -0,0 +1,206 @@
+use alloy_primitives::{address, uint, Address, Bytes, U256};
+use alloy_sol_types::sol;
+use risc0_steel::Commitment;
+
+use crate::{
+    math::{self, mul_div, Rounding, WAD},
+    tvl::USDC,
+    SECONDS_PER_YEAR,
+};
+
+sol! {
+    struct TotalsBasic {
+        uint64 baseSupplyIndex;
+        uint64 baseBorrowIndex;
+        uint64 trackingSupplyIndex;
+        uint64 trackingBorrowIndex;
+        uint104 totalSupplyBase;
+        uint104 totalBorrowBase;
+        uint40 lastAccrualTime;
+        uint8 pauseFlags;
+    }
+
+    /// Simplified interface of the Compound Finance Comet contract
+    interface CometMainInterface {
+        function getSupplyRate(uint256 utilization) virtual public view returns (uint64);
+        function getBorrowRate(uint256 utilization) virtual public view returns (uint64);
+        function getUtilization() public view returns (uint256);
+        function baseToken() public view returns (address);
+
+        function totalSupply() public view returns(uint256);
+        function totalBorrow() public view returns(uint256);
+
+        function baseTrackingSupplySpeed() public view returns(uint256);
+        function baseTrackingBorrowSpeed() public view returns(uint256);
+
+        function getReserves() public view returns(int256);
+        function totalsBasic() public view returns(TotalsBasic memory);
+    }
+}
+
+sol! {
+    /// Data committed to by the `compound_apr` guest. All rates are annual and in WAD; the COMP
+    /// rewards rates value the COMP distributed to a side of the market in USDC.
+    struct Journal {
+        Commitment commitment;
+        uint8 status;
+        uint64 annualBaseSupplyRate;
+        uint256 annualBaseSupplyApy;
+        uint256 annualCompRewardsSupplyRate;
+        uint64 annualBaseBorrowRate;
+        uint256 annualBaseBorrowApy;
+        uint256 annualCompRewardsBorrowRate;
+    }
+
+    /// Data committed to by the `comet_revenue` guest. Amounts are in base units of the market
+    /// base asset.
+    struct RevenueJournal {
+        Commitment commitment;
+        Commitment pastCommitment;
+        uint8 status;
+        address comet;
+        int256 reserves;
+        int256 pastReserves;
+        int256 reserveGrowth;
+        uint256 borrowInterest;
+        uint256 supplyInterest;
+        int256 interestRevenue;
+    }
+}
+
+/// Compound III USDC market on Ethereum mainnet.
+pub const CUSDC_V3: Address = address!("c3d688B66703497DAA19211EEdff47f25384cdc3");
+
+/// Scale of the Comet base supply and borrow indices.
+pub const BASE_INDEX_SCALE: U256 = uint!(1_000_000_000_000_000_U256);
+
+/// Scale of the Comet base tracking speeds, which are in COMP per second.
+pub const TRACKING_INDEX_SCALE: U256 = uint!(1_000_000_000_000_000_U256);
+
+/// COMP token on Ethereum mainnet.
+pub const COMP: Address = address!("c00e94Cb662C3520282E6f5717214004A7f26888");
+
+/// WETH on Ethereum mainnet.
+pub const WETH: Address = address!("C02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2");
+
+/// Uniswap V3 path the `compound_apr` guest quotes one COMP with, shared with its host so that
+/// both price COMP the same way.
+///
+/// COMP is quoted in USDC, the base asset of [`CUSDC_V3`], through the 0.3% COMP/WETH and the
+/// 0.05% WETH/USDC pools.
+pub fn comp_price_path() -> Bytes {
+    let mut path = Vec::with_capacity(3 * 20 + 2 * 3);
+    path.extend_from_slice(COMP.as_slice());
+    path.extend_from_slice(&3000u32.to_be_bytes()[1..]);
+    path.extend_from_slice(WETH.as_slice());
+    path.extend_from_slice(&500u32.to_be_bytes()[1..]);
+    path.extend_from_slice(USDC.as_slice());
+    Bytes::from(path)
+}
+
+/// Annual COMP rewards APR, in WAD, of a market side, given its base tracking speed, the price of
+/// one COMP and the total supplied or borrowed, both in base units of the base asset.
+pub fn rewards_apr(tracking_speed: U256, comp_price: U256, total: U256) -> math::Result<U256> {
+    // COMP distributed in a year, in WAD.
+    let yearly_rewards = mul_div(
+        math::mul(tracking_speed, U256::from(SECONDS_PER_YEAR))?,
+        WAD,
+        TRACKING_INDEX_SCALE,
+        Rounding::Down,
+    )?;
+    mul_div(yearly_rewards, comp_price, total, Rounding::Down)
+}
+
+/// Interest accrued on `principal` while the index moved from `past_index` to `index`.
+pub fn accrued_interest(principal: U256, past_index: U256, index: U256) -> math::Result<U256> {
+    if index <= past_index {
+        return Ok(U256::ZERO);
+    }
+    mul_div(
+        principal,
+        index - past_index,
+        BASE_INDEX_SCALE,
+        Rounding::Down,
+    )
+}
+
+/// Interest paid by borrowers and interest earned by suppliers between two `totalsBasic`
+/// snapshots, assuming the principals of the past snapshot.
+pub fn interest_between(past: &TotalsBasic, present: &TotalsBasic) -> math::Result<(U256, U256)> {
+    let borrow_interest = accrued_interest(
+        U256::from(past.totalBorrowBase),
+        U256::from(past.baseBorrowIndex),
+        U256::from(present.baseBorrowIndex),
+    )?;
+    let supply_interest = accrued_interest(
+        U256::from(past.totalSupplyBase),
+        U256::from(past.baseSupplyIndex),
+        U256::from(present.baseSupplyIndex),
+    )?;
+    Ok((borrow_interest, supply_interest))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use alloy_primitives::aliases::U104;
+
+    use super::*;
+    use crate::pricing::SwapPath;
+
+    fn totals(supply_index: u64, borrow_index: u64) -> TotalsBasic {
+        TotalsBasic {
+            baseSupplyIndex: supply_index,
+            baseBorrowIndex: borrow_index,
+            trackingSupplyIndex: 0,
+            trackingBorrowIndex: 0,
+            totalSupplyBase: U104::from(1_000_000_000_000u64),
+            totalBorrowBase: U104::from(800_000_000_000u64),
+            lastAccrualTime: Default::default(),
+            pauseFlags: 0,
+        }
+    }
+
+    #[test]
+    fn comp_is_quoted_in_usdc() {
+        let path = SwapPath::from_str(&format!("{COMP}:3000:{WETH}:500:{USDC}")).unwrap();
+        assert_eq!(comp_price_path(), path.encode());
+    }
+
+    #[test]
+    fn rewards_aprs_are_in_wad() {
+        // 0.1 COMP/s at 50 USDC to 1B USDC of supply is 157,680,000 USDC a year.
+        let apr = rewards_apr(
+            U256::from(100_000_000_000_000u64),
+            U256::from(50_000_000),
+            U256::from(1_000_000_000_000_000u64),
+        )
+        .unwrap();
+        assert_eq!(apr, U256::from(157_680_000_000_000_000u64));
+    }
+
+    #[test]
+    fn interest_follows_the_index_growth_of_the_past_principals() {
+        let past = totals(1_000_000_000_000_000, 1_000_000_000_000_000);
+        let present = totals(1_010_000_000_000_000, 1_020_000_000_000_000);
+        let (borrow_interest, supply_interest) = interest_between(&past, &present).unwrap();
+        // 2% on 800,000 USDC borrowed and 1% on 1,000,000 USDC supplied.
+        assert_eq!(borrow_interest, U256::from(16_000_000_000u64));
+        assert_eq!(supply_interest, U256::from(10_000_000_000u64));
+    }
+
+    #[test]
+    fn shrinking_indices_accrue_nothing() {
+        let principal = U256::from(1_000_000);
+        let index = U256::from(1_000_000_000_000_000u64);
+        assert_eq!(
+            accrued_interest(principal, index, index - U256::from(1)).unwrap(),
+            U256::ZERO
+        );
+        assert_eq!(
+            accrued_interest(principal, index, index).unwrap(),
+            U256::ZERO
+        );
+    }
+}
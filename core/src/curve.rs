@@ -0,0 +1,147 @@
+use alloy_primitives::{address, Address, U256};
+use alloy_sol_types::sol;
+use risc0_steel::Commitment;
+
+use crate::{
+    math::{self, mul_div, Rounding, WAD},
+    SECONDS_PER_YEAR,
+};
+
+sol! {
+    interface ILiquidityGauge {
+        function inflation_rate() external view returns (uint256);
+        function working_supply() external view returns (uint256);
+        function totalSupply() external view returns (uint256);
+        function lp_token() external view returns (address);
+    }
+
+    interface ICurveToken {
+        function minter() external view returns (address);
+    }
+
+    interface IGaugeController {
+        function gauge_relative_weight(address addr) external view returns (uint256);
+    }
+
+    interface ICurvePool {
+        function get_virtual_price() external view returns (uint256);
+    }
+}
+
+sol! {
+    /// Data committed to by the `curve_gauge_apr` guest.
+    ///
+    /// Prices are the value of one whole token in base units of the numeraire, rates are annual
+    /// and in WAD. `pool` is the Curve pool of the LP token staked in the `gauge`, and `baseToken`
+    /// the token its virtual price is denominated in.
+    struct Journal {
+        Commitment commitment;
+        uint8 status;
+        address gauge;
+        address gaugeController;
+        address pool;
+        address baseToken;
+        address numeraire;
+        uint256 inflationRate;
+        uint256 relativeWeight;
+        uint256 workingSupply;
+        uint256 totalSupply;
+        uint256 virtualPrice;
+        uint256 crvPrice;
+        uint256 lpPrice;
+        uint256 baseApr;
+        uint256 maxBoostApr;
+    }
+}
+
+/// Curve GaugeController on Ethereum mainnet.
+pub const GAUGE_CONTROLLER: Address = address!("2F50D538606Fa9EDD2B11E2446BEb18C9D5846bB");
+/// CRV token on Ethereum mainnet.
+pub const CRV: Address = address!("D533a949740bb3306d119CC777fa900bA034cd52");
+
+/// Whether `pool` is the Curve pool of the gauge LP token `lp_token`, given the minter of that
+/// token. Newer pools are their own LP token, older ones mint a separate one.
+pub fn is_gauge_pool(pool: Address, lp_token: Address, minter: Option<Address>) -> bool {
+    lp_token == pool || minter == Some(pool)
+}
+
+/// Price of one LP token, given the pool virtual price and the price of its base asset.
+pub fn lp_price(virtual_price: U256, base_price: U256) -> math::Result<U256> {
+    mul_div(virtual_price, base_price, WAD, Rounding::Down)
+}
+
+/// CRV reward APR, in WAD, of a fully boosted gauge deposit.
+///
+/// A fully boosted deposit has a working balance equal to its balance, so it earns its share of
+/// the gauge emissions over the `working_supply`.
+pub fn max_boost_apr(
+    inflation_rate: U256,
+    relative_weight: U256,
+    working_supply: U256,
+    crv_price: U256,
+    lp_price: U256,
+) -> math::Result<U256> {
+    let working_value = mul_div(working_supply, lp_price, WAD, Rounding::Down)?;
+    if working_value.is_zero() {
+        return Ok(U256::ZERO);
+    }
+    let gauge_rate = mul_div(inflation_rate, relative_weight, WAD, Rounding::Down)?;
+    let yearly_emission = math::mul(gauge_rate, U256::from(SECONDS_PER_YEAR))?;
+    let yearly_value = mul_div(yearly_emission, crv_price, WAD, Rounding::Down)?;
+    mul_div(yearly_value, WAD, working_value, Rounding::Down)
+}
+
+/// CRV reward APR, in WAD, of an unboosted gauge deposit, whose working balance is 40% of it.
+pub fn base_apr(max_boost_apr: U256) -> math::Result<U256> {
+    mul_div(max_boost_apr, U256::from(4), U256::from(10), Rounding::Down)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const USDC_UNIT: u64 = 1_000_000;
+
+    #[test]
+    fn lp_prices_scale_the_base_price_by_the_virtual_price() {
+        let virtual_price = U256::from(1_020_000_000_000_000_000u64);
+        let price = lp_price(virtual_price, U256::from(USDC_UNIT)).unwrap();
+        assert_eq!(price, U256::from(1_020_000));
+    }
+
+    #[test]
+    fn aprs_follow_the_gauge_share_of_the_emissions() {
+        // 1 CRV/s for the whole controller, 10% of it to a gauge with 1M working LP tokens
+        // worth 1 USDC each, and CRV at 0.5 USDC.
+        let apr = max_boost_apr(
+            WAD,
+            WAD / U256::from(10),
+            U256::from(1_000_000) * WAD,
+            U256::from(USDC_UNIT / 2),
+            U256::from(USDC_UNIT),
+        )
+        .unwrap();
+        // 3,153,600 CRV a year, worth 1,576,800 USDC, over 1,000,000 USDC.
+        assert_eq!(apr, U256::from(1_576_800_000_000_000_000u64));
+        assert_eq!(
+            base_apr(apr).unwrap(),
+            U256::from(630_720_000_000_000_000u64)
+        );
+    }
+
+    #[test]
+    fn gauges_without_working_supply_earn_nothing() {
+        let apr = max_boost_apr(WAD, WAD, U256::ZERO, U256::from(USDC_UNIT), WAD).unwrap();
+        assert_eq!(apr, U256::ZERO);
+    }
+
+    #[test]
+    fn pools_issue_the_gauge_lp_token() {
+        let pool = Address::repeat_byte(1);
+        let lp_token = Address::repeat_byte(2);
+        assert!(is_gauge_pool(pool, pool, None));
+        assert!(is_gauge_pool(pool, lp_token, Some(pool)));
+        assert!(!is_gauge_pool(pool, lp_token, Some(lp_token)));
+        assert!(!is_gauge_pool(pool, lp_token, None));
+    }
+}
@@ -0,0 +1,77 @@
+use alloy_primitives::{uint, I256, U256};
+use alloy_sol_types::sol;
+use risc0_steel::Commitment;
+
+use crate::math::{self, Rounding};
+
+sol! {
+    /// ERC-20 functions, including the optional metadata extension.
+    interface IERC20 {
+        function balanceOf(address account) external view returns (uint256);
+        function totalSupply() external view returns (uint256);
+        function decimals() external view returns (uint8);
+        function symbol() external view returns (string);
+    }
+}
+
+sol! {
+    /// Data committed to by the `balance_of` guest. `inflationBasisPoints` is negative when the
+    /// circulating supply shrank.
+    struct Journal {
+        Commitment commitment;
+        uint8 status;
+        address tokenAddress;
+        uint256 circulatingSupply;
+        uint256 pastCirculatingSupply;
+        int256 inflationBasisPoints;
+    }
+}
+
+const BASIS_POINTS: U256 = uint!(10_000_U256);
+
+/// Total supply minus the balances held by non-circulating accounts.
+pub fn circulating_supply(
+    total_supply: U256,
+    excluded_balances: impl IntoIterator<Item = U256>,
+) -> math::Result<U256> {
+    excluded_balances
+        .into_iter()
+        .try_fold(total_supply, math::sub)
+}
+
+/// Change of the circulating supply since `past_circulating_supply`, in basis points, negative
+/// on deflation.
+pub fn inflation_basis_points(
+    past_circulating_supply: U256,
+    circulating_supply: U256,
+) -> math::Result<I256> {
+    math::signed_mul_div(
+        math::signed_change(past_circulating_supply, circulating_supply)?,
+        BASIS_POINTS,
+        past_circulating_supply,
+        Rounding::Down,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inflation_is_signed() {
+        let supply = U256::from(1_000_000);
+        assert_eq!(
+            inflation_basis_points(supply, U256::from(1_025_000)),
+            Ok(I256::try_from(250).unwrap())
+        );
+        assert_eq!(
+            inflation_basis_points(supply, U256::from(975_000)),
+            Ok(I256::try_from(-250).unwrap())
+        );
+        assert_eq!(inflation_basis_points(supply, supply), Ok(I256::ZERO));
+        assert_eq!(
+            inflation_basis_points(U256::ZERO, supply),
+            Err(math::MathError::DivisionByZero)
+        );
+    }
+}
@@ -0,0 +1,34 @@
+use alloy_sol_types::sol;
+use risc0_steel::Commitment;
+
+sol! {
+    interface IERC4626 {
+        function asset() external view returns (address);
+        function decimals() external view returns (uint8);
+        function totalAssets() external view returns (uint256);
+        function totalSupply() external view returns (uint256);
+        function convertToAssets(uint256 shares) external view returns (uint256);
+    }
+}
+
+sol! {
+    /// Data committed to by the `erc4626_apy` guest. Share prices are the assets redeemable for
+    /// one whole share, rates are annual and in WAD: `apr` is the simple annualized growth of the
+    /// share price and `apy` the growth compounded over a year of such windows. A share price
+    /// that shrank, as after a loss of the vault, yields negative rates.
+    struct Journal {
+        Commitment commitment;
+        Commitment pastCommitment;
+        uint8 status;
+        address vault;
+        address asset;
+        uint256 sharePrice;
+        uint256 pastSharePrice;
+        uint256 totalAssets;
+        uint256 totalSupply;
+        uint256 pastTotalAssets;
+        uint256 pastTotalSupply;
+        int256 apr;
+        int256 apy;
+    }
+}
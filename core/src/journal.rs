@@ -0,0 +1,348 @@
+//! Header identifying the guest and layout of every journal.
+//!
+//! A journal is the ABI encoded [`Header`] followed by the ABI encoded journal of its guest, so
+//! the header can be decoded at the same offset whatever the layout of the rest of the journal.
+
+use std::fmt;
+
+use alloy_primitives::B256;
+use alloy_sol_types::{sol, SolType, SolValue};
+
+use crate::{
+    aave, aggregate, comparison, compound, curve, erc20, erc4626, lido, morpho, realized, score,
+    tvl, uniswap,
+};
+
+sol! {
+    /// Header starting every journal.
+    ///
+    /// `inputHash` is the SHA-256 digest of the serialized input of the guest.
+    struct Header {
+        uint8 metricType;
+        uint16 schemaVersion;
+        uint64 chainId;
+        bytes32 inputHash;
+    }
+}
+
+/// Size of the ABI encoded [`Header`].
+pub const HEADER_SIZE: usize = 4 * 32;
+
+/// Guest that committed a journal, committed as the `metricType` of its [`Header`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum MetricType {
+    BalanceOf = 0,
+    CompoundApr = 1,
+    AaveApr = 2,
+    LidoApr = 3,
+    Erc4626Apy = 4,
+    MetaMorphoApr = 5,
+    UniswapFeeApr = 6,
+    Tvl = 7,
+    CurveGaugeApr = 8,
+    CometRevenue = 9,
+    RealizedApr = 10,
+    Aggregate = 11,
+    Comparison = 12,
+    Score = 13,
+}
+
+impl MetricType {
+    /// Name of the guest.
+    pub fn guest(&self) -> &'static str {
+        match self {
+            Self::BalanceOf => "balance_of",
+            Self::CompoundApr => "compound_apr",
+            Self::AaveApr => "aave_apr",
+            Self::LidoApr => "lido_apr",
+            Self::Erc4626Apy => "erc4626_apy",
+            Self::MetaMorphoApr => "metamorpho_apr",
+            Self::UniswapFeeApr => "uniswap_fee_apr",
+            Self::Tvl => "tvl",
+            Self::CurveGaugeApr => "curve_gauge_apr",
+            Self::CometRevenue => "comet_revenue",
+            Self::RealizedApr => "realized_apr",
+            Self::Aggregate => "aggregate",
+            Self::Comparison => "comparison",
+            Self::Score => "score",
+        }
+    }
+
+    /// Version of the journal layout of the guest, increased whenever its fields change.
+    pub fn schema_version(&self) -> u16 {
+        1
+    }
+}
+
+impl From<MetricType> for u8 {
+    fn from(metric_type: MetricType) -> Self {
+        metric_type as u8
+    }
+}
+
+impl TryFrom<u8> for MetricType {
+    type Error = u8;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        Ok(match value {
+            0 => Self::BalanceOf,
+            1 => Self::CompoundApr,
+            2 => Self::AaveApr,
+            3 => Self::LidoApr,
+            4 => Self::Erc4626Apy,
+            5 => Self::MetaMorphoApr,
+            6 => Self::UniswapFeeApr,
+            7 => Self::Tvl,
+            8 => Self::CurveGaugeApr,
+            9 => Self::CometRevenue,
+            10 => Self::RealizedApr,
+            11 => Self::Aggregate,
+            12 => Self::Comparison,
+            13 => Self::Score,
+            _ => return Err(value),
+        })
+    }
+}
+
+/// Error returned when decoding a journal.
+#[derive(Debug)]
+pub enum JournalError {
+    /// The header or the journal is not valid ABI.
+    Abi(alloy_sol_types::Error),
+    /// The header names an unknown guest.
+    UnknownMetricType(u8),
+    /// The journal was committed by another guest.
+    WrongMetricType {
+        expected: MetricType,
+        found: MetricType,
+    },
+    /// The journal layout is of another version than the one of this build.
+    UnsupportedSchemaVersion {
+        metric_type: MetricType,
+        version: u16,
+    },
+}
+
+impl fmt::Display for JournalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Abi(err) => write!(f, "invalid journal: {err}"),
+            Self::UnknownMetricType(metric_type) => {
+                write!(f, "unknown metric type: {metric_type}")
+            }
+            Self::WrongMetricType { expected, found } => {
+                write!(
+                    f,
+                    "journal of {} instead of {}",
+                    found.guest(),
+                    expected.guest()
+                )
+            }
+            Self::UnsupportedSchemaVersion {
+                metric_type,
+                version,
+            } => write!(
+                f,
+                "unsupported schema version {version} of {}",
+                metric_type.guest()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for JournalError {}
+
+impl From<alloy_sol_types::Error> for JournalError {
+    fn from(err: alloy_sol_types::Error) -> Self {
+        Self::Abi(err)
+    }
+}
+
+/// Decodes the header of a journal, checking that it names a known guest and schema version.
+pub fn decode_header(journal: &[u8]) -> Result<(Header, MetricType), JournalError> {
+    let Some(header) = journal.get(..HEADER_SIZE) else {
+        return Err(alloy_sol_types::Error::Overrun.into());
+    };
+    let header = <Header as SolType>::abi_decode(header, true)?;
+    let metric_type =
+        MetricType::try_from(header.metricType).map_err(JournalError::UnknownMetricType)?;
+    if header.schemaVersion != metric_type.schema_version() {
+        return Err(JournalError::UnsupportedSchemaVersion {
+            metric_type,
+            version: header.schemaVersion,
+        });
+    }
+    Ok((header, metric_type))
+}
+
+/// Journal committed by a guest, after a [`Header`] naming [`MetricJournal::METRIC_TYPE`].
+pub trait MetricJournal: SolValue + From<<Self::SolType as SolType>::RustType> {
+    /// Guest committing the journal.
+    const METRIC_TYPE: MetricType;
+
+    /// Encodes the journal after its header.
+    fn encode_with_header(&self, chain_id: u64, input_hash: B256) -> Vec<u8> {
+        let header = Header {
+            metricType: Self::METRIC_TYPE.into(),
+            schemaVersion: Self::METRIC_TYPE.schema_version(),
+            chainId: chain_id,
+            inputHash: input_hash,
+        };
+        let mut journal = header.abi_encode();
+        journal.extend(self.abi_encode());
+        journal
+    }
+
+    /// Decodes a journal and its header, checking that it was committed by the guest.
+    fn decode_with_header(journal: &[u8]) -> Result<(Header, Self), JournalError> {
+        let (header, metric_type) = decode_header(journal)?;
+        if metric_type != Self::METRIC_TYPE {
+            return Err(JournalError::WrongMetricType {
+                expected: Self::METRIC_TYPE,
+                found: metric_type,
+            });
+        }
+        let journal = Self::abi_decode(&journal[HEADER_SIZE..], true)?;
+        Ok((header, journal))
+    }
+}
+
+macro_rules! metric_journal {
+    ($($journal:ty => $metric_type:ident),* $(,)?) => {
+        $(
+            impl MetricJournal for $journal {
+                const METRIC_TYPE: MetricType = MetricType::$metric_type;
+            }
+        )*
+    };
+}
+
+metric_journal! {
+    erc20::Journal => BalanceOf,
+    compound::Journal => CompoundApr,
+    aave::Journal => AaveApr,
+    lido::Journal => LidoApr,
+    erc4626::Journal => Erc4626Apy,
+    morpho::Journal => MetaMorphoApr,
+    uniswap::Journal => UniswapFeeApr,
+    tvl::Journal => Tvl,
+    curve::Journal => CurveGaugeApr,
+    compound::RevenueJournal => CometRevenue,
+    realized::Journal => RealizedApr,
+    aggregate::Journal => Aggregate,
+    comparison::Journal => Comparison,
+    score::Journal => Score,
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::{Address, I256, U256};
+    use risc0_steel::Commitment;
+
+    use super::*;
+
+    fn commitment(block: u64) -> Commitment {
+        Commitment {
+            id: U256::from(block),
+            digest: B256::repeat_byte(0xaa),
+            configID: B256::repeat_byte(0xcc),
+        }
+    }
+
+    /// Encodes `journal`, decodes it back and checks that nothing was lost on the way.
+    fn round_trip<J: MetricJournal>(journal: &J) {
+        let input_hash = B256::repeat_byte(0x11);
+        let encoded = journal.encode_with_header(1, input_hash);
+        let (header, decoded) = J::decode_with_header(&encoded).unwrap();
+        assert_eq!(header.metricType, u8::from(J::METRIC_TYPE));
+        assert_eq!(header.schemaVersion, J::METRIC_TYPE.schema_version());
+        assert_eq!(header.chainId, 1);
+        assert_eq!(header.inputHash, input_hash);
+        assert_eq!(decoded.abi_encode(), journal.abi_encode());
+        assert_eq!(decode_header(&encoded).unwrap().1, J::METRIC_TYPE);
+    }
+
+    fn erc20_journal() -> erc20::Journal {
+        erc20::Journal {
+            commitment: commitment(21_000_000),
+            status: 0,
+            tokenAddress: Address::repeat_byte(0x01),
+            circulatingSupply: U256::from(975_000),
+            pastCirculatingSupply: U256::from(1_000_000),
+            inflationBasisPoints: I256::try_from(-250).unwrap(),
+        }
+    }
+
+    #[test]
+    fn journals_round_trip() {
+        round_trip(&erc20_journal());
+        round_trip(&lido::Journal {
+            commitment: commitment(21_000_000),
+            pastCommitment: commitment(20_990_000),
+            status: 0,
+            stETH: lido::STETH,
+            shareRate: U256::from(10).pow(U256::from(27)),
+            pastShareRate: U256::from(10).pow(U256::from(27)) + U256::from(1),
+            apr: I256::MINUS_ONE,
+            apy: I256::MINUS_ONE,
+        });
+        round_trip(&aggregate::Journal {
+            commitments: (0..3).map(|i| commitment(21_000_000 + i)).collect(),
+            status: 0,
+            imageId: B256::repeat_byte(0x22),
+            metric: aggregate::Metric::LidoApr.into(),
+            subject: B256::repeat_byte(0x33),
+            samples: 3,
+            skipped: 0,
+            min: I256::try_from(-5).unwrap(),
+            max: I256::try_from(7).unwrap(),
+            mean: I256::ONE,
+            timeWeightedMean: I256::ZERO,
+        });
+    }
+
+    #[test]
+    fn journal_of_another_guest_is_rejected() {
+        let encoded = erc20_journal().encode_with_header(1, B256::ZERO);
+        assert!(matches!(
+            lido::Journal::decode_with_header(&encoded),
+            Err(JournalError::WrongMetricType {
+                expected: MetricType::LidoApr,
+                found: MetricType::BalanceOf,
+            })
+        ));
+    }
+
+    #[test]
+    fn invalid_headers_are_rejected() {
+        let encoded = erc20_journal().encode_with_header(1, B256::ZERO);
+        assert!(matches!(
+            decode_header(&encoded[..HEADER_SIZE - 1]),
+            Err(JournalError::Abi(_))
+        ));
+
+        // The metric type and schema version are the last bytes of the first two words.
+        let mut unknown = encoded.clone();
+        unknown[31] = 0xff;
+        assert!(matches!(
+            decode_header(&unknown),
+            Err(JournalError::UnknownMetricType(0xff))
+        ));
+
+        let mut future = encoded.clone();
+        future[63] = 2;
+        assert!(matches!(
+            erc20::Journal::decode_with_header(&future),
+            Err(JournalError::UnsupportedSchemaVersion {
+                metric_type: MetricType::BalanceOf,
+                version: 2,
+            })
+        ));
+
+        assert!(matches!(
+            erc20::Journal::decode_with_header(&encoded[..encoded.len() - 1]),
+            Err(JournalError::Abi(_))
+        ));
+    }
+}
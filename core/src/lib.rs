@@ -0,0 +1,25 @@
+//! Contract interfaces, journals and math shared by the guest programs and their publishers.
+//!
+//! Everything in here must stay deterministic and free of I/O, so that the host can mirror
+//! exactly what the guest computes.
+
+pub mod aave;
+pub mod aggregate;
+pub mod comparison;
+pub mod compound;
+pub mod curve;
+pub mod erc20;
+pub mod erc4626;
+pub mod journal;
+pub mod lido;
+pub mod math;
+pub mod morpho;
+pub mod pricing;
+pub mod realized;
+pub mod score;
+pub mod status;
+pub mod tvl;
+pub mod uniswap;
+
+/// Number of seconds used to annualize per-second rates.
+pub const SECONDS_PER_YEAR: u64 = 60 * 60 * 24 * 365;
@@ -0,0 +1,38 @@
+use alloy_primitives::{address, Address, U256};
+use alloy_sol_types::sol;
+use risc0_steel::Commitment;
+
+use crate::math::{self, Rounding, RAY};
+
+sol! {
+    interface IStETH {
+        function getTotalPooledEther() external view returns (uint256);
+        function getTotalShares() external view returns (uint256);
+    }
+}
+
+sol! {
+    /// Data committed to by the `lido_apr` guest.
+    ///
+    /// `apr` is the simple annualized growth of the share rate between the two blocks and `apy`
+    /// the growth compounded over a year of such windows, both in WAD. A share rate that shrank,
+    /// as after slashing, yields negative rates.
+    struct Journal {
+        Commitment commitment;
+        Commitment pastCommitment;
+        uint8 status;
+        address stETH;
+        uint256 shareRate;
+        uint256 pastShareRate;
+        int256 apr;
+        int256 apy;
+    }
+}
+
+/// Lido stETH on Ethereum mainnet.
+pub const STETH: Address = address!("ae7ab96520DE3A18E5e111B5EaAb095312D7fE84");
+
+/// Amount of pooled ether backing one stETH share, in RAY.
+pub fn share_rate(total_pooled_ether: U256, total_shares: U256) -> math::Result<U256> {
+    math::mul_div(total_pooled_ether, RAY, total_shares, Rounding::Down)
+}
@@ -0,0 +1,634 @@
+//! Deterministic WAD/RAY fixed-point arithmetic on [`U256`].
+//!
+//! Every operation is overflow-checked and reports failures as a [`MathError`] instead of
+//! panicking or wrapping, so a guest and its host mirror fail in exactly the same way.
+
+use std::fmt;
+
+use alloy_primitives::{uint, Sign, I256, U256, U512};
+
+use crate::SECONDS_PER_YEAR;
+
+/// 1e18, the precision of WAD values.
+pub const WAD: U256 = uint!(1_000_000_000_000_000_000_U256);
+/// 1e27, the precision of RAY values.
+pub const RAY: U256 = uint!(1_000_000_000_000_000_000_000_000_000_U256);
+
+const WAD_RAY_RATIO: U256 = uint!(1_000_000_000_U256);
+const HALF_WAD_RAY_RATIO: U256 = uint!(500_000_000_U256);
+/// ln(2) in RAY, rounded down.
+const LN_2: U256 = uint!(693_147_180_559_945_309_417_232_121_U256);
+/// Largest exponent, in RAY, that [`realized_apy`] compounds a window growth to: a year of growth
+/// is capped at e^64, about 6.2e27 times the index.
+const MAX_APY_EXPONENT: U256 = uint!(64_000_000_000_000_000_000_000_000_000_U256);
+
+/// Error returned by a checked fixed-point operation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MathError {
+    /// The result does not fit in 256 bits.
+    Overflow,
+    /// The result would be negative.
+    Underflow,
+    /// The divisor is zero.
+    DivisionByZero,
+}
+
+impl fmt::Display for MathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Overflow => f.write_str("arithmetic overflow"),
+            Self::Underflow => f.write_str("arithmetic underflow"),
+            Self::DivisionByZero => f.write_str("division by zero"),
+        }
+    }
+}
+
+impl std::error::Error for MathError {}
+
+pub type Result<T> = std::result::Result<T, MathError>;
+
+/// Rounding mode of a division.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rounding {
+    /// Towards zero.
+    Down,
+    /// Away from zero.
+    Up,
+    /// To the nearest value, with ties away from zero.
+    HalfUp,
+}
+
+/// Checked `a + b`.
+pub fn add(a: U256, b: U256) -> Result<U256> {
+    a.checked_add(b).ok_or(MathError::Overflow)
+}
+
+/// Checked `a - b`.
+pub fn sub(a: U256, b: U256) -> Result<U256> {
+    a.checked_sub(b).ok_or(MathError::Underflow)
+}
+
+/// Checked `a * b`.
+pub fn mul(a: U256, b: U256) -> Result<U256> {
+    a.checked_mul(b).ok_or(MathError::Overflow)
+}
+
+/// Checked `a / b`, rounding down.
+pub fn div(a: U256, b: U256) -> Result<U256> {
+    a.checked_div(b).ok_or(MathError::DivisionByZero)
+}
+
+/// Checked sum of `values`.
+pub fn sum(values: impl IntoIterator<Item = U256>) -> Result<U256> {
+    values.into_iter().try_fold(U256::ZERO, add)
+}
+
+/// `10^decimals`, the base unit of a token with `decimals` decimals.
+pub fn pow10(decimals: u8) -> Result<U256> {
+    U256::from(10)
+        .checked_pow(U256::from(decimals))
+        .ok_or(MathError::Overflow)
+}
+
+/// Computes `a * b / denominator` with a 512-bit intermediate product, so that only a result
+/// that does not fit in 256 bits overflows.
+pub fn mul_div(a: U256, b: U256, denominator: U256, rounding: Rounding) -> Result<U256> {
+    if denominator.is_zero() {
+        return Err(MathError::DivisionByZero);
+    }
+    let denominator = U512::from(denominator);
+    let (quotient, remainder) = (U512::from(a) * U512::from(b)).div_rem(denominator);
+    let round_up = match rounding {
+        Rounding::Down => false,
+        Rounding::Up => !remainder.is_zero(),
+        Rounding::HalfUp => remainder * U512::from(2) >= denominator,
+    };
+    let quotient = if round_up {
+        quotient + U512::from(1)
+    } else {
+        quotient
+    };
+
+    let limbs = quotient.as_limbs();
+    if limbs[4..].iter().any(|&limb| limb != 0) {
+        return Err(MathError::Overflow);
+    }
+    Ok(U256::from_limbs([limbs[0], limbs[1], limbs[2], limbs[3]]))
+}
+
+/// Multiplies two WAD values, rounding half up.
+pub fn wad_mul(a: U256, b: U256) -> Result<U256> {
+    mul_div(a, b, WAD, Rounding::HalfUp)
+}
+
+/// Divides two WAD values, rounding half up.
+pub fn wad_div(a: U256, b: U256) -> Result<U256> {
+    mul_div(a, WAD, b, Rounding::HalfUp)
+}
+
+/// Multiplies two RAY values, rounding half up.
+pub fn ray_mul(a: U256, b: U256) -> Result<U256> {
+    mul_div(a, b, RAY, Rounding::HalfUp)
+}
+
+/// Divides two RAY values, rounding half up.
+pub fn ray_div(a: U256, b: U256) -> Result<U256> {
+    mul_div(a, RAY, b, Rounding::HalfUp)
+}
+
+/// Raises a RAY value to an integer power using exponentiation by squaring.
+///
+/// Each of the at most `2 * log2(exp)` multiplications rounds by half a RAY unit, so for the
+/// exponents used here (at most one year in seconds) the relative error stays below 1e-25.
+pub fn ray_pow(mut base: U256, mut exp: u64) -> Result<U256> {
+    let mut result = RAY;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = ray_mul(result, base)?;
+        }
+        exp >>= 1;
+        // Skip the last squaring, which is never used and could overflow on its own.
+        if exp > 0 {
+            base = ray_mul(base, base)?;
+        }
+    }
+    Ok(result)
+}
+
+/// Converts a RAY value to WAD, rounding half up.
+pub fn ray_to_wad(a: U256) -> U256 {
+    let (quotient, remainder) = a.div_rem(WAD_RAY_RATIO);
+    if remainder >= HALF_WAD_RAY_RATIO {
+        quotient + U256::from(1)
+    } else {
+        quotient
+    }
+}
+
+/// Converts a WAD value to RAY.
+pub fn wad_to_ray(a: U256) -> Result<U256> {
+    mul(a, WAD_RAY_RATIO)
+}
+
+/// Converts a per-second rate in WAD into the APY, in WAD, obtained when compounding every
+/// second, like Comet and Aave accrue interest.
+pub fn wad_rate_to_apy(rate_per_second: U256) -> Result<U256> {
+    let growth = ray_pow(add(RAY, wad_to_ray(rate_per_second)?)?, SECONDS_PER_YEAR)?;
+    Ok(ray_to_wad(sub(growth, RAY)?))
+}
+
+/// Converts an annual rate in RAY into the APY obtained when compounding every second.
+pub fn ray_apr_to_apy(apr: U256) -> Result<U256> {
+    let rate_per_second = apr / U256::from(SECONDS_PER_YEAR);
+    sub(ray_pow(add(RAY, rate_per_second)?, SECONDS_PER_YEAR)?, RAY)
+}
+
+/// Converts an annual rate in WAD into the APY obtained when compounding every second.
+pub fn wad_apr_to_apy(apr: U256) -> Result<U256> {
+    Ok(ray_to_wad(ray_apr_to_apy(wad_to_ray(apr)?)?))
+}
+
+/// Signed change from `past_value` to `value`.
+pub fn signed_change(past_value: U256, value: U256) -> Result<I256> {
+    let value = I256::try_from(value).map_err(|_| MathError::Overflow)?;
+    let past_value = I256::try_from(past_value).map_err(|_| MathError::Overflow)?;
+    // Both values are non-negative, so the difference cannot overflow.
+    Ok(value - past_value)
+}
+
+/// Checked `a + b` of signed values.
+pub fn signed_add(a: I256, b: I256) -> Result<I256> {
+    a.checked_add(b).ok_or(MathError::Overflow)
+}
+
+/// Checked `a * b` of signed values.
+pub fn signed_mul(a: I256, b: I256) -> Result<I256> {
+    a.checked_mul(b).ok_or(MathError::Overflow)
+}
+
+/// Checked `a / b` of signed values, rounding towards zero.
+pub fn signed_div(a: I256, b: I256) -> Result<I256> {
+    if b.is_zero() {
+        return Err(MathError::DivisionByZero);
+    }
+    a.checked_div(b).ok_or(MathError::Overflow)
+}
+
+/// Checked sum of signed `values`.
+pub fn signed_sum(values: impl IntoIterator<Item = I256>) -> Result<I256> {
+    values.into_iter().try_fold(I256::ZERO, signed_add)
+}
+
+/// Computes `a * b / denominator` like [`mul_div`], with `rounding` applied to the magnitude of
+/// the result.
+pub fn signed_mul_div(a: I256, b: U256, denominator: U256, rounding: Rounding) -> Result<I256> {
+    let (sign, abs) = a.into_sign_and_abs();
+    with_sign(sign, mul_div(abs, b, denominator, rounding)?)
+}
+
+fn with_sign(sign: Sign, abs: U256) -> Result<I256> {
+    I256::checked_from_sign_and_abs(sign, abs).ok_or(MathError::Overflow)
+}
+
+/// Natural logarithm of a RAY value, in RAY.
+///
+/// The value is reduced to `m * 2^k` with `m` in `[1, 2)`, and `ln(m)` is summed from the series
+/// `2 * atanh((m - 1) / (m + 1))`, whose terms shrink at least ninefold each. The result is
+/// within a few RAY units of the exact logarithm. The logarithm of zero overflows.
+pub fn ray_ln(a: U256) -> Result<I256> {
+    if a.is_zero() {
+        return Err(MathError::Overflow);
+    }
+    // Normalize into [RAY, 2 * RAY), counting the halvings.
+    let ray_bits = RAY.bit_len();
+    let (mut m, mut k) = if a >= RAY {
+        let shift = a.bit_len() - ray_bits;
+        (a >> shift, shift as i64)
+    } else {
+        let shift = ray_bits - a.bit_len();
+        (a << shift, -(shift as i64))
+    };
+    if m >= RAY << 1 {
+        m >>= 1;
+        k += 1;
+    } else if m < RAY {
+        m <<= 1;
+        k -= 1;
+    }
+
+    let z = mul_div(m - RAY, RAY, m + RAY, Rounding::Down)?;
+    let z_squared = mul_div(z, z, RAY, Rounding::Down)?;
+    let mut series = U256::ZERO;
+    let mut term = z;
+    let mut n = 1u64;
+    while !term.is_zero() {
+        series += term / U256::from(n);
+        term = mul_div(term, z_squared, RAY, Rounding::Down)?;
+        n += 2;
+    }
+
+    let ln_m = I256::from_raw(series << 1);
+    let sign = if k < 0 {
+        Sign::Negative
+    } else {
+        Sign::Positive
+    };
+    let ln_2k = with_sign(sign, LN_2 * U256::from(k.unsigned_abs()))?;
+    signed_add(ln_2k, ln_m)
+}
+
+/// `e` raised to a RAY value, in RAY.
+///
+/// The exponent is reduced to `r + k * ln(2)` with `|r| <= ln(2) / 2`, and `e^r` is summed from
+/// its Taylor series before being scaled by `2^k`. Results below one RAY unit round to zero.
+pub fn ray_exp(a: I256) -> Result<U256> {
+    let (sign, abs) = a.into_sign_and_abs();
+    let k = mul_div(abs, U256::from(1), LN_2, Rounding::HalfUp)?;
+    if k > U256::from(U256::BITS) {
+        return match sign {
+            Sign::Negative => Ok(U256::ZERO),
+            Sign::Positive => Err(MathError::Overflow),
+        };
+    }
+    let k = k.to::<usize>();
+    // |r| <= ln(2) / 2, so every product below fits in 256 bits.
+    let r = I256::from_raw(abs) - I256::from_raw(LN_2 * U256::from(k));
+    let r = if sign.is_negative() { -r } else { r };
+
+    let ray = I256::from_raw(RAY);
+    let mut series = ray;
+    let mut term = ray;
+    let mut n = 1u64;
+    while !term.is_zero() {
+        term = term * r / (ray * I256::from_raw(U256::from(n)));
+        series += term;
+        n += 1;
+    }
+    // e^r is at least 1/2, so the series is positive.
+    let series = series.into_raw();
+
+    match sign {
+        Sign::Positive if series.bit_len() + k > U256::BITS => Err(MathError::Overflow),
+        Sign::Positive => Ok(series << k),
+        Sign::Negative => Ok(series >> k),
+    }
+}
+
+/// Annualizes the change between two index values of the same precision, returning the simple
+/// APR in WAD that accrued over `elapsed` seconds. A shrinking index yields a negative APR.
+pub fn annualized_change(past_index: U256, index: U256, elapsed: u64) -> Result<I256> {
+    let change = signed_change(past_index, index)?;
+    let change = signed_mul_div(change, WAD, past_index, Rounding::Down)?;
+    signed_mul_div(
+        change,
+        U256::from(SECONDS_PER_YEAR),
+        U256::from(elapsed),
+        Rounding::Down,
+    )
+}
+
+/// Annualizes the growth between two index values of the same precision, returning the APY in
+/// WAD realized over `elapsed` seconds, `(index / past_index)^(year / elapsed) - 1`, rounded
+/// half up.
+///
+/// The window growth is compounded once per window in a year, so an APY measured over a year is
+/// the growth of that year, and a shrinking index yields a negative APY down to -100%. As a
+/// growth over a few blocks compounds past any representable value, the APY saturates at
+/// e^64 - 1 instead of failing, and so does not take the APR computed alongside it down.
+pub fn realized_apy(past_index: U256, index: U256, elapsed: u64) -> Result<I256> {
+    if elapsed == 0 {
+        return Err(MathError::DivisionByZero);
+    }
+    let ratio = mul_div(index, RAY, past_index, Rounding::HalfUp)?;
+    let growth = if ratio.is_zero() {
+        U256::ZERO
+    } else {
+        let exponent = signed_mul_div(
+            ray_ln(ratio)?,
+            U256::from(SECONDS_PER_YEAR),
+            U256::from(elapsed),
+            Rounding::HalfUp,
+        )?;
+        ray_exp(exponent.min(I256::from_raw(MAX_APY_EXPONENT)))?
+    };
+    let (sign, apy) = signed_change(RAY, growth)?.into_sign_and_abs();
+    with_sign(sign, ray_to_wad(apy))
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::ruint::UintTryFrom;
+    use proptest::prelude::*;
+
+    use super::*;
+
+    /// Converts a RAY or WAD value to `f64`.
+    fn to_f64(value: I256, unit: U256) -> f64 {
+        let (sign, abs) = value.into_sign_and_abs();
+        let abs = f64::from(abs) / f64::from(unit);
+        if sign.is_negative() {
+            -abs
+        } else {
+            abs
+        }
+    }
+
+    fn signed(value: U256) -> I256 {
+        I256::from_raw(value)
+    }
+
+    #[test]
+    fn ray_ln_matches_f64() {
+        for x in [
+            1e-20f64, 1e-9, 0.25, 0.5, 0.999_999, 1.0, 1.000_001, 1.5, 2.0, 10.0, 1e12,
+        ] {
+            let a = U256::from(x * 1e27);
+            let ln = to_f64(ray_ln(a).unwrap(), RAY);
+            assert!(
+                (ln - x.ln()).abs() <= 1e-12 * x.ln().abs().max(1.0),
+                "ln({x}) = {ln}"
+            );
+        }
+        assert_eq!(ray_ln(RAY), Ok(I256::ZERO));
+        assert_eq!(ray_ln(U256::ZERO), Err(MathError::Overflow));
+    }
+
+    #[test]
+    fn ray_exp_matches_f64() {
+        for x in [
+            -50.0f64, -1.0, -0.3, -1e-9, 0.0, 1e-9, 0.3, 0.693, 1.0, 10.0, 50.0,
+        ] {
+            let a = signed_mul_div(signed(RAY), U256::from(x.abs() * 1e18), WAD, Rounding::Down)
+                .map(|a| if x < 0.0 { -a } else { a })
+                .unwrap();
+            let exp = f64::from(ray_exp(a).unwrap()) / 1e27;
+            assert!(
+                (exp - x.exp()).abs() <= 1e-12 * x.exp() + 1e-24,
+                "exp({x}) = {exp}"
+            );
+        }
+        assert_eq!(ray_exp(I256::ZERO), Ok(RAY));
+        assert_eq!(
+            ray_exp(signed(RAY * U256::from(1000))),
+            Err(MathError::Overflow)
+        );
+        assert_eq!(ray_exp(-signed(RAY * U256::from(1000))), Ok(U256::ZERO));
+    }
+
+    #[test]
+    fn ln_and_exp_invert_each_other() {
+        for a in [
+            RAY / U256::from(3),
+            RAY,
+            RAY * U256::from(7) / U256::from(5),
+        ] {
+            let round_trip = ray_exp(ray_ln(a).unwrap()).unwrap();
+            let error = round_trip.max(a) - round_trip.min(a);
+            assert!(error <= U256::from(100), "{a} -> {round_trip}");
+        }
+    }
+
+    #[test]
+    fn realized_apy_compounds_the_window_growth() {
+        let year = SECONDS_PER_YEAR;
+        // Growth over a whole year is the APY.
+        let apy = realized_apy(RAY, RAY * U256::from(105) / U256::from(100), year).unwrap();
+        assert_eq!(apy, signed(WAD * U256::from(5) / U256::from(100)));
+        // A shrinking index is a negative APY, down to -100%.
+        let apy = realized_apy(RAY, RAY * U256::from(95) / U256::from(100), year).unwrap();
+        assert_eq!(apy, -signed(WAD * U256::from(5) / U256::from(100)));
+        assert_eq!(realized_apy(RAY, U256::ZERO, 60), Ok(-signed(WAD)));
+        // 1% over a quarter of a year compounds four times.
+        let apy = realized_apy(WAD, WAD * U256::from(101) / U256::from(100), year / 4).unwrap();
+        let expected = 1.01f64.powi(4) - 1.0;
+        assert!((to_f64(apy, WAD) - expected).abs() <= 1e-15, "{apy}");
+        // A daily growth of 0.01% compounds 365 times.
+        let apy = realized_apy(RAY, RAY + RAY / U256::from(10_000), year / 365).unwrap();
+        let expected = 1.0001f64.powf(365.0) - 1.0;
+        assert!((to_f64(apy, WAD) - expected).abs() <= 1e-12, "{apy}");
+
+        assert_eq!(realized_apy(RAY, RAY, 60), Ok(I256::ZERO));
+        assert_eq!(realized_apy(RAY, RAY, 0), Err(MathError::DivisionByZero));
+        assert_eq!(
+            realized_apy(U256::ZERO, RAY, 60),
+            Err(MathError::DivisionByZero)
+        );
+
+        // Compounding a one-second doubling saturates.
+        let saturated = ray_to_wad(ray_exp(signed(MAX_APY_EXPONENT)).unwrap() - RAY);
+        assert_eq!(
+            realized_apy(RAY, RAY * U256::from(2), 1),
+            Ok(signed(saturated))
+        );
+        assert_eq!(
+            realized_apy(RAY, RAY + RAY / U256::from(1_000), 12),
+            Ok(signed(saturated))
+        );
+        assert!((to_f64(signed(saturated), WAD) / 64f64.exp_m1() - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn annualized_change_is_signed() {
+        let year = SECONDS_PER_YEAR;
+        let quarter = |percent: u64| WAD * U256::from(percent) / U256::from(100);
+        assert_eq!(
+            annualized_change(WAD, WAD + quarter(1), year / 4),
+            Ok(signed(quarter(4)))
+        );
+        assert_eq!(
+            annualized_change(WAD, WAD - quarter(1), year / 4),
+            Ok(-signed(quarter(4)))
+        );
+        assert_eq!(
+            annualized_change(WAD, WAD, 0),
+            Err(MathError::DivisionByZero)
+        );
+    }
+
+    /// Annual rates from zero over tiny and typical rates to more than 100%, in WAD.
+    fn apr_grid() -> Vec<U256> {
+        [0.0, 1e-15, 1e-9, 1e-4, 0.01, 0.035, 0.1, 0.5, 1.0, 2.5]
+            .into_iter()
+            .map(|apr: f64| U256::from(apr * 1e18))
+            .collect()
+    }
+
+    /// Asserts that `value` in WAD is within one WAD unit plus a relative 1e-12 of `reference`.
+    fn assert_close(value: U256, reference: f64, context: &str) {
+        let value = f64::from(value) / 1e18;
+        let bound = 1e-18 + 1e-12 * reference.abs();
+        assert!(
+            (value - reference).abs() <= bound,
+            "{context}: {value} differs from {reference} by more than {bound}"
+        );
+    }
+
+    #[test]
+    fn ray_pow_matches_f64() {
+        for apr in apr_grid() {
+            let rate = wad_to_ray(apr).unwrap() / U256::from(SECONDS_PER_YEAR);
+            let r = f64::from(rate) / 1e27;
+            for exp in [0, 1, 2, 12, 3_600, 86_400, SECONDS_PER_YEAR] {
+                let growth = ray_pow(RAY + rate, exp).unwrap();
+                let reference = (exp as f64 * r.ln_1p()).exp();
+                assert_close(ray_to_wad(growth), reference, &format!("(1 + {r})^{exp}"));
+            }
+        }
+        assert_eq!(ray_pow(U256::ZERO, 0), Ok(RAY));
+        assert_eq!(
+            ray_pow(RAY * U256::from(2), 1_000),
+            Err(MathError::Overflow)
+        );
+    }
+
+    #[test]
+    fn wad_rate_to_apy_matches_f64() {
+        for apr in apr_grid() {
+            let rate = apr / U256::from(SECONDS_PER_YEAR);
+            let r = f64::from(rate) / 1e18;
+            let reference = (SECONDS_PER_YEAR as f64 * r.ln_1p()).exp_m1();
+            assert_close(
+                wad_rate_to_apy(rate).unwrap(),
+                reference,
+                &format!("rate {r}"),
+            );
+        }
+    }
+
+    #[test]
+    fn wad_apr_to_apy_matches_f64() {
+        let year = SECONDS_PER_YEAR as f64;
+        for apr in apr_grid() {
+            let a = f64::from(apr) / 1e18;
+            let reference = (year * (a / year).ln_1p()).exp_m1();
+            assert_close(wad_apr_to_apy(apr).unwrap(), reference, &format!("apr {a}"));
+        }
+    }
+
+    /// Values of every magnitude, as overflows are only reached by the largest ones.
+    fn any_u256() -> impl Strategy<Value = U256> {
+        prop_oneof![
+            any::<u64>().prop_map(U256::from),
+            any::<u128>().prop_map(U256::from),
+            any::<[u64; 4]>().prop_map(U256::from_limbs),
+            Just(U256::ZERO),
+            Just(U256::MAX),
+        ]
+    }
+
+    fn any_rounding() -> impl Strategy<Value = Rounding> {
+        prop_oneof![
+            Just(Rounding::Down),
+            Just(Rounding::Up),
+            Just(Rounding::HalfUp)
+        ]
+    }
+
+    /// Narrows a 512-bit reference result, or overflows.
+    fn narrow(value: U512) -> Result<U256> {
+        U256::uint_try_from(value).map_err(|_| MathError::Overflow)
+    }
+
+    proptest! {
+        #[test]
+        fn mul_div_matches_u512(
+            a in any_u256(),
+            b in any_u256(),
+            denominator in any_u256(),
+            rounding in any_rounding(),
+        ) {
+            let expected = if denominator.is_zero() {
+                Err(MathError::DivisionByZero)
+            } else {
+                let product = U512::from(a) * U512::from(b);
+                let denominator = U512::from(denominator);
+                let one = U512::from(1);
+                let two = U512::from(2);
+                narrow(match rounding {
+                    Rounding::Down => product / denominator,
+                    Rounding::Up => (product + denominator - one) / denominator,
+                    Rounding::HalfUp => (product + denominator / two) / denominator,
+                })
+            };
+            prop_assert_eq!(mul_div(a, b, denominator, rounding), expected);
+        }
+
+        #[test]
+        fn checked_ops_match_u512(a in any_u256(), b in any_u256()) {
+            prop_assert_eq!(add(a, b), narrow(U512::from(a) + U512::from(b)));
+            prop_assert_eq!(mul(a, b), narrow(U512::from(a) * U512::from(b)));
+            let difference = if a >= b { Ok(a - b) } else { Err(MathError::Underflow) };
+            prop_assert_eq!(sub(a, b), difference);
+            let quotient = if b.is_zero() { Err(MathError::DivisionByZero) } else { Ok(a / b) };
+            prop_assert_eq!(div(a, b), quotient);
+        }
+
+        #[test]
+        fn realized_apys_of_a_few_blocks_saturate(
+            past_index in 1_000_000_000_000_000_000u128..u128::MAX / 1_000,
+            growth_bps in -10_000i64..=100_000,
+            blocks in 1u64..=10,
+        ) {
+            let past_index = U256::from(past_index);
+            let index = past_index * U256::from(10_000 + growth_bps) / U256::from(10_000);
+            let apy = realized_apy(past_index, index, 12 * blocks).unwrap();
+            let saturated = ray_to_wad(ray_exp(signed(MAX_APY_EXPONENT)).unwrap() - RAY);
+            prop_assert!(apy >= -signed(WAD) && apy <= signed(saturated), "{}", apy);
+            prop_assert!(index >= past_index || !apy.is_positive(), "{}", apy);
+            prop_assert!(index <= past_index || !apy.is_negative(), "{}", apy);
+            // The APR of the same window is computed independently and does not overflow.
+            prop_assert!(annualized_change(past_index, index, 12 * blocks).is_ok());
+        }
+
+        #[test]
+        fn wad_ray_conversions_round_trip(a in any_u256()) {
+            let ratio = U512::from(WAD_RAY_RATIO);
+            prop_assert_eq!(wad_to_ray(a), narrow(U512::from(a) * ratio));
+            if let Ok(ray) = wad_to_ray(a) {
+                prop_assert_eq!(ray_to_wad(ray), a);
+            }
+            let half_up = (U512::from(a) + U512::from(HALF_WAD_RAY_RATIO)) / ratio;
+            prop_assert_eq!(Ok(ray_to_wad(a)), narrow(half_up));
+        }
+    }
+}
@@ -0,0 +1,205 @@
+use alloy_primitives::{address, Address, U256};
+use alloy_sol_types::sol;
+use risc0_steel::Commitment;
+
+use crate::{
+    math::{self, Rounding, RAY, WAD},
+    SECONDS_PER_YEAR,
+};
+
+sol! {
+    struct MarketParams {
+        address loanToken;
+        address collateralToken;
+        address oracle;
+        address irm;
+        uint256 lltv;
+    }
+
+    struct Market {
+        uint128 totalSupplyAssets;
+        uint128 totalSupplyShares;
+        uint128 totalBorrowAssets;
+        uint128 totalBorrowShares;
+        uint128 lastUpdate;
+        uint128 fee;
+    }
+
+    /// Morpho Blue singleton.
+    interface IMorpho {
+        function market(bytes32 id) external view returns (Market memory);
+        function idToMarketParams(bytes32 id) external view returns (MarketParams memory);
+        function position(bytes32 id, address user) external view returns (
+            uint256 supplyShares,
+            uint128 borrowShares,
+            uint128 collateral
+        );
+    }
+
+    interface IIrm {
+        function borrowRateView(MarketParams marketParams, Market market) external view returns (uint256);
+    }
+
+    /// MetaMorpho vault.
+    interface IMetaMorpho {
+        function MORPHO() external view returns (address);
+        function fee() external view returns (uint96);
+        function totalAssets() external view returns (uint256);
+        function supplyQueue(uint256 index) external view returns (bytes32);
+        function supplyQueueLength() external view returns (uint256);
+        function withdrawQueue(uint256 index) external view returns (bytes32);
+        function withdrawQueueLength() external view returns (uint256);
+    }
+}
+
+sol! {
+    /// Position of a MetaMorpho vault in one of its markets.
+    struct MarketAllocation {
+        bytes32 id;
+        bool inSupplyQueue;
+        uint256 supplyAssets;
+        uint256 supplyApr;
+        uint256 supplyApy;
+    }
+
+    /// Data committed to by the `metamorpho_apr` guest. All rates are annual and in WAD.
+    struct Journal {
+        Commitment commitment;
+        uint8 status;
+        address vault;
+        uint256 fee;
+        uint256 totalAssets;
+        MarketAllocation[] markets;
+        uint256 grossApr;
+        uint256 netApr;
+        uint256 netApy;
+    }
+}
+
+/// Morpho Blue on Ethereum mainnet.
+pub const MORPHO_BLUE: Address = address!("BBBBBbbBBb9cC5e90e3b3Af64bdAF62C37EEFFCb");
+
+const VIRTUAL_SHARES: U256 = U256::from_limbs([1_000_000, 0, 0, 0]);
+const VIRTUAL_ASSETS: U256 = U256::from_limbs([1, 0, 0, 0]);
+
+/// Converts supply shares into assets, rounding down like `SharesMathLib.toAssetsDown`.
+pub fn to_assets_down(shares: U256, total_assets: u128, total_shares: u128) -> math::Result<U256> {
+    math::mul_div(
+        shares,
+        U256::from(total_assets) + VIRTUAL_ASSETS,
+        U256::from(total_shares) + VIRTUAL_SHARES,
+        Rounding::Down,
+    )
+}
+
+/// Assets per share in RAY, including the virtual shares and assets, used as an interest index.
+pub fn share_price(total_assets: u128, total_shares: u128) -> math::Result<U256> {
+    math::mul_div(
+        U256::from(total_assets) + VIRTUAL_ASSETS,
+        RAY,
+        U256::from(total_shares) + VIRTUAL_SHARES,
+        Rounding::Down,
+    )
+}
+
+/// Annual supply APR of a market, in WAD, given its per-second borrow rate.
+///
+/// Suppliers earn the borrow interest on the utilized share of the market, minus the market fee.
+pub fn supply_apr(borrow_rate: U256, market: &Market) -> math::Result<U256> {
+    if market.totalSupplyAssets == 0 {
+        return Ok(U256::ZERO);
+    }
+    let utilization = math::mul_div(
+        U256::from(market.totalBorrowAssets),
+        WAD,
+        U256::from(market.totalSupplyAssets),
+        Rounding::Down,
+    )?;
+    let borrow_rate = math::mul_div(borrow_rate, utilization, WAD, Rounding::Down)?;
+    let supply_rate = net_of_fee(borrow_rate, U256::from(market.fee))?;
+    math::mul(supply_rate, U256::from(SECONDS_PER_YEAR))
+}
+
+/// Position-weighted APR of a vault across its markets, in WAD, before the vault fee.
+pub fn weighted_apr(markets: &[MarketAllocation]) -> math::Result<U256> {
+    let total_assets = math::sum(markets.iter().map(|market| market.supplyAssets))?;
+    if total_assets.is_zero() {
+        return Ok(U256::ZERO);
+    }
+    let weighted_sum = markets.iter().try_fold(U256::ZERO, |total, market| {
+        math::add(total, math::mul(market.supplyAssets, market.supplyApr)?)
+    })?;
+    math::div(weighted_sum, total_assets)
+}
+
+/// Deducts a fee in WAD, such as the vault performance fee, from a gross APR.
+pub fn net_of_fee(apr: U256, fee: U256) -> math::Result<U256> {
+    math::mul_div(apr, math::sub(WAD, fee)?, WAD, Rounding::Down)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn market(supply: u128, borrow: u128, fee: u128) -> Market {
+        Market {
+            totalSupplyAssets: supply,
+            totalSupplyShares: supply * 1_000_000,
+            totalBorrowAssets: borrow,
+            totalBorrowShares: borrow * 1_000_000,
+            lastUpdate: 0,
+            fee,
+        }
+    }
+
+    fn allocation(supply_assets: u64, supply_apr: U256) -> MarketAllocation {
+        MarketAllocation {
+            id: Default::default(),
+            inSupplyQueue: true,
+            supplyAssets: U256::from(supply_assets),
+            supplyApr: supply_apr,
+            supplyApy: U256::ZERO,
+        }
+    }
+
+    fn percent(value: u64) -> U256 {
+        WAD * U256::from(value) / U256::from(100)
+    }
+
+    #[test]
+    fn suppliers_earn_the_utilized_borrow_rate_net_of_the_fee() {
+        // A 10% APR borrowed on half of the market, with a 10% fee, pays suppliers 4.5%.
+        let borrow_rate = percent(10) / U256::from(SECONDS_PER_YEAR);
+        let apr = supply_apr(borrow_rate, &market(1_000, 500, 10u128.pow(17))).unwrap();
+        let expected = percent(45) / U256::from(10);
+        assert!(expected - apr < U256::from(SECONDS_PER_YEAR), "{apr}");
+        // An empty market earns nothing.
+        assert_eq!(supply_apr(borrow_rate, &market(0, 0, 0)), Ok(U256::ZERO));
+    }
+
+    #[test]
+    fn idle_markets_dilute_the_vault_apr() {
+        // An idle market has no interest rate model, so its borrow rate and APR are zero.
+        let idle = supply_apr(U256::ZERO, &market(1_000, 0, 0)).unwrap();
+        assert_eq!(idle, U256::ZERO);
+        let markets = [allocation(300, percent(8)), allocation(100, idle)];
+        assert_eq!(weighted_apr(&markets), Ok(percent(6)));
+        assert_eq!(net_of_fee(percent(6), percent(50)), Ok(percent(3)));
+        // A vault with nothing allocated earns nothing.
+        assert_eq!(weighted_apr(&[allocation(0, percent(8))]), Ok(U256::ZERO));
+    }
+
+    #[test]
+    fn shares_convert_with_virtual_assets() {
+        // An empty market prices a share at the virtual assets over the virtual shares.
+        assert_eq!(share_price(0, 0), Ok(RAY / U256::from(1_000_000)));
+        assert_eq!(
+            to_assets_down(U256::from(2_000_000), 0, 0),
+            Ok(U256::from(2))
+        );
+        assert_eq!(
+            to_assets_down(U256::from(1_000_000), 999, 999_000_000),
+            Ok(U256::from(1))
+        );
+    }
+}
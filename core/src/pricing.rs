@@ -0,0 +1,107 @@
+use std::str::FromStr;
+
+use alloy_primitives::{address, aliases::U24, Address, Bytes};
+use alloy_sol_types::sol;
+use serde::{Deserialize, Serialize};
+
+sol! {
+    interface QuoterV2 {
+        function quoteExactInput(bytes memory path, uint256 amountIn) public returns(
+            uint256 amountOut,
+            uint160[] memory sqrtPriceX96AfterList,
+            uint32[] memory initializedTicksCrossedList,
+            uint256 gasEstimate
+        );
+    }
+}
+
+/// Uniswap V3 QuoterV2 on Ethereum mainnet.
+pub const QUOTER_V2: Address = address!("61fFE014bA17989E743c5F6cB21bF9697530B21e");
+
+/// A Uniswap V3 swap route, used to price one token in terms of another.
+///
+/// `fees[i]` is the fee tier of the pool between `tokens[i]` and `tokens[i + 1]`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SwapPath {
+    pub tokens: Vec<Address>,
+    pub fees: Vec<U24>,
+}
+
+impl SwapPath {
+    /// Token being priced.
+    pub fn token_in(&self) -> Address {
+        self.tokens[0]
+    }
+
+    /// Token the price is quoted in.
+    pub fn token_out(&self) -> Address {
+        self.tokens[self.tokens.len() - 1]
+    }
+
+    /// Encodes the route in the packed format expected by `quoteExactInput`.
+    pub fn encode(&self) -> Bytes {
+        let mut path = Vec::with_capacity(self.tokens.len() * 23);
+        for (token, fee) in self.tokens.iter().zip(&self.fees) {
+            path.extend_from_slice(token.as_slice());
+            path.extend_from_slice(&fee.to_be_bytes::<3>());
+        }
+        path.extend_from_slice(self.token_out().as_slice());
+        Bytes::from(path)
+    }
+}
+
+/// Finds the route pricing `token` in `numeraire` among `paths`.
+pub fn find_route(paths: &[SwapPath], token: Address, numeraire: Address) -> Option<&SwapPath> {
+    paths
+        .iter()
+        .find(|path| path.token_in() == token && path.token_out() == numeraire)
+}
+
+/// Parses a route written as `token:fee:token[:fee:token...]`.
+impl FromStr for SwapPath {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut tokens = Vec::new();
+        let mut fees = Vec::new();
+        for (i, part) in s.split(':').enumerate() {
+            if i % 2 == 0 {
+                tokens.push(Address::from_str(part).map_err(|e| format!("invalid token: {e}"))?);
+            } else {
+                fees.push(U24::from_str(part).map_err(|e| format!("invalid fee {part}: {e}"))?);
+            }
+        }
+        if tokens.len() < 2 || tokens.len() != fees.len() + 1 {
+            return Err(format!("invalid swap path: {s}"));
+        }
+        Ok(Self { tokens, fees })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::hex;
+
+    use super::*;
+
+    const USDC: &str = "A0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48";
+    const WETH: &str = "C02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2";
+
+    #[test]
+    fn paths_encode_three_byte_fees() {
+        let path = SwapPath::from_str(&format!("{WETH}:500:{USDC}")).unwrap();
+        assert_eq!(
+            path.encode(),
+            Bytes::from(hex::decode(format!("{WETH}0001f4{USDC}")).unwrap())
+        );
+        let widest = SwapPath::from_str(&format!("{WETH}:16777215:{USDC}")).unwrap();
+        assert_eq!(widest.fees, vec![U24::MAX]);
+    }
+
+    #[test]
+    fn fees_wider_than_three_bytes_are_rejected() {
+        assert!(SwapPath::from_str(&format!("{WETH}:16777216:{USDC}")).is_err());
+        assert!(SwapPath::from_str(&format!("{WETH}:-1:{USDC}")).is_err());
+        assert!(SwapPath::from_str(&format!("{WETH}:500")).is_err());
+    }
+}
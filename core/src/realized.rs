@@ -0,0 +1,57 @@
+use alloy_primitives::{Address, B256};
+use alloy_sol_types::sol;
+use risc0_steel::Commitment;
+use serde::{Deserialize, Serialize};
+
+sol! {
+    /// Data committed to by the `realized_apr` guest.
+    ///
+    /// Indices are taken as of the last accrual of the market at each block, and `elapsed` is
+    /// the time between those accruals. Rates are annual and in WAD: APRs are the simple
+    /// annualized growth of an index and APYs its growth compounded over a year of such windows.
+    /// A shrinking index, as after bad debt, yields negative rates, and a market that did not
+    /// accrue between the blocks has no realized rates and commits a division by zero status.
+    struct Journal {
+        Commitment commitment;
+        Commitment pastCommitment;
+        uint8 status;
+        address market;
+        bytes32 marketId;
+        uint256 supplyIndex;
+        uint256 pastSupplyIndex;
+        uint256 borrowIndex;
+        uint256 pastBorrowIndex;
+        uint64 elapsed;
+        int256 realizedSupplyApr;
+        int256 realizedSupplyApy;
+        int256 realizedBorrowApr;
+        int256 realizedBorrowApy;
+    }
+}
+
+/// Lending market whose realized rates are measured.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LendingMarket {
+    /// A Compound III market.
+    Comet(Address),
+    /// A Morpho Blue market.
+    Morpho { morpho: Address, id: B256 },
+}
+
+impl LendingMarket {
+    /// Address of the contract holding the market state.
+    pub fn address(&self) -> Address {
+        match self {
+            Self::Comet(comet) => *comet,
+            Self::Morpho { morpho, .. } => *morpho,
+        }
+    }
+
+    /// Identifier of the market within its contract, zero for Comet.
+    pub fn id(&self) -> B256 {
+        match self {
+            Self::Comet(_) => B256::ZERO,
+            Self::Morpho { id, .. } => *id,
+        }
+    }
+}
@@ -0,0 +1,185 @@
+use std::collections::BTreeSet;
+
+use alloy_primitives::{B256, I256, U256};
+use alloy_sol_types::sol;
+use risc0_steel::Commitment;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    aggregate::{Metric, Sample},
+    math::{self, Rounding, WAD},
+    status::Status,
+};
+
+sol! {
+    /// Weight and normalization of one metric of a scoring policy.
+    ///
+    /// Values are mapped linearly from `floor`, scoring zero, to `cap`, scoring one, and clamped
+    /// in between; `inverted` reverses the scale for metrics where lower is better, like
+    /// inflation. Journals of the metric must be proven by the guest with image ID `imageId`.
+    struct Weight {
+        uint8 metric;
+        bytes32 imageId;
+        uint32 weight;
+        int256 floor;
+        int256 cap;
+        bool inverted;
+    }
+
+    /// Scoring policy, identified by the keccak256 digest of its ABI encoding.
+    struct Policy {
+        Weight[] weights;
+    }
+
+    /// Score of one protocol, in WAD, from the metric journals listed in policy order.
+    ///
+    /// Every journal measures the `subject`, the vault, pool or token of the protocol, see
+    /// [`Sample::subject`].
+    struct ProtocolScore {
+        string name;
+        bytes32 subject;
+        uint8 status;
+        Commitment[] commitments;
+        int256[] values;
+        uint256 score;
+    }
+
+    /// Data committed to by the `score` guest.
+    struct Journal {
+        bytes32 policyDigest;
+        Policy policy;
+        uint8 status;
+        ProtocolScore[] scores;
+    }
+}
+
+/// Journals of the metrics of one protocol, in the order of the policy weights.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProtocolJournals {
+    pub name: String,
+    pub journals: Vec<Vec<u8>>,
+}
+
+/// Checks that the policy has a metric, known metrics, a positive total weight and valid ranges.
+pub fn check_policy(policy: &Policy) -> Result<(), String> {
+    if policy.weights.is_empty() {
+        return Err("policy has no weights".to_string());
+    }
+    for weight in &policy.weights {
+        Metric::try_from(weight.metric).map_err(|metric| format!("unknown metric: {metric}"))?;
+        if weight.floor >= weight.cap {
+            return Err(format!(
+                "floor of metric {} is not below its cap",
+                weight.metric
+            ));
+        }
+    }
+    if policy.weights.iter().all(|weight| weight.weight == 0) {
+        return Err("policy weights are all zero".to_string());
+    }
+    Ok(())
+}
+
+/// Checks that every protocol is named once.
+pub fn check_names<'a>(names: impl IntoIterator<Item = &'a str>) -> Result<(), String> {
+    let mut seen = BTreeSet::new();
+    for name in names {
+        if !seen.insert(name) {
+            return Err(format!("protocol {name} is scored twice"));
+        }
+    }
+    Ok(())
+}
+
+/// Subject shared by the samples of a protocol, which must all measure the same one.
+pub fn subject(samples: &[Sample]) -> Result<B256, String> {
+    let first = samples.first().ok_or("no samples")?;
+    match samples
+        .iter()
+        .find(|sample| sample.subject != first.subject)
+    {
+        Some(sample) => Err(format!(
+            "metrics of different subjects: {} and {}",
+            first.subject, sample.subject
+        )),
+        None => Ok(first.subject),
+    }
+}
+
+/// Maps a value onto `[0, WAD]` following the normalization of `weight`.
+pub fn normalize(value: I256, weight: &Weight) -> math::Result<U256> {
+    let normalized = if value <= weight.floor {
+        U256::ZERO
+    } else if value >= weight.cap {
+        WAD
+    } else {
+        // Both differences are positive and below 2^256, so their two's complement bits are
+        // their unsigned values, even where they overflow `int256`.
+        math::mul_div(
+            value.wrapping_sub(weight.floor).into_raw(),
+            WAD,
+            weight.cap.wrapping_sub(weight.floor).into_raw(),
+            Rounding::Down,
+        )?
+    };
+    Ok(if weight.inverted {
+        WAD - normalized
+    } else {
+        normalized
+    })
+}
+
+/// Weighted mean of the normalized samples of a protocol, in WAD, from samples in policy order.
+///
+/// A protocol with a sample that was not computed is not scored, and takes the status of the
+/// first such sample.
+pub fn score(policy: &Policy, samples: &[Sample]) -> Result<U256, Status> {
+    if let Some(sample) = samples.iter().find(|sample| !sample.is_ok()) {
+        return Err(Status::try_from(sample.status).expect("unknown status"));
+    }
+    let weighted = policy
+        .weights
+        .iter()
+        .zip(samples)
+        .map(|(weight, sample)| {
+            math::mul(normalize(sample.value, weight)?, U256::from(weight.weight))
+        })
+        .collect::<math::Result<Vec<U256>>>()?;
+    let total_weight = math::sum(
+        policy
+            .weights
+            .iter()
+            .map(|weight| U256::from(weight.weight)),
+    )?;
+    Ok(math::div(math::sum(weighted)?, total_weight)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(subject: u8) -> Sample {
+        Sample {
+            chain_id: 1,
+            commitment: Commitment::default(),
+            status: Status::Ok.into(),
+            subject: B256::repeat_byte(subject),
+            value: I256::ZERO,
+        }
+    }
+
+    #[test]
+    fn protocols_are_named_once() {
+        assert_eq!(check_names(["aave", "morpho"]), Ok(()));
+        assert!(check_names(["aave", "morpho", "aave"])
+            .unwrap_err()
+            .contains("aave"));
+    }
+
+    #[test]
+    fn metrics_of_a_protocol_share_its_subject() {
+        assert_eq!(subject(&[sample(1), sample(1)]), Ok(B256::repeat_byte(1)));
+        assert!(subject(&[sample(1), sample(2)]).is_err());
+        assert!(subject(&[]).is_err());
+    }
+}
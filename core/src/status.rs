@@ -0,0 +1,97 @@
+use std::fmt;
+
+use crate::math::MathError;
+
+/// Outcome of a metric computation, committed as the `status` field of every journal.
+///
+/// Degenerate market states, like a market without borrowers or a reverting contract, are
+/// committed as a status instead of making the guest panic, so that they can be proven as well.
+/// Unless the status is [`Status::Ok`], all values of the journal derived from the chain state
+/// are zero; commitments and the guest inputs echoed in the journal are always set.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum Status {
+    /// All values were computed.
+    #[default]
+    Ok = 0,
+    /// A supply the values are relative to is zero.
+    NoSupply = 1,
+    /// A borrow total the values are relative to is zero.
+    NoBorrow = 2,
+    /// A contract call reverted.
+    CallReverted = 3,
+    /// An intermediate value overflowed.
+    Overflow = 4,
+    /// An intermediate value would have been negative.
+    Underflow = 5,
+    /// A divisor was zero.
+    DivisionByZero = 6,
+    /// No sample of an aggregated series, market of a comparison or protocol of a score was
+    /// computed.
+    NoSamples = 7,
+}
+
+impl Status {
+    /// Splits the result of a computation into the status to commit and the computed values,
+    /// which are zero unless the computation succeeded.
+    pub fn split<T: Default>(result: Result<T, Status>) -> (Status, T) {
+        match result {
+            Ok(values) => (Status::Ok, values),
+            Err(status) => (status, T::default()),
+        }
+    }
+
+    /// Maps the error of a reverted contract call to [`Status::CallReverted`].
+    pub fn reverted<E>(_err: E) -> Status {
+        Status::CallReverted
+    }
+}
+
+impl From<MathError> for Status {
+    fn from(err: MathError) -> Self {
+        match err {
+            MathError::Overflow => Status::Overflow,
+            MathError::Underflow => Status::Underflow,
+            MathError::DivisionByZero => Status::DivisionByZero,
+        }
+    }
+}
+
+impl From<Status> for u8 {
+    fn from(status: Status) -> Self {
+        status as u8
+    }
+}
+
+impl TryFrom<u8> for Status {
+    type Error = u8;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        Ok(match value {
+            0 => Status::Ok,
+            1 => Status::NoSupply,
+            2 => Status::NoBorrow,
+            3 => Status::CallReverted,
+            4 => Status::Overflow,
+            5 => Status::Underflow,
+            6 => Status::DivisionByZero,
+            7 => Status::NoSamples,
+            _ => return Err(value),
+        })
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Status::Ok => f.write_str("ok"),
+            Status::NoSupply => f.write_str("no supply"),
+            Status::NoBorrow => f.write_str("no borrow"),
+            Status::CallReverted => f.write_str("call reverted"),
+            Status::Overflow => f.write_str("arithmetic overflow"),
+            Status::Underflow => f.write_str("arithmetic underflow"),
+            Status::DivisionByZero => f.write_str("division by zero"),
+            Status::NoSamples => f.write_str("no samples"),
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use alloy_primitives::{address, Address};
+use alloy_sol_types::sol;
+use risc0_steel::Commitment;
+use serde::{Deserialize, Serialize};
+
+sol! {
+    /// Balance of one token held by one protocol contract.
+    struct Holding {
+        address holder;
+        address token;
+        uint256 balance;
+        uint256 value;
+        uint256 pastBalance;
+        uint256 pastValue;
+    }
+
+    /// Data committed to by the `tvl` guest. Values are in base units of the numeraire, each
+    /// block priced at its own state. Without a past block, the past fields are zero.
+    struct Journal {
+        Commitment commitment;
+        Commitment pastCommitment;
+        uint8 status;
+        address numeraire;
+        Holding[] holdings;
+        uint256 totalValue;
+        uint256 pastTotalValue;
+        int256 totalValueChange;
+    }
+}
+
+/// USDC on Ethereum mainnet, the default USD numeraire.
+pub const USDC: Address = address!("A0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48");
+
+/// A token balance counted towards the TVL of a protocol.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HoldingSpec {
+    pub holder: Address,
+    pub token: Address,
+}
+
+/// Parses a holding written as `holder:token`.
+impl std::str::FromStr for HoldingSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (holder, token) = s
+            .split_once(':')
+            .ok_or_else(|| format!("invalid holding: {s}"))?;
+        Ok(Self {
+            holder: holder.parse().map_err(|e| format!("invalid holder: {e}"))?,
+            token: token.parse().map_err(|e| format!("invalid token: {e}"))?,
+        })
+    }
+}
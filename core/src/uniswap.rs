@@ -0,0 +1,246 @@
+use alloy_primitives::{uint, U256};
+use alloy_sol_types::sol;
+use risc0_steel::Commitment;
+
+use crate::{
+    math::{self, mul_div, MathError, Rounding, WAD},
+    SECONDS_PER_YEAR,
+};
+
+sol! {
+    interface IUniswapV3Pool {
+        function token0() external view returns (address);
+        function token1() external view returns (address);
+        function tickSpacing() external view returns (int24);
+        function liquidity() external view returns (uint128);
+        function feeGrowthGlobal0X128() external view returns (uint256);
+        function feeGrowthGlobal1X128() external view returns (uint256);
+        function slot0() external view returns (
+            uint160 sqrtPriceX96,
+            int24 tick,
+            uint16 observationIndex,
+            uint16 observationCardinality,
+            uint16 observationCardinalityNext,
+            uint8 feeProtocol,
+            bool unlocked
+        );
+    }
+}
+
+sol! {
+    /// Data committed to by the `uniswap_fee_apr` guest.
+    ///
+    /// Values are in base units of the numeraire, priced at the current block. The fee APR is the
+    /// annualized return, in WAD, of liquidity kept in the active tick range over the window.
+    struct Journal {
+        Commitment commitment;
+        Commitment pastCommitment;
+        uint8 status;
+        address pool;
+        address numeraire;
+        uint128 liquidity;
+        int24 tick;
+        int24 tickLower;
+        int24 tickUpper;
+        uint256 feeGrowthGlobal0Delta;
+        uint256 feeGrowthGlobal1Delta;
+        uint256 activeLiquidityValue;
+        uint256 feesValue;
+        uint256 feeApr;
+    }
+}
+
+/// 2^96, the precision of `sqrtPriceX96`.
+pub const Q96: U256 = uint!(0x1000000000000000000000000_U256);
+/// 2^128, the precision of the fee growth accumulators.
+pub const Q128: U256 = uint!(0x100000000000000000000000000000000_U256);
+
+/// Largest tick whose price `TickMath` can represent; the smallest is its negation.
+pub const MAX_TICK: i32 = 887272;
+
+/// Port of `TickMath.getSqrtRatioAtTick`, returning `sqrt(1.0001^tick)` as a Q64.96 value.
+///
+/// Fails with [`MathError::Overflow`] for ticks beyond [`MAX_TICK`], as the bounds of the range
+/// around a tick near the edge may be.
+pub fn sqrt_ratio_at_tick(tick: i32) -> math::Result<U256> {
+    const FACTORS: [U256; 19] = [
+        uint!(0xfff97272373d413259a46990580e213a_U256),
+        uint!(0xfff2e50f5f656932ef12357cf3c7fdcc_U256),
+        uint!(0xffe5caca7e10e4e61c3624eaa0941cd0_U256),
+        uint!(0xffcb9843d60f6159c9db58835c926644_U256),
+        uint!(0xff973b41fa98c081472e6896dfb254c0_U256),
+        uint!(0xff2ea16466c96a3843ec78b326b52861_U256),
+        uint!(0xfe5dee046a99a2a811c461f1969c3053_U256),
+        uint!(0xfcbe86c7900a88aedcffc83b479aa3a4_U256),
+        uint!(0xf987a7253ac413176f2b074cf7815e54_U256),
+        uint!(0xf3392b0822b70005940c7a398e4b70f3_U256),
+        uint!(0xe7159475a2c29b7443b29c7fa6e889d9_U256),
+        uint!(0xd097f3bdfd2022b8845ad8f792aa5825_U256),
+        uint!(0xa9f746462d870fdf8a65dc1f90e061e5_U256),
+        uint!(0x70d869a156d2a1b890bb3df62baf32f7_U256),
+        uint!(0x31be135f97d08fd981231505542fcfa6_U256),
+        uint!(0x9aa508b5b7a84e1c677de54f3e99bc9_U256),
+        uint!(0x5d6af8dedb81196699c329225ee604_U256),
+        uint!(0x2216e584f5fa1ea926041bedfe98_U256),
+        uint!(0x48a170391f7dc42444e8fa2_U256),
+    ];
+
+    let abs_tick = tick.unsigned_abs();
+    if abs_tick > MAX_TICK.unsigned_abs() {
+        return Err(MathError::Overflow);
+    }
+
+    let mut ratio = if abs_tick & 0x1 != 0 {
+        uint!(0xfffcb933bd6fad37aa2d162d1a594001_U256)
+    } else {
+        Q128
+    };
+    for (bit, factor) in FACTORS.iter().enumerate() {
+        if abs_tick & (0x2 << bit) != 0 {
+            ratio = (ratio * *factor) >> 128;
+        }
+    }
+    if tick > 0 {
+        ratio = U256::MAX / ratio;
+    }
+
+    // Round up when going from Q128.128 to Q64.96.
+    if (ratio % U256::from(1u64 << 32)).is_zero() {
+        Ok(ratio >> 32)
+    } else {
+        Ok((ratio >> 32) + U256::from(1))
+    }
+}
+
+/// Token amounts backing `liquidity` in the range `[sqrt_lower, sqrt_upper)` at `sqrt_price`,
+/// which must lie inside the range.
+pub fn amounts_for_liquidity(
+    sqrt_price: U256,
+    sqrt_lower: U256,
+    sqrt_upper: U256,
+    liquidity: U256,
+) -> math::Result<(U256, U256)> {
+    let amount0 = mul_div(
+        mul_div(liquidity, Q96, sqrt_price, Rounding::Down)?,
+        math::sub(sqrt_upper, sqrt_price)?,
+        sqrt_upper,
+        Rounding::Down,
+    )?;
+    let amount1 = mul_div(
+        liquidity,
+        math::sub(sqrt_price, sqrt_lower)?,
+        Q96,
+        Rounding::Down,
+    )?;
+    Ok((amount0, amount1))
+}
+
+/// Fees earned by `liquidity` over a window given the growth of the fee accumulators.
+pub fn fees_for_liquidity(
+    fee_growth_0_delta: U256,
+    fee_growth_1_delta: U256,
+    liquidity: U256,
+) -> math::Result<(U256, U256)> {
+    Ok((
+        mul_div(fee_growth_0_delta, liquidity, Q128, Rounding::Down)?,
+        mul_div(fee_growth_1_delta, liquidity, Q128, Rounding::Down)?,
+    ))
+}
+
+/// Annualizes the fees earned on a position over `elapsed` seconds, returning the APR in WAD.
+pub fn fee_apr(fees_value: U256, position_value: U256, elapsed: u64) -> math::Result<U256> {
+    if position_value.is_zero() {
+        return Ok(U256::ZERO);
+    }
+    let fees_per_value = mul_div(fees_value, WAD, position_value, Rounding::Down)?;
+    mul_div(
+        fees_per_value,
+        U256::from(SECONDS_PER_YEAR),
+        U256::from(elapsed),
+        Rounding::Down,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_f64(value: U256) -> f64 {
+        f64::from(value)
+    }
+
+    #[test]
+    fn sqrt_ratios_match_tick_math() {
+        assert_eq!(sqrt_ratio_at_tick(0).unwrap(), Q96);
+        assert_eq!(
+            sqrt_ratio_at_tick(-MAX_TICK).unwrap(),
+            U256::from(4295128739u64)
+        );
+        assert_eq!(
+            sqrt_ratio_at_tick(MAX_TICK).unwrap(),
+            uint!(1461446703485210103287273052203988822378723970342_U256)
+        );
+        for tick in [-500_000, -60_000, -887, -1, 1, 10, 60_000, 500_000] {
+            let reference = (f64::from(tick) / 2.0 * 1e-4f64.ln_1p()).exp() * to_f64(Q96);
+            let ratio = to_f64(sqrt_ratio_at_tick(tick).unwrap());
+            assert!((ratio / reference - 1.0).abs() < 1e-12, "{tick}");
+        }
+    }
+
+    #[test]
+    fn ticks_beyond_the_bounds_overflow() {
+        assert_eq!(sqrt_ratio_at_tick(MAX_TICK + 1), Err(MathError::Overflow));
+        assert_eq!(sqrt_ratio_at_tick(-MAX_TICK - 60), Err(MathError::Overflow));
+        assert_eq!(sqrt_ratio_at_tick(i32::MIN), Err(MathError::Overflow));
+    }
+
+    #[test]
+    fn liquidity_is_backed_by_both_tokens_inside_the_range() {
+        let liquidity = U256::from(10u128.pow(18));
+        let (lower, upper) = (-600, 600);
+        let (amount0, amount1) = amounts_for_liquidity(
+            Q96,
+            sqrt_ratio_at_tick(lower).unwrap(),
+            sqrt_ratio_at_tick(upper).unwrap(),
+            liquidity,
+        )
+        .unwrap();
+        // At a price of one, L * (1 - sqrt(p_lower)) of each token, the range being symmetric.
+        let reference = 1e18 * (1.0 - 1.0001f64.powf(f64::from(lower) / 2.0));
+        for amount in [amount0, amount1] {
+            assert!((to_f64(amount) / reference - 1.0).abs() < 1e-9);
+        }
+        // At the lower bound, the liquidity is all in token0.
+        let sqrt_lower = sqrt_ratio_at_tick(lower).unwrap();
+        let (_, amount1) = amounts_for_liquidity(
+            sqrt_lower,
+            sqrt_lower,
+            sqrt_ratio_at_tick(upper).unwrap(),
+            liquidity,
+        )
+        .unwrap();
+        assert!(amount1.is_zero());
+    }
+
+    #[test]
+    fn fees_follow_the_growth_of_the_accumulators() {
+        let liquidity = U256::from(5_000u64);
+        // A growth of 3 / liquidity units per liquidity, in Q128, earns 3 tokens.
+        let growth = Q128 * U256::from(3) / liquidity;
+        let (fees0, fees1) = fees_for_liquidity(growth, U256::ZERO, liquidity).unwrap();
+        assert_eq!((fees0, fees1), (U256::from(2), U256::ZERO));
+        // Growth across the wrap-around of an accumulator is still its difference.
+        let past = U256::MAX - Q128 + U256::from(1);
+        let delta = Q128.wrapping_sub(past);
+        let (fees0, _) = fees_for_liquidity(delta, U256::ZERO, liquidity).unwrap();
+        assert_eq!(fees0, U256::from(10_000));
+    }
+
+    #[test]
+    fn fee_aprs_are_annualized() {
+        let month = SECONDS_PER_YEAR / 12;
+        let apr = fee_apr(U256::from(1), U256::from(100), month).unwrap();
+        assert_eq!(apr, U256::from(120_000_000_000_000_000u64));
+        assert_eq!(fee_apr(U256::from(1), U256::ZERO, month), Ok(U256::ZERO));
+    }
+}
@@ -0,0 +1,20 @@
+#!/bin/bash
+# Prove the rates of an Aave V3 reserve against a running EVM chain.
+set -e -o pipefail
+
+BLOCK_NUMBER=$(cast block-number --rpc-url ${ETH_RPC_URL:?} | jq -re | xargs cast to-hex)
+export COMMITMENT_BLOCK=$BLOCK_NUMBER
+
+# Enable the history feature and override the commitment block
+if [[ ${HISTORY_BLOCKS} -gt 0 ]]; then
+  printf -v COMMITMENT_BLOCK '%#x' "$((BLOCK_NUMBER + HISTORY_BLOCKS))"
+  PUBLISHER_FEATURES="history"
+fi
+
+# Publish a new state
+echo "Publishing a new state..."
+RISC0_DEV_MODE=true RISC0_INFO=1 RUST_LOG=${RUST_LOG:-info,risc0_steel=debug} cargo run --bin aave_apr_publisher -F "$PUBLISHER_FEATURES" -- \
+  --eth-wallet-private-key=${ETH_WALLET_PRIVATE_KEY:?} \
+  --eth-rpc-url=${ETH_RPC_URL:?} \
+  --execution-block=${BLOCK_NUMBER:?} \
+  --asset=${AAVE_ASSET:?}
@@ -0,0 +1,157 @@
+#![allow(unused_doc_comments)]
+#![no_main]
+
+use alloy_primitives::{Address, U256};
+use erc20_counter_core::{
+    aave::{self, IPool, IRewardsController, Journal},
+    erc20::IERC20,
+    journal::MetricJournal,
+    math,
+    pricing::{self, QuoterV2, SwapPath, QUOTER_V2},
+    status::Status,
+};
+use erc20_counter_guests::Input;
+use risc0_steel::{
+    ethereum::{EthEvmInput, ETH_MAINNET_CHAIN_SPEC},
+    Contract, EvmBlockHeader,
+};
+use risc0_zkvm::guest::env;
+
+risc0_zkvm::guest::entry!(main);
+
+fn main() {
+    // Read the input from the guest environment.
+    let mut guest_input = Input::read();
+    let input: EthEvmInput = guest_input.next();
+    let pool: Address = guest_input.next();
+    let asset: Address = guest_input.next();
+    let rewards_controller: Option<Address> = guest_input.next();
+    let reward_paths: Vec<SwapPath> = guest_input.next();
+
+    // Converts the input into a `EvmEnv` for execution. The `with_chain_spec` method is used
+    // to specify the chain configuration. It checks that the state matches the state root in the
+    // header provided in the input.
+    let env = input.into_env().with_chain_spec(&ETH_MAINNET_CHAIN_SPEC);
+    let timestamp = env.header().timestamp();
+
+    let outcome = || -> Result<_, Status> {
+        let reserve = Contract::new(pool, &env)
+            .call_builder(&IPool::getReserveDataCall { asset })
+            .try_call()
+            .map_err(Status::reverted)?
+            ._0;
+
+        // Rates are stored as annual rates in RAY.
+        let liquidity_rate = U256::from(reserve.currentLiquidityRate);
+        let variable_borrow_rate = U256::from(reserve.currentVariableBorrowRate);
+
+        // Incentives paid to holders of `token` (the aToken or the variable debt token), priced in
+        // the reserve asset. Rewards without a route in `reward_paths` are not counted.
+        let incentives_apr = |token: Address| -> Result<U256, Status> {
+            let Some(rewards_controller) = rewards_controller else {
+                return Ok(U256::ZERO);
+            };
+            let controller = Contract::new(rewards_controller, &env);
+            let rewards = controller
+                .call_builder(&IRewardsController::getRewardsByAssetCall { asset: token })
+                .try_call()
+                .map_err(Status::reverted)?
+                ._0;
+            let total_supply = Contract::new(token, &env)
+                .call_builder(&IERC20::totalSupplyCall {})
+                .try_call()
+                .map_err(Status::reverted)?
+                ._0;
+
+            let mut apr = U256::ZERO;
+            for reward in rewards {
+                let path = pricing::find_route(&reward_paths, reward, asset);
+                if path.is_none() && reward != asset {
+                    continue;
+                }
+
+                let data = controller
+                    .call_builder(&IRewardsController::getRewardsDataCall {
+                        asset: token,
+                        reward,
+                    })
+                    .try_call()
+                    .map_err(Status::reverted)?;
+                let reward_decimals = Contract::new(reward, &env)
+                    .call_builder(&IERC20::decimalsCall {})
+                    .try_call()
+                    .map_err(Status::reverted)?
+                    ._0;
+                let reward_unit = math::pow10(reward_decimals)?;
+                let reward_price = match path {
+                    Some(path) => {
+                        Contract::new(QUOTER_V2, &env)
+                            .call_builder(&QuoterV2::quoteExactInputCall {
+                                path: path.encode(),
+                                amountIn: reward_unit,
+                            })
+                            .try_call()
+                            .map_err(Status::reverted)?
+                            .amountOut
+                    }
+                    None => reward_unit,
+                };
+
+                let reward_apr = aave::incentive_apr(
+                    data.emissionPerSecond,
+                    data.distributionEnd,
+                    timestamp,
+                    reward_price,
+                    reward_decimals,
+                    total_supply,
+                )?;
+                apr = math::add(apr, reward_apr)?;
+            }
+            Ok(apr)
+        };
+        let supply_incentives_apr = incentives_apr(reserve.aTokenAddress)?;
+        let borrow_incentives_apr = incentives_apr(reserve.variableDebtTokenAddress)?;
+
+        let supply_apr = math::ray_to_wad(liquidity_rate);
+        let supply_apy = math::ray_to_wad(math::ray_apr_to_apy(liquidity_rate)?);
+        let borrow_apr = math::ray_to_wad(variable_borrow_rate);
+        let borrow_apy = math::ray_to_wad(math::ray_apr_to_apy(variable_borrow_rate)?);
+        Ok((
+            supply_apr,
+            supply_apy,
+            borrow_apr,
+            borrow_apy,
+            supply_incentives_apr,
+            borrow_incentives_apr,
+        ))
+    };
+    let (
+        status,
+        (
+            supply_apr,
+            supply_apy,
+            borrow_apr,
+            borrow_apy,
+            supply_incentives_apr,
+            borrow_incentives_apr,
+        ),
+    ) = Status::split(outcome());
+
+    // Commit the block hash and number used when deriving `view_call_env` to the journal.
+    let journal = Journal {
+        commitment: env.into_commitment(),
+        status: status.into(),
+        pool,
+        reserve: asset,
+        rewardsController: rewards_controller.unwrap_or_default(),
+        supplyApr: supply_apr,
+        supplyApy: supply_apy,
+        borrowApr: borrow_apr,
+        borrowApy: borrow_apy,
+        supplyIncentivesApr: supply_incentives_apr,
+        borrowIncentivesApr: borrow_incentives_apr,
+    };
+    env::commit_slice(
+        &journal.encode_with_header(ETH_MAINNET_CHAIN_SPEC.chain_id, guest_input.digest()),
+    );
+}
@@ -0,0 +1,56 @@
+#![allow(unused_doc_comments)]
+#![no_main]
+
+use alloy_primitives::B256;
+use erc20_counter_core::{
+    aggregate::{self, Journal, Metric, Sample},
+    journal::MetricJournal,
+    status::Status,
+};
+use erc20_counter_guests::Input;
+use risc0_zkvm::{guest::env, sha::Digest};
+
+risc0_zkvm::guest::entry!(main);
+
+fn main() {
+    // Read the input from the guest environment.
+    let mut guest_input = Input::read();
+    let image_id: Digest = guest_input.next();
+    let metric: Metric = guest_input.next();
+    let journals: Vec<Vec<u8>> = guest_input.next();
+
+    // Each journal is verified as an assumption, which the host resolves with the receipts of
+    // the series.
+    let samples: Vec<Sample> = journals
+        .iter()
+        .map(|journal| {
+            env::verify(image_id, journal.as_slice()).unwrap();
+            metric.decode(journal).expect("invalid journal")
+        })
+        .collect();
+    aggregate::check_order(&samples).unwrap();
+
+    let (status, summary) = Status::split(aggregate::summarize(&samples));
+    let skipped = samples.iter().filter(|sample| !sample.is_ok()).count();
+
+    // Commit the block of every sample, so that the whole series can be validated and not only
+    // its window.
+    let chain_id = samples[0].chain_id;
+    let journal = Journal {
+        commitments: samples
+            .iter()
+            .map(|sample| sample.commitment.clone())
+            .collect(),
+        status: status.into(),
+        imageId: B256::from_slice(image_id.as_bytes()),
+        metric: metric.into(),
+        subject: samples[0].subject,
+        samples: samples.len() as u32,
+        skipped: skipped as u32,
+        min: summary.min,
+        max: summary.max,
+        mean: summary.mean,
+        timeWeightedMean: summary.time_weighted_mean,
+    };
+    env::commit_slice(&journal.encode_with_header(chain_id, guest_input.digest()));
+}
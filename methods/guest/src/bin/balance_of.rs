@@ -1,44 +1,29 @@
 #![allow(unused_doc_comments)]
 #![no_main]
 
-use alloy_primitives::{Address, U256};
-use alloy_sol_types::{sol, SolValue};
+use alloy_primitives::Address;
+use erc20_counter_core::{
+    erc20::{self, Journal, IERC20},
+    journal::MetricJournal,
+    status::Status,
+};
+use erc20_counter_guests::Input;
 use risc0_steel::{
     ethereum::{EthEvmInput, ETH_MAINNET_CHAIN_SPEC},
-    Commitment, Contract,
+    Contract,
 };
 use risc0_zkvm::guest::env;
 
 risc0_zkvm::guest::entry!(main);
 
-/// Specify the function to call using the [`sol!`] macro.
-/// This parses the Solidity syntax to generate a struct that implements the `SolCall` trait.
-sol! {
-    /// ERC-20 balance function signature.
-    interface IERC20 {
-        function balanceOf(address account) external view returns (uint256);
-        function totalSupply() external view returns (uint256);
-    }
-}
-
-/// ABI encodable journal data.
-sol! {
-    struct Journal {
-        Commitment commitment;
-        address tokenAddress;
-        uint256 circulatingSupply;
-        uint256 pastCirculatingSupply;
-        uint256 inflationBasisPoints;
-    }
-}
-
 fn main() {
     // Read the input from the guest environment.
-    let input: EthEvmInput = env::read();
-    let past_input: EthEvmInput = env::read();
-    let token_address: Address = env::read();
-    let account: Address = env::read();
-    let account_2: Address = env::read();
+    let mut guest_input = Input::read();
+    let input: EthEvmInput = guest_input.next();
+    let past_input: EthEvmInput = guest_input.next();
+    let token_address: Address = guest_input.next();
+    let account: Address = guest_input.next();
+    let account_2: Address = guest_input.next();
 
     // Converts the input into a `EvmEnv` for execution. The `with_chain_spec` method is used
     // to specify the chain configuration. It checks that the state matches the state root in the
@@ -54,39 +39,74 @@ fn main() {
     let balance_of_call = IERC20::balanceOfCall { account };
     let balance_of_call_2 = IERC20::balanceOfCall { account: account_2 };
 
-    ////// Present supply
-    let token_contract = Contract::new(token_address, &env);
+    let outcome = || -> Result<_, Status> {
+        ////// Present supply
+        let token_contract = Contract::new(token_address, &env);
 
-    // let returns = Contract::new(contract, &env).call_builder(&call).call();
-    let total_supply = token_contract.call_builder(&supply_call).call()._0;
-    let balance = token_contract.call_builder(&balance_of_call).call()._0;
-    let balance_2 = token_contract.call_builder(&balance_of_call_2).call()._0;
-    let circulating_supply = total_supply - balance - balance_2;
+        // let returns = Contract::new(contract, &env).call_builder(&call).call();
+        let total_supply = token_contract
+            .call_builder(&supply_call)
+            .try_call()
+            .map_err(Status::reverted)?
+            ._0;
+        let balance = token_contract
+            .call_builder(&balance_of_call)
+            .try_call()
+            .map_err(Status::reverted)?
+            ._0;
+        let balance_2 = token_contract
+            .call_builder(&balance_of_call_2)
+            .try_call()
+            .map_err(Status::reverted)?
+            ._0;
+        let circulating_supply = erc20::circulating_supply(total_supply, [balance, balance_2])?;
 
-    ////// Past supply
-    // Execute the view call; it returns the result in the type generated by the `sol!` macro.
-    let past_token_contract = Contract::new(token_address, &past_env);
+        ////// Past supply
+        // Execute the view call; it returns the result in the type generated by the `sol!` macro.
+        let past_token_contract = Contract::new(token_address, &past_env);
 
-    // let returns = Contract::new(contract, &env).call_builder(&call).call();
-    let past_total_supply = past_token_contract.call_builder(&supply_call).call()._0;
-    let past_balance = past_token_contract.call_builder(&balance_of_call).call()._0;
-    let past_balance_2 = past_token_contract
-        .call_builder(&balance_of_call_2)
-        .call()
-        ._0;
-    let past_circulating_supply = past_total_supply - past_balance - past_balance_2;
+        let past_total_supply = past_token_contract
+            .call_builder(&supply_call)
+            .try_call()
+            .map_err(Status::reverted)?
+            ._0;
+        let past_balance = past_token_contract
+            .call_builder(&balance_of_call)
+            .try_call()
+            .map_err(Status::reverted)?
+            ._0;
+        let past_balance_2 = past_token_contract
+            .call_builder(&balance_of_call_2)
+            .try_call()
+            .map_err(Status::reverted)?
+            ._0;
+        let past_circulating_supply =
+            erc20::circulating_supply(past_total_supply, [past_balance, past_balance_2])?;
+        if past_circulating_supply.is_zero() {
+            return Err(Status::NoSupply);
+        }
 
-    let inflation_basis_points = ((circulating_supply - past_circulating_supply)
-        * U256::from(10000))
-        / past_circulating_supply;
+        let inflation_basis_points =
+            erc20::inflation_basis_points(past_circulating_supply, circulating_supply)?;
+        Ok((
+            circulating_supply,
+            past_circulating_supply,
+            inflation_basis_points,
+        ))
+    };
+    let (status, (circulating_supply, past_circulating_supply, inflation_basis_points)) =
+        Status::split(outcome());
 
     // Commit the block hash and number used when deriving `view_call_env` to the journal.
     let journal = Journal {
         commitment: env.into_commitment(),
+        status: status.into(),
         tokenAddress: token_address,
         circulatingSupply: circulating_supply,
         pastCirculatingSupply: past_circulating_supply,
         inflationBasisPoints: inflation_basis_points,
     };
-    env::commit_slice(&journal.abi_encode());
+    env::commit_slice(
+        &journal.encode_with_header(ETH_MAINNET_CHAIN_SPEC.chain_id, guest_input.digest()),
+    );
 }
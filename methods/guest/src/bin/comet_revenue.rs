@@ -0,0 +1,110 @@
+#![allow(unused_doc_comments)]
+#![no_main]
+
+use alloy_primitives::Address;
+use erc20_counter_core::{
+    compound::{self, CometMainInterface, RevenueJournal},
+    journal::MetricJournal,
+    math::{self, MathError},
+    status::Status,
+};
+use erc20_counter_guests::Input;
+use risc0_steel::{
+    ethereum::{EthEvmInput, ETH_MAINNET_CHAIN_SPEC},
+    Contract, EvmBlockHeader,
+};
+use risc0_zkvm::guest::env;
+
+risc0_zkvm::guest::entry!(main);
+
+fn main() {
+    // Read the input from the guest environment.
+    let mut guest_input = Input::read();
+    let input: EthEvmInput = guest_input.next();
+    let past_input: EthEvmInput = guest_input.next();
+    let comet: Address = guest_input.next();
+
+    // Converts the input into a `EvmEnv` for execution. The `with_chain_spec` method is used
+    // to specify the chain configuration. It checks that the state matches the state root in the
+    // header provided in the input.
+    let env = input.into_env().with_chain_spec(&ETH_MAINNET_CHAIN_SPEC);
+    let past_env = past_input
+        .into_env()
+        .with_chain_spec(&ETH_MAINNET_CHAIN_SPEC);
+    assert!(
+        past_env.header().timestamp() < env.header().timestamp(),
+        "past block must precede the execution block"
+    );
+
+    let outcome = || -> Result<_, Status> {
+        ////// Present reserves
+        let comet_contract = Contract::new(comet, &env);
+        let reserves = comet_contract
+            .call_builder(&CometMainInterface::getReservesCall {})
+            .try_call()
+            .map_err(Status::reverted)?
+            ._0;
+        let totals = comet_contract
+            .call_builder(&CometMainInterface::totalsBasicCall {})
+            .try_call()
+            .map_err(Status::reverted)?
+            ._0;
+
+        ////// Past reserves
+        let past_comet_contract = Contract::new(comet, &past_env);
+        let past_reserves = past_comet_contract
+            .call_builder(&CometMainInterface::getReservesCall {})
+            .try_call()
+            .map_err(Status::reverted)?
+            ._0;
+        let past_totals = past_comet_contract
+            .call_builder(&CometMainInterface::totalsBasicCall {})
+            .try_call()
+            .map_err(Status::reverted)?
+            ._0;
+
+        // Borrowers pay `borrow_interest`, of which suppliers receive `supply_interest`; the
+        // protocol keeps the difference.
+        let (borrow_interest, supply_interest) = compound::interest_between(&past_totals, &totals)?;
+        let reserve_growth = reserves
+            .checked_sub(past_reserves)
+            .ok_or(MathError::Underflow)?;
+        let interest_revenue = math::signed_change(supply_interest, borrow_interest)?;
+        Ok((
+            reserves,
+            past_reserves,
+            reserve_growth,
+            borrow_interest,
+            supply_interest,
+            interest_revenue,
+        ))
+    };
+    let (
+        status,
+        (
+            reserves,
+            past_reserves,
+            reserve_growth,
+            borrow_interest,
+            supply_interest,
+            interest_revenue,
+        ),
+    ) = Status::split(outcome());
+
+    // Commit both blocks, so that the window of the revenue can be validated.
+    let journal = RevenueJournal {
+        commitment: env.into_commitment(),
+        pastCommitment: past_env.into_commitment(),
+        status: status.into(),
+        comet,
+        reserves,
+        pastReserves: past_reserves,
+        reserveGrowth: reserve_growth,
+        borrowInterest: borrow_interest,
+        supplyInterest: supply_interest,
+        interestRevenue: interest_revenue,
+    };
+    env::commit_slice(
+        &journal.encode_with_header(ETH_MAINNET_CHAIN_SPEC.chain_id, guest_input.digest()),
+    );
+}
@@ -0,0 +1,111 @@
+#![allow(unused_doc_comments)]
+#![no_main]
+
+use alloy_primitives::{Address, U256};
+use erc20_counter_core::{
+    aave::IPool,
+    comparison::{self, Journal, SupplyMarket},
+    compound::CometMainInterface,
+    journal::MetricJournal,
+    math,
+    morpho::{self, IIrm, IMorpho},
+    status::Status,
+    SECONDS_PER_YEAR,
+};
+use erc20_counter_guests::Input;
+use risc0_steel::{
+    ethereum::{EthEvmInput, ETH_MAINNET_CHAIN_SPEC},
+    Contract,
+};
+use risc0_zkvm::guest::env;
+
+risc0_zkvm::guest::entry!(main);
+
+fn main() {
+    // Read the input from the guest environment.
+    let mut guest_input = Input::read();
+    let input: EthEvmInput = guest_input.next();
+    let markets: Vec<SupplyMarket> = guest_input.next();
+
+    assert!(!markets.is_empty(), "no markets to compare");
+
+    // Converts the input into a `EvmEnv` for execution. The `with_chain_spec` method is used
+    // to specify the chain configuration. It checks that the state matches the state root in the
+    // header provided in the input.
+    let env = input.into_env().with_chain_spec(&ETH_MAINNET_CHAIN_SPEC);
+
+    // Supply APR of a market in WAD. A degenerate market only fails its own entry, not the
+    // comparison.
+    let supply_apr = |market: &SupplyMarket| -> Result<U256, Status> {
+        let contract = Contract::new(market.address(), &env);
+        match *market {
+            SupplyMarket::Comet(_) => {
+                let utilization = contract
+                    .call_builder(&CometMainInterface::getUtilizationCall {})
+                    .try_call()
+                    .map_err(Status::reverted)?
+                    ._0;
+                let supply_rate = contract
+                    .call_builder(&CometMainInterface::getSupplyRateCall { utilization })
+                    .try_call()
+                    .map_err(Status::reverted)?
+                    ._0;
+                Ok(math::mul(
+                    U256::from(supply_rate),
+                    U256::from(SECONDS_PER_YEAR),
+                )?)
+            }
+            SupplyMarket::Morpho { id, .. } => {
+                let state = contract
+                    .call_builder(&IMorpho::marketCall { id })
+                    .try_call()
+                    .map_err(Status::reverted)?
+                    ._0;
+                let market_params = contract
+                    .call_builder(&IMorpho::idToMarketParamsCall { id })
+                    .try_call()
+                    .map_err(Status::reverted)?
+                    ._0;
+                // Idle markets have no interest rate model and earn nothing.
+                let borrow_rate = if market_params.irm == Address::ZERO {
+                    U256::ZERO
+                } else {
+                    Contract::new(market_params.irm, &env)
+                        .call_builder(&IIrm::borrowRateViewCall {
+                            marketParams: market_params,
+                            market: state.clone(),
+                        })
+                        .try_call()
+                        .map_err(Status::reverted)?
+                        ._0
+                };
+                Ok(morpho::supply_apr(borrow_rate, &state)?)
+            }
+            SupplyMarket::Aave { asset, .. } => {
+                let reserve = contract
+                    .call_builder(&IPool::getReserveDataCall { asset })
+                    .try_call()
+                    .map_err(Status::reverted)?
+                    ._0;
+                // Rates are stored as annual rates in RAY.
+                Ok(math::ray_to_wad(U256::from(reserve.currentLiquidityRate)))
+            }
+        }
+    };
+    let markets = markets
+        .iter()
+        .map(|market| market.with_apr(supply_apr(market)))
+        .collect();
+    let (status, ranking, winner) = comparison::rank(markets);
+
+    // Commit the block hash and number used when deriving `view_call_env` to the journal.
+    let journal = Journal {
+        commitment: env.into_commitment(),
+        status: status.into(),
+        ranking,
+        winner,
+    };
+    env::commit_slice(
+        &journal.encode_with_header(ETH_MAINNET_CHAIN_SPEC.chain_id, guest_input.digest()),
+    );
+}
@@ -0,0 +1,174 @@
+#![allow(unused_doc_comments)]
+#![no_main]
+
+use alloy_primitives::{Address, U256};
+use erc20_counter_core::{
+    curve::{
+        self, ICurvePool, ICurveToken, IGaugeController, ILiquidityGauge, Journal, CRV,
+        GAUGE_CONTROLLER,
+    },
+    erc20::IERC20,
+    journal::MetricJournal,
+    math,
+    pricing::{self, QuoterV2, SwapPath, QUOTER_V2},
+    status::Status,
+};
+use erc20_counter_guests::Input;
+use risc0_steel::{
+    ethereum::{EthEvmInput, ETH_MAINNET_CHAIN_SPEC},
+    Contract,
+};
+use risc0_zkvm::guest::env;
+
+risc0_zkvm::guest::entry!(main);
+
+fn main() {
+    // Read the input from the guest environment.
+    let mut guest_input = Input::read();
+    let input: EthEvmInput = guest_input.next();
+    let gauge: Address = guest_input.next();
+    let gauge_controller: Address = guest_input.next();
+    let pool: Address = guest_input.next();
+    let base_token: Address = guest_input.next();
+    let numeraire: Address = guest_input.next();
+    let price_paths: Vec<SwapPath> = guest_input.next();
+
+    // Converts the input into a `EvmEnv` for execution. The `with_chain_spec` method is used
+    // to specify the chain configuration. It checks that the state matches the state root in the
+    // header provided in the input.
+    let env = input.into_env().with_chain_spec(&ETH_MAINNET_CHAIN_SPEC);
+
+    assert_eq!(
+        gauge_controller, GAUGE_CONTROLLER,
+        "not the Curve GaugeController"
+    );
+    let lp_token = Contract::new(gauge, &env)
+        .call_builder(&ILiquidityGauge::lp_tokenCall {})
+        .call()
+        ._0;
+    let minter = (lp_token != pool).then(|| {
+        Contract::new(lp_token, &env)
+            .call_builder(&ICurveToken::minterCall {})
+            .call()
+            ._0
+    });
+    assert!(
+        curve::is_gauge_pool(pool, lp_token, minter),
+        "pool does not issue the LP token of the gauge"
+    );
+
+    let outcome = || -> Result<_, Status> {
+        let gauge_contract = Contract::new(gauge, &env);
+        let inflation_rate = gauge_contract
+            .call_builder(&ILiquidityGauge::inflation_rateCall {})
+            .try_call()
+            .map_err(Status::reverted)?
+            ._0;
+        let working_supply = gauge_contract
+            .call_builder(&ILiquidityGauge::working_supplyCall {})
+            .try_call()
+            .map_err(Status::reverted)?
+            ._0;
+        if working_supply.is_zero() {
+            return Err(Status::NoSupply);
+        }
+        let total_supply = gauge_contract
+            .call_builder(&ILiquidityGauge::totalSupplyCall {})
+            .try_call()
+            .map_err(Status::reverted)?
+            ._0;
+        let relative_weight = Contract::new(gauge_controller, &env)
+            .call_builder(&IGaugeController::gauge_relative_weightCall { addr: gauge })
+            .try_call()
+            .map_err(Status::reverted)?
+            ._0;
+        let virtual_price = Contract::new(pool, &env)
+            .call_builder(&ICurvePool::get_virtual_priceCall {})
+            .try_call()
+            .map_err(Status::reverted)?
+            ._0;
+
+        // Price of one whole token in base units of the numeraire.
+        let price = |token: Address| -> Result<U256, Status> {
+            let decimals = Contract::new(token, &env)
+                .call_builder(&IERC20::decimalsCall {})
+                .try_call()
+                .map_err(Status::reverted)?
+                ._0;
+            let unit = math::pow10(decimals)?;
+            if token == numeraire {
+                return Ok(unit);
+            }
+            let path = pricing::find_route(&price_paths, token, numeraire)
+                .expect("missing price route for token");
+            let price = Contract::new(QUOTER_V2, &env)
+                .call_builder(&QuoterV2::quoteExactInputCall {
+                    path: path.encode(),
+                    amountIn: unit,
+                })
+                .try_call()
+                .map_err(Status::reverted)?
+                .amountOut;
+            Ok(price)
+        };
+        let crv_price = price(CRV)?;
+        let lp_price = curve::lp_price(virtual_price, price(base_token)?)?;
+
+        let max_boost_apr = curve::max_boost_apr(
+            inflation_rate,
+            relative_weight,
+            working_supply,
+            crv_price,
+            lp_price,
+        )?;
+        let base_apr = curve::base_apr(max_boost_apr)?;
+        Ok((
+            inflation_rate,
+            relative_weight,
+            working_supply,
+            total_supply,
+            virtual_price,
+            crv_price,
+            lp_price,
+            base_apr,
+            max_boost_apr,
+        ))
+    };
+    let (
+        status,
+        (
+            inflation_rate,
+            relative_weight,
+            working_supply,
+            total_supply,
+            virtual_price,
+            crv_price,
+            lp_price,
+            base_apr,
+            max_boost_apr,
+        ),
+    ) = Status::split(outcome());
+
+    // Commit the block hash and number used when deriving `view_call_env` to the journal.
+    let journal = Journal {
+        commitment: env.into_commitment(),
+        status: status.into(),
+        gauge,
+        gaugeController: gauge_controller,
+        pool,
+        baseToken: base_token,
+        numeraire,
+        inflationRate: inflation_rate,
+        relativeWeight: relative_weight,
+        workingSupply: working_supply,
+        totalSupply: total_supply,
+        virtualPrice: virtual_price,
+        crvPrice: crv_price,
+        lpPrice: lp_price,
+        baseApr: base_apr,
+        maxBoostApr: max_boost_apr,
+    };
+    env::commit_slice(
+        &journal.encode_with_header(ETH_MAINNET_CHAIN_SPEC.chain_id, guest_input.digest()),
+    );
+}
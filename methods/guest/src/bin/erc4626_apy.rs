@@ -0,0 +1,145 @@
+#![allow(unused_doc_comments)]
+#![no_main]
+
+use alloy_primitives::Address;
+use erc20_counter_core::{
+    erc4626::{Journal, IERC4626},
+    journal::MetricJournal,
+    math,
+    status::Status,
+};
+use erc20_counter_guests::Input;
+use risc0_steel::{
+    ethereum::{EthEvmInput, ETH_MAINNET_CHAIN_SPEC},
+    Contract, EvmBlockHeader,
+};
+use risc0_zkvm::guest::env;
+
+risc0_zkvm::guest::entry!(main);
+
+fn main() {
+    // Read the input from the guest environment.
+    let mut guest_input = Input::read();
+    let input: EthEvmInput = guest_input.next();
+    let past_input: EthEvmInput = guest_input.next();
+    let vault: Address = guest_input.next();
+
+    // Converts the input into a `EvmEnv` for execution. The `with_chain_spec` method is used
+    // to specify the chain configuration. It checks that the state matches the state root in the
+    // header provided in the input.
+    let env = input.into_env().with_chain_spec(&ETH_MAINNET_CHAIN_SPEC);
+    let past_env = past_input
+        .into_env()
+        .with_chain_spec(&ETH_MAINNET_CHAIN_SPEC);
+
+    let timestamp = env.header().timestamp();
+    let past_timestamp = past_env.header().timestamp();
+    assert!(
+        past_timestamp < timestamp,
+        "past block must precede the execution block"
+    );
+
+    let outcome = || -> Result<_, Status> {
+        ////// Present share price
+        let vault_contract = Contract::new(vault, &env);
+        let asset = vault_contract
+            .call_builder(&IERC4626::assetCall {})
+            .try_call()
+            .map_err(Status::reverted)?
+            ._0;
+        let decimals = vault_contract
+            .call_builder(&IERC4626::decimalsCall {})
+            .try_call()
+            .map_err(Status::reverted)?
+            ._0;
+        let convert_call = IERC4626::convertToAssetsCall {
+            shares: math::pow10(decimals)?,
+        };
+        let share_price = vault_contract
+            .call_builder(&convert_call)
+            .try_call()
+            .map_err(Status::reverted)?
+            ._0;
+        let total_assets = vault_contract
+            .call_builder(&IERC4626::totalAssetsCall {})
+            .try_call()
+            .map_err(Status::reverted)?
+            ._0;
+        let total_supply = vault_contract
+            .call_builder(&IERC4626::totalSupplyCall {})
+            .try_call()
+            .map_err(Status::reverted)?
+            ._0;
+
+        ////// Past share price
+        let past_vault_contract = Contract::new(vault, &past_env);
+        let past_share_price = past_vault_contract
+            .call_builder(&convert_call)
+            .try_call()
+            .map_err(Status::reverted)?
+            ._0;
+        let past_total_assets = past_vault_contract
+            .call_builder(&IERC4626::totalAssetsCall {})
+            .try_call()
+            .map_err(Status::reverted)?
+            ._0;
+        let past_total_supply = past_vault_contract
+            .call_builder(&IERC4626::totalSupplyCall {})
+            .try_call()
+            .map_err(Status::reverted)?
+            ._0;
+
+        if total_supply.is_zero() || past_total_supply.is_zero() {
+            return Err(Status::NoSupply);
+        }
+
+        let elapsed = timestamp - past_timestamp;
+        let apr = math::annualized_change(past_share_price, share_price, elapsed)?;
+        let apy = math::realized_apy(past_share_price, share_price, elapsed)?;
+        Ok((
+            asset,
+            share_price,
+            past_share_price,
+            total_assets,
+            total_supply,
+            past_total_assets,
+            past_total_supply,
+            apr,
+            apy,
+        ))
+    };
+    let (
+        status,
+        (
+            asset,
+            share_price,
+            past_share_price,
+            total_assets,
+            total_supply,
+            past_total_assets,
+            past_total_supply,
+            apr,
+            apy,
+        ),
+    ) = Status::split(outcome());
+
+    // Commit both blocks, so that the window of the APY can be validated.
+    let journal = Journal {
+        commitment: env.into_commitment(),
+        pastCommitment: past_env.into_commitment(),
+        status: status.into(),
+        vault,
+        asset,
+        sharePrice: share_price,
+        pastSharePrice: past_share_price,
+        totalAssets: total_assets,
+        totalSupply: total_supply,
+        pastTotalAssets: past_total_assets,
+        pastTotalSupply: past_total_supply,
+        apr,
+        apy,
+    };
+    env::commit_slice(
+        &journal.encode_with_header(ETH_MAINNET_CHAIN_SPEC.chain_id, guest_input.digest()),
+    );
+}
@@ -0,0 +1,101 @@
+#![allow(unused_doc_comments)]
+#![no_main]
+
+use alloy_primitives::Address;
+use erc20_counter_core::{
+    journal::MetricJournal,
+    lido::{self, IStETH, Journal},
+    math,
+    status::Status,
+};
+use erc20_counter_guests::Input;
+use risc0_steel::{
+    ethereum::{EthEvmInput, ETH_MAINNET_CHAIN_SPEC},
+    Contract, EvmBlockHeader,
+};
+use risc0_zkvm::guest::env;
+
+risc0_zkvm::guest::entry!(main);
+
+fn main() {
+    // Read the input from the guest environment.
+    let mut guest_input = Input::read();
+    let input: EthEvmInput = guest_input.next();
+    let past_input: EthEvmInput = guest_input.next();
+    let steth: Address = guest_input.next();
+
+    // Converts the input into a `EvmEnv` for execution. The `with_chain_spec` method is used
+    // to specify the chain configuration. It checks that the state matches the state root in the
+    // header provided in the input.
+    let env = input.into_env().with_chain_spec(&ETH_MAINNET_CHAIN_SPEC);
+    let past_env = past_input
+        .into_env()
+        .with_chain_spec(&ETH_MAINNET_CHAIN_SPEC);
+
+    let timestamp = env.header().timestamp();
+    let past_timestamp = past_env.header().timestamp();
+    assert!(
+        past_timestamp < timestamp,
+        "past block must precede the execution block"
+    );
+
+    let pooled_ether_call = IStETH::getTotalPooledEtherCall {};
+    let shares_call = IStETH::getTotalSharesCall {};
+
+    let outcome = || -> Result<_, Status> {
+        ////// Present share rate
+        let steth_contract = Contract::new(steth, &env);
+        let total_pooled_ether = steth_contract
+            .call_builder(&pooled_ether_call)
+            .try_call()
+            .map_err(Status::reverted)?
+            ._0;
+        let total_shares = steth_contract
+            .call_builder(&shares_call)
+            .try_call()
+            .map_err(Status::reverted)?
+            ._0;
+        if total_shares.is_zero() {
+            return Err(Status::NoSupply);
+        }
+        let share_rate = lido::share_rate(total_pooled_ether, total_shares)?;
+
+        ////// Past share rate
+        let past_steth_contract = Contract::new(steth, &past_env);
+        let past_total_pooled_ether = past_steth_contract
+            .call_builder(&pooled_ether_call)
+            .try_call()
+            .map_err(Status::reverted)?
+            ._0;
+        let past_total_shares = past_steth_contract
+            .call_builder(&shares_call)
+            .try_call()
+            .map_err(Status::reverted)?
+            ._0;
+        if past_total_shares.is_zero() {
+            return Err(Status::NoSupply);
+        }
+        let past_share_rate = lido::share_rate(past_total_pooled_ether, past_total_shares)?;
+
+        let elapsed = timestamp - past_timestamp;
+        let apr = math::annualized_change(past_share_rate, share_rate, elapsed)?;
+        let apy = math::realized_apy(past_share_rate, share_rate, elapsed)?;
+        Ok((share_rate, past_share_rate, apr, apy))
+    };
+    let (status, (share_rate, past_share_rate, apr, apy)) = Status::split(outcome());
+
+    // Commit both blocks, so that the window of the APR can be validated.
+    let journal = Journal {
+        commitment: env.into_commitment(),
+        pastCommitment: past_env.into_commitment(),
+        status: status.into(),
+        stETH: steth,
+        shareRate: share_rate,
+        pastShareRate: past_share_rate,
+        apr,
+        apy,
+    };
+    env::commit_slice(
+        &journal.encode_with_header(ETH_MAINNET_CHAIN_SPEC.chain_id, guest_input.digest()),
+    );
+}
@@ -0,0 +1,150 @@
+#![allow(unused_doc_comments)]
+#![no_main]
+
+use alloy_primitives::{Address, U256};
+use erc20_counter_core::{
+    journal::MetricJournal,
+    math,
+    morpho::{self, IIrm, IMetaMorpho, IMorpho, Journal, MarketAllocation},
+    status::Status,
+};
+use erc20_counter_guests::Input;
+use risc0_steel::{
+    ethereum::{EthEvmInput, ETH_MAINNET_CHAIN_SPEC},
+    Contract,
+};
+use risc0_zkvm::guest::env;
+
+risc0_zkvm::guest::entry!(main);
+
+fn main() {
+    // Read the input from the guest environment.
+    let mut guest_input = Input::read();
+    let input: EthEvmInput = guest_input.next();
+    let vault: Address = guest_input.next();
+
+    // Converts the input into a `EvmEnv` for execution. The `with_chain_spec` method is used
+    // to specify the chain configuration. It checks that the state matches the state root in the
+    // header provided in the input.
+    let env = input.into_env().with_chain_spec(&ETH_MAINNET_CHAIN_SPEC);
+
+    let outcome = || -> Result<_, Status> {
+        let vault_contract = Contract::new(vault, &env);
+        let morpho_address = vault_contract
+            .call_builder(&IMetaMorpho::MORPHOCall {})
+            .try_call()
+            .map_err(Status::reverted)?
+            ._0;
+        let fee = U256::from(
+            vault_contract
+                .call_builder(&IMetaMorpho::feeCall {})
+                .try_call()
+                .map_err(Status::reverted)?
+                ._0,
+        );
+        let total_assets = vault_contract
+            .call_builder(&IMetaMorpho::totalAssetsCall {})
+            .try_call()
+            .map_err(Status::reverted)?
+            ._0;
+
+        let supply_queue_length = vault_contract
+            .call_builder(&IMetaMorpho::supplyQueueLengthCall {})
+            .try_call()
+            .map_err(Status::reverted)?
+            ._0;
+        let supply_queue: Vec<_> = (0..supply_queue_length.to::<u64>())
+            .map(|index| {
+                vault_contract
+                    .call_builder(&IMetaMorpho::supplyQueueCall {
+                        index: U256::from(index),
+                    })
+                    .try_call()
+                    .map(|queue| queue._0)
+                    .map_err(Status::reverted)
+            })
+            .collect::<Result<_, _>>()?;
+
+        // Every market the vault can hold a position in is part of the withdraw queue.
+        let withdraw_queue_length = vault_contract
+            .call_builder(&IMetaMorpho::withdrawQueueLengthCall {})
+            .try_call()
+            .map_err(Status::reverted)?
+            ._0;
+        let morpho_contract = Contract::new(morpho_address, &env);
+        let mut markets = Vec::new();
+        for index in 0..withdraw_queue_length.to::<u64>() {
+            let id = vault_contract
+                .call_builder(&IMetaMorpho::withdrawQueueCall {
+                    index: U256::from(index),
+                })
+                .try_call()
+                .map_err(Status::reverted)?
+                ._0;
+            let market = morpho_contract
+                .call_builder(&IMorpho::marketCall { id })
+                .try_call()
+                .map_err(Status::reverted)?
+                ._0;
+            let market_params = morpho_contract
+                .call_builder(&IMorpho::idToMarketParamsCall { id })
+                .try_call()
+                .map_err(Status::reverted)?
+                ._0;
+            let position = morpho_contract
+                .call_builder(&IMorpho::positionCall { id, user: vault })
+                .try_call()
+                .map_err(Status::reverted)?;
+
+            // Idle markets have no interest rate model and earn nothing.
+            let borrow_rate = if market_params.irm == Address::ZERO {
+                U256::ZERO
+            } else {
+                Contract::new(market_params.irm, &env)
+                    .call_builder(&IIrm::borrowRateViewCall {
+                        marketParams: market_params,
+                        market: market.clone(),
+                    })
+                    .try_call()
+                    .map_err(Status::reverted)?
+                    ._0
+            };
+
+            let supply_apr = morpho::supply_apr(borrow_rate, &market)?;
+            markets.push(MarketAllocation {
+                id,
+                inSupplyQueue: supply_queue.contains(&id),
+                supplyAssets: morpho::to_assets_down(
+                    position.supplyShares,
+                    market.totalSupplyAssets,
+                    market.totalSupplyShares,
+                )?,
+                supplyApr: supply_apr,
+                supplyApy: math::wad_apr_to_apy(supply_apr)?,
+            });
+        }
+
+        let gross_apr = morpho::weighted_apr(&markets)?;
+        let net_apr = morpho::net_of_fee(gross_apr, fee)?;
+        let net_apy = math::wad_apr_to_apy(net_apr)?;
+        Ok((fee, total_assets, markets, gross_apr, net_apr, net_apy))
+    };
+    let (status, (fee, total_assets, markets, gross_apr, net_apr, net_apy)) =
+        Status::split(outcome());
+
+    // Commit the block hash and number used when deriving `view_call_env` to the journal.
+    let journal = Journal {
+        commitment: env.into_commitment(),
+        status: status.into(),
+        vault,
+        fee,
+        totalAssets: total_assets,
+        markets,
+        grossApr: gross_apr,
+        netApr: net_apr,
+        netApy: net_apy,
+    };
+    env::commit_slice(
+        &journal.encode_with_header(ETH_MAINNET_CHAIN_SPEC.chain_id, guest_input.digest()),
+    );
+}
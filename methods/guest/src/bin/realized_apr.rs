@@ -0,0 +1,131 @@
+#![allow(unused_doc_comments)]
+#![no_main]
+
+use alloy_primitives::U256;
+use erc20_counter_core::{
+    compound::CometMainInterface,
+    journal::MetricJournal,
+    math::{self, MathError},
+    morpho::{self, IMorpho},
+    realized::{Journal, LendingMarket},
+    status::Status,
+};
+use erc20_counter_guests::Input;
+use risc0_steel::{
+    ethereum::{EthEvmInput, ETH_MAINNET_CHAIN_SPEC},
+    Contract, EvmBlockHeader,
+};
+use risc0_zkvm::guest::env;
+
+risc0_zkvm::guest::entry!(main);
+
+fn main() {
+    // Read the input from the guest environment.
+    let mut guest_input = Input::read();
+    let input: EthEvmInput = guest_input.next();
+    let past_input: EthEvmInput = guest_input.next();
+    let market: LendingMarket = guest_input.next();
+
+    // Converts the input into a `EvmEnv` for execution. The `with_chain_spec` method is used
+    // to specify the chain configuration. It checks that the state matches the state root in the
+    // header provided in the input.
+    let env = input.into_env().with_chain_spec(&ETH_MAINNET_CHAIN_SPEC);
+    let past_env = past_input
+        .into_env()
+        .with_chain_spec(&ETH_MAINNET_CHAIN_SPEC);
+
+    assert!(
+        past_env.header().timestamp() < env.header().timestamp(),
+        "past block must precede the execution block"
+    );
+
+    let outcome = || -> Result<_, Status> {
+        // Supply index, borrow index and the time they were last accrued, at each block.
+        let mut snapshots = Vec::with_capacity(2);
+        for env in [&env, &past_env] {
+            let contract = Contract::new(market.address(), env);
+            let snapshot = match market {
+                LendingMarket::Comet(_) => {
+                    let totals = contract
+                        .call_builder(&CometMainInterface::totalsBasicCall {})
+                        .try_call()
+                        .map_err(Status::reverted)?
+                        ._0;
+                    (
+                        U256::from(totals.baseSupplyIndex),
+                        U256::from(totals.baseBorrowIndex),
+                        totals.lastAccrualTime.to::<u64>(),
+                    )
+                }
+                LendingMarket::Morpho { id, .. } => {
+                    let state = contract
+                        .call_builder(&IMorpho::marketCall { id })
+                        .try_call()
+                        .map_err(Status::reverted)?
+                        ._0;
+                    (
+                        morpho::share_price(state.totalSupplyAssets, state.totalSupplyShares)?,
+                        morpho::share_price(state.totalBorrowAssets, state.totalBorrowShares)?,
+                        state.lastUpdate as u64,
+                    )
+                }
+            };
+            snapshots.push(snapshot);
+        }
+        let (supply_index, borrow_index, accrual_time) = snapshots[0];
+        let (past_supply_index, past_borrow_index, past_accrual_time) = snapshots[1];
+        let elapsed = accrual_time
+            .checked_sub(past_accrual_time)
+            .ok_or(MathError::Underflow)?;
+        let supply_apr = math::annualized_change(past_supply_index, supply_index, elapsed)?;
+        let borrow_apr = math::annualized_change(past_borrow_index, borrow_index, elapsed)?;
+        let supply_apy = math::realized_apy(past_supply_index, supply_index, elapsed)?;
+        let borrow_apy = math::realized_apy(past_borrow_index, borrow_index, elapsed)?;
+        Ok((
+            supply_index,
+            past_supply_index,
+            borrow_index,
+            past_borrow_index,
+            elapsed,
+            supply_apr,
+            supply_apy,
+            borrow_apr,
+            borrow_apy,
+        ))
+    };
+    let (
+        status,
+        (
+            supply_index,
+            past_supply_index,
+            borrow_index,
+            past_borrow_index,
+            elapsed,
+            supply_apr,
+            supply_apy,
+            borrow_apr,
+            borrow_apy,
+        ),
+    ) = Status::split(outcome());
+
+    // Commit both blocks, so that the window of the rates can be validated.
+    let journal = Journal {
+        commitment: env.into_commitment(),
+        pastCommitment: past_env.into_commitment(),
+        status: status.into(),
+        market: market.address(),
+        marketId: market.id(),
+        supplyIndex: supply_index,
+        pastSupplyIndex: past_supply_index,
+        borrowIndex: borrow_index,
+        pastBorrowIndex: past_borrow_index,
+        elapsed,
+        realizedSupplyApr: supply_apr,
+        realizedSupplyApy: supply_apy,
+        realizedBorrowApr: borrow_apr,
+        realizedBorrowApy: borrow_apy,
+    };
+    env::commit_slice(
+        &journal.encode_with_header(ETH_MAINNET_CHAIN_SPEC.chain_id, guest_input.digest()),
+    );
+}
@@ -0,0 +1,93 @@
+#![allow(unused_doc_comments)]
+#![no_main]
+
+use alloy_primitives::keccak256;
+use alloy_sol_types::SolValue;
+use erc20_counter_core::{
+    aggregate::{Metric, Sample},
+    journal::MetricJournal,
+    score::{self, Journal, Policy, ProtocolJournals, ProtocolScore},
+    status::Status,
+};
+use erc20_counter_guests::Input;
+use risc0_zkvm::{guest::env, sha::Digest};
+
+risc0_zkvm::guest::entry!(main);
+
+fn main() {
+    // Read the input from the guest environment. The policy is read ABI encoded, so that its
+    // digest is the same inside and outside of the zkVM.
+    let mut guest_input = Input::read();
+    let policy_bytes: Vec<u8> = guest_input.next();
+    let protocols: Vec<ProtocolJournals> = guest_input.next();
+
+    let policy = Policy::abi_decode(&policy_bytes, true).expect("invalid policy");
+    score::check_policy(&policy).unwrap();
+    assert!(!protocols.is_empty(), "no protocols to score");
+    score::check_names(protocols.iter().map(|protocol| protocol.name.as_str())).unwrap();
+
+    // All metrics must be of the same chain, which is committed in the header.
+    let mut chain_id = None;
+
+    let scores: Vec<ProtocolScore> = protocols
+        .into_iter()
+        .map(|protocol| {
+            assert_eq!(
+                protocol.journals.len(),
+                policy.weights.len(),
+                "protocol {} does not have a journal per metric",
+                protocol.name
+            );
+            // Each journal is verified as an assumption, which the host resolves with the
+            // receipts of the metric guests.
+            let samples: Vec<Sample> = policy
+                .weights
+                .iter()
+                .zip(&protocol.journals)
+                .map(|(weight, journal)| {
+                    env::verify(Digest::from(weight.imageId.0), journal.as_slice()).unwrap();
+                    let metric = Metric::try_from(weight.metric).unwrap();
+                    let sample = metric.decode(journal).expect("invalid journal");
+                    assert_eq!(
+                        *chain_id.get_or_insert(sample.chain_id),
+                        sample.chain_id,
+                        "metrics of different chains"
+                    );
+                    sample
+                })
+                .collect();
+            let subject = score::subject(&samples)
+                .unwrap_or_else(|err| panic!("protocol {}: {err}", protocol.name));
+
+            let (status, score) = Status::split(score::score(&policy, &samples));
+            ProtocolScore {
+                name: protocol.name,
+                subject,
+                status: status.into(),
+                commitments: samples
+                    .iter()
+                    .map(|sample| sample.commitment.clone())
+                    .collect(),
+                values: samples.iter().map(|sample| sample.value).collect(),
+                score,
+            }
+        })
+        .collect();
+
+    let ok = u8::from(Status::Ok);
+    let status = if scores.iter().any(|score| score.status == ok) {
+        Status::Ok
+    } else {
+        Status::NoSamples
+    };
+
+    let journal = Journal {
+        policyDigest: keccak256(&policy_bytes),
+        policy,
+        status: status.into(),
+        scores,
+    };
+    env::commit_slice(
+        &journal.encode_with_header(chain_id.expect("policy has weights"), guest_input.digest()),
+    );
+}
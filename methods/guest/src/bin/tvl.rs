@@ -0,0 +1,145 @@
+#![allow(unused_doc_comments)]
+#![no_main]
+
+use std::{collections::HashMap, iter};
+
+use alloy_primitives::{Address, U256};
+use erc20_counter_core::{
+    erc20::IERC20,
+    journal::MetricJournal,
+    math::{self, mul_div, Rounding},
+    pricing::{self, QuoterV2, SwapPath, QUOTER_V2},
+    status::Status,
+    tvl::{Holding, HoldingSpec, Journal},
+};
+use erc20_counter_guests::Input;
+use risc0_steel::{
+    ethereum::{EthEvmInput, ETH_MAINNET_CHAIN_SPEC},
+    Commitment, Contract,
+};
+use risc0_zkvm::guest::env;
+
+risc0_zkvm::guest::entry!(main);
+
+fn main() {
+    // Read the input from the guest environment.
+    let mut guest_input = Input::read();
+    let input: EthEvmInput = guest_input.next();
+    let past_input: Option<EthEvmInput> = guest_input.next();
+    let holdings: Vec<HoldingSpec> = guest_input.next();
+    let numeraire: Address = guest_input.next();
+    let price_paths: Vec<SwapPath> = guest_input.next();
+
+    // Converts the input into a `EvmEnv` for execution. The `with_chain_spec` method is used
+    // to specify the chain configuration. It checks that the state matches the state root in the
+    // header provided in the input.
+    let env = input.into_env().with_chain_spec(&ETH_MAINNET_CHAIN_SPEC);
+    let past_env =
+        past_input.map(|input| input.into_env().with_chain_spec(&ETH_MAINNET_CHAIN_SPEC));
+
+    let outcome = || -> Result<_, Status> {
+        // Balances and their values at the execution block and, if given, at the past block.
+        let mut valuations = Vec::new();
+        for env in iter::once(&env).chain(past_env.as_ref()) {
+            // Price of one whole token in base units of the numeraire, quoted once per token.
+            let mut prices: HashMap<Address, (U256, U256)> = HashMap::new();
+            let mut valuation = Vec::with_capacity(holdings.len());
+            for holding in &holdings {
+                let token_contract = Contract::new(holding.token, env);
+                let balance = token_contract
+                    .call_builder(&IERC20::balanceOfCall {
+                        account: holding.holder,
+                    })
+                    .try_call()
+                    .map_err(Status::reverted)?
+                    ._0;
+                let (price, unit) = match prices.get(&holding.token) {
+                    Some(price) => *price,
+                    None => {
+                        let decimals = token_contract
+                            .call_builder(&IERC20::decimalsCall {})
+                            .try_call()
+                            .map_err(Status::reverted)?
+                            ._0;
+                        let unit = math::pow10(decimals)?;
+                        let price = if holding.token == numeraire {
+                            unit
+                        } else {
+                            let path = pricing::find_route(&price_paths, holding.token, numeraire)
+                                .expect("missing price route for token");
+                            Contract::new(QUOTER_V2, env)
+                                .call_builder(&QuoterV2::quoteExactInputCall {
+                                    path: path.encode(),
+                                    amountIn: unit,
+                                })
+                                .try_call()
+                                .map_err(Status::reverted)?
+                                .amountOut
+                        };
+                        prices.insert(holding.token, (price, unit));
+                        (price, unit)
+                    }
+                };
+                valuation.push((balance, mul_div(balance, price, unit, Rounding::Down)?));
+            }
+            valuations.push(valuation);
+        }
+
+        let total_values = valuations
+            .iter()
+            .map(|valuation| math::sum(valuation.iter().map(|(_, value)| *value)))
+            .collect::<math::Result<Vec<U256>>>()?;
+        let total_value = total_values[0];
+        let past_total_value = total_values.get(1).copied().unwrap_or_default();
+        let total_value_change = match total_values.get(1) {
+            Some(past_total_value) => math::signed_change(*past_total_value, total_value)?,
+            None => Default::default(),
+        };
+        Ok((
+            valuations,
+            total_value,
+            past_total_value,
+            total_value_change,
+        ))
+    };
+    let (status, (valuations, total_value, past_total_value, total_value_change)) =
+        Status::split(outcome());
+
+    // Holdings are listed even if the valuation failed, with zero balances and values.
+    let valuation = |block: usize, i: usize| {
+        valuations
+            .get(block)
+            .map_or((U256::ZERO, U256::ZERO), |valuation| valuation[i])
+    };
+    let holdings: Vec<Holding> = holdings
+        .iter()
+        .enumerate()
+        .map(|(i, holding)| {
+            let (balance, value) = valuation(0, i);
+            let (past_balance, past_value) = valuation(1, i);
+            Holding {
+                holder: holding.holder,
+                token: holding.token,
+                balance,
+                value,
+                pastBalance: past_balance,
+                pastValue: past_value,
+            }
+        })
+        .collect();
+
+    // Commit the blocks used to the journal.
+    let journal = Journal {
+        commitment: env.into_commitment(),
+        pastCommitment: past_env.map_or_else(Commitment::default, |env| env.into_commitment()),
+        status: status.into(),
+        numeraire,
+        holdings,
+        totalValue: total_value,
+        pastTotalValue: past_total_value,
+        totalValueChange: total_value_change,
+    };
+    env::commit_slice(
+        &journal.encode_with_header(ETH_MAINNET_CHAIN_SPEC.chain_id, guest_input.digest()),
+    );
+}
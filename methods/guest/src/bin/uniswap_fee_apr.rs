@@ -0,0 +1,205 @@
+#![allow(unused_doc_comments)]
+#![no_main]
+
+use alloy_primitives::{aliases::I24, Address, U256};
+use erc20_counter_core::{
+    erc20::IERC20,
+    journal::MetricJournal,
+    math::{self, mul_div, Rounding},
+    pricing::{self, QuoterV2, SwapPath, QUOTER_V2},
+    status::Status,
+    uniswap::{self, IUniswapV3Pool, Journal},
+};
+use erc20_counter_guests::Input;
+use risc0_steel::{
+    ethereum::{EthEvmInput, ETH_MAINNET_CHAIN_SPEC},
+    Contract, EvmBlockHeader,
+};
+use risc0_zkvm::guest::env;
+
+risc0_zkvm::guest::entry!(main);
+
+fn main() {
+    // Read the input from the guest environment.
+    let mut guest_input = Input::read();
+    let input: EthEvmInput = guest_input.next();
+    let past_input: EthEvmInput = guest_input.next();
+    let pool: Address = guest_input.next();
+    let numeraire: Address = guest_input.next();
+    let price_paths: Vec<SwapPath> = guest_input.next();
+
+    // Converts the input into a `EvmEnv` for execution. The `with_chain_spec` method is used
+    // to specify the chain configuration. It checks that the state matches the state root in the
+    // header provided in the input.
+    let env = input.into_env().with_chain_spec(&ETH_MAINNET_CHAIN_SPEC);
+    let past_env = past_input
+        .into_env()
+        .with_chain_spec(&ETH_MAINNET_CHAIN_SPEC);
+
+    let timestamp = env.header().timestamp();
+    let past_timestamp = past_env.header().timestamp();
+    assert!(
+        past_timestamp < timestamp,
+        "past block must precede the execution block"
+    );
+
+    let outcome = || -> Result<_, Status> {
+        ////// Present pool state
+        let pool_contract = Contract::new(pool, &env);
+        let token0 = pool_contract
+            .call_builder(&IUniswapV3Pool::token0Call {})
+            .try_call()
+            .map_err(Status::reverted)?
+            ._0;
+        let token1 = pool_contract
+            .call_builder(&IUniswapV3Pool::token1Call {})
+            .try_call()
+            .map_err(Status::reverted)?
+            ._0;
+        let tick_spacing = pool_contract
+            .call_builder(&IUniswapV3Pool::tickSpacingCall {})
+            .try_call()
+            .map_err(Status::reverted)?
+            ._0;
+        let liquidity = pool_contract
+            .call_builder(&IUniswapV3Pool::liquidityCall {})
+            .try_call()
+            .map_err(Status::reverted)?
+            ._0;
+        if liquidity == 0 {
+            return Err(Status::NoSupply);
+        }
+        let slot0 = pool_contract
+            .call_builder(&IUniswapV3Pool::slot0Call {})
+            .try_call()
+            .map_err(Status::reverted)?;
+        let fee_growth_0 = pool_contract
+            .call_builder(&IUniswapV3Pool::feeGrowthGlobal0X128Call {})
+            .try_call()
+            .map_err(Status::reverted)?
+            ._0;
+        let fee_growth_1 = pool_contract
+            .call_builder(&IUniswapV3Pool::feeGrowthGlobal1X128Call {})
+            .try_call()
+            .map_err(Status::reverted)?
+            ._0;
+
+        ////// Past pool state
+        let past_pool_contract = Contract::new(pool, &past_env);
+        let past_fee_growth_0 = past_pool_contract
+            .call_builder(&IUniswapV3Pool::feeGrowthGlobal0X128Call {})
+            .try_call()
+            .map_err(Status::reverted)?
+            ._0;
+        let past_fee_growth_1 = past_pool_contract
+            .call_builder(&IUniswapV3Pool::feeGrowthGlobal1X128Call {})
+            .try_call()
+            .map_err(Status::reverted)?
+            ._0;
+
+        // The accumulators are allowed to overflow, so the growth is computed modulo 2^256.
+        let fee_growth_0_delta = fee_growth_0.wrapping_sub(past_fee_growth_0);
+        let fee_growth_1_delta = fee_growth_1.wrapping_sub(past_fee_growth_1);
+
+        // Active tick range, i.e. the initializable ticks around the current tick.
+        let tick = i32::try_from(slot0.tick).unwrap();
+        let tick_spacing = i32::try_from(tick_spacing).unwrap();
+        let tick_lower = tick.div_euclid(tick_spacing) * tick_spacing;
+        let tick_upper = tick_lower + tick_spacing;
+
+        // Price of one whole token in base units of the numeraire.
+        let price = |token: Address| -> Result<(U256, U256), Status> {
+            let decimals = Contract::new(token, &env)
+                .call_builder(&IERC20::decimalsCall {})
+                .try_call()
+                .map_err(Status::reverted)?
+                ._0;
+            let unit = math::pow10(decimals)?;
+            if token == numeraire {
+                return Ok((unit, unit));
+            }
+            let path = pricing::find_route(&price_paths, token, numeraire)
+                .expect("missing price route for pool token");
+            let price = Contract::new(QUOTER_V2, &env)
+                .call_builder(&QuoterV2::quoteExactInputCall {
+                    path: path.encode(),
+                    amountIn: unit,
+                })
+                .try_call()
+                .map_err(Status::reverted)?
+                .amountOut;
+            Ok((price, unit))
+        };
+        let (price0, unit0) = price(token0)?;
+        let (price1, unit1) = price(token1)?;
+        let value = |amount0: U256, amount1: U256| -> math::Result<U256> {
+            math::add(
+                mul_div(amount0, price0, unit0, Rounding::Down)?,
+                mul_div(amount1, price1, unit1, Rounding::Down)?,
+            )
+        };
+
+        let liquidity_u256 = U256::from(liquidity);
+        let (amount0, amount1) = uniswap::amounts_for_liquidity(
+            U256::from(slot0.sqrtPriceX96),
+            uniswap::sqrt_ratio_at_tick(tick_lower)?,
+            uniswap::sqrt_ratio_at_tick(tick_upper)?,
+            liquidity_u256,
+        )?;
+        let (fees0, fees1) =
+            uniswap::fees_for_liquidity(fee_growth_0_delta, fee_growth_1_delta, liquidity_u256)?;
+        let active_liquidity_value = value(amount0, amount1)?;
+        let fees_value = value(fees0, fees1)?;
+        let fee_apr = uniswap::fee_apr(
+            fees_value,
+            active_liquidity_value,
+            timestamp - past_timestamp,
+        )?;
+        Ok((
+            liquidity,
+            tick,
+            tick_lower,
+            tick_upper,
+            fee_growth_0_delta,
+            fee_growth_1_delta,
+            active_liquidity_value,
+            fees_value,
+            fee_apr,
+        ))
+    };
+    let (
+        status,
+        (
+            liquidity,
+            tick,
+            tick_lower,
+            tick_upper,
+            fee_growth_0_delta,
+            fee_growth_1_delta,
+            active_liquidity_value,
+            fees_value,
+            fee_apr,
+        ),
+    ) = Status::split(outcome());
+
+    // Commit both blocks, so that the window of the APR can be validated.
+    let journal = Journal {
+        commitment: env.into_commitment(),
+        pastCommitment: past_env.into_commitment(),
+        status: status.into(),
+        pool,
+        numeraire,
+        liquidity,
+        tick: I24::try_from(tick).unwrap(),
+        tickLower: I24::try_from(tick_lower).unwrap(),
+        tickUpper: I24::try_from(tick_upper).unwrap(),
+        feeGrowthGlobal0Delta: fee_growth_0_delta,
+        feeGrowthGlobal1Delta: fee_growth_1_delta,
+        activeLiquidityValue: active_liquidity_value,
+        feesValue: fees_value,
+        feeApr: fee_apr,
+    };
+    env::commit_slice(
+        &journal.encode_with_header(ETH_MAINNET_CHAIN_SPEC.chain_id, guest_input.digest()),
+    );
+}
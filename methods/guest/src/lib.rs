@@ -0,0 +1,45 @@
+//! Helpers shared by the guest programs.
+
+use std::io::Read;
+
+use alloy_primitives::B256;
+use risc0_zkvm::{
+    guest::env,
+    serde::Deserializer,
+    sha::{Impl, Sha256},
+};
+use serde::de::DeserializeOwned;
+
+/// Input of a guest, read at once so that its digest can be committed in the journal header.
+pub struct Input {
+    words: &'static [u32],
+    digest: B256,
+}
+
+impl Input {
+    /// Reads the whole input written by the host.
+    pub fn read() -> Self {
+        let mut bytes = Vec::new();
+        env::stdin().read_to_end(&mut bytes).unwrap();
+        let digest = B256::from_slice(Impl::hash_bytes(&bytes).as_bytes());
+        let words: Vec<u32> = bytes
+            .chunks_exact(4)
+            .map(|word| u32::from_le_bytes(word.try_into().unwrap()))
+            .collect();
+        // The input lives as long as the guest, and is deserialized from in place.
+        Self {
+            words: words.leak(),
+            digest,
+        }
+    }
+
+    /// Reads the next value of the input, like `env::read`.
+    pub fn next<T: DeserializeOwned>(&mut self) -> T {
+        T::deserialize(&mut Deserializer::new(&mut self.words)).unwrap()
+    }
+
+    /// SHA-256 digest of the input, committed as the `inputHash` of the journal header.
+    pub fn digest(&self) -> B256 {
+        self.digest
+    }
+}